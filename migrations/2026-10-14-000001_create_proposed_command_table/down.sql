@@ -0,0 +1 @@
+DROP TABLE proposed_command;
@@ -0,0 +1,7 @@
+CREATE TABLE proposed_command (
+    id SERIAL PRIMARY KEY,
+    fk_issue INTEGER UNIQUE NOT NULL REFERENCES issue (id) ON DELETE CASCADE,
+    fk_initiator INTEGER NOT NULL REFERENCES githubuser (id),
+    fk_initiating_comment INTEGER NOT NULL REFERENCES issuecomment (id),
+    disposition VARCHAR NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE fcp_concern ADD COLUMN fk_directed_at INTEGER REFERENCES githubuser (id);
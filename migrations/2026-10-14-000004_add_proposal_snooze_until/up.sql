@@ -0,0 +1 @@
+ALTER TABLE fcp_proposal ADD COLUMN snooze_until TIMESTAMP;
@@ -0,0 +1,5 @@
+ALTER TABLE fcp_proposal ADD COLUMN fcp_closed BOOLEAN NOT NULL DEFAULT false;
+
+UPDATE fcp_proposal SET fcp_closed = (state = 'closed');
+
+ALTER TABLE fcp_proposal DROP COLUMN state;
@@ -0,0 +1,10 @@
+-- Breaking: fcp_closed is dropped in favour of state, see CHANGELOG.md.
+ALTER TABLE fcp_proposal ADD COLUMN state VARCHAR NOT NULL DEFAULT 'pending';
+
+UPDATE fcp_proposal SET state = CASE
+    WHEN fcp_closed THEN 'closed'
+    WHEN fcp_start IS NOT NULL THEN 'in-fcp'
+    ELSE 'pending'
+END;
+
+ALTER TABLE fcp_proposal DROP COLUMN fcp_closed;
@@ -0,0 +1 @@
+ALTER TABLE fcp_proposal ADD COLUMN almost_ready_notified BOOLEAN NOT NULL DEFAULT false;
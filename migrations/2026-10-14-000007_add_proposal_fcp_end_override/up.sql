@@ -0,0 +1 @@
+ALTER TABLE fcp_proposal ADD COLUMN fcp_end_override TIMESTAMP;
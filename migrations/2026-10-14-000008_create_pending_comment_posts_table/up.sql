@@ -0,0 +1,8 @@
+CREATE TABLE pending_comment_posts (
+    id SERIAL PRIMARY KEY,
+    fk_proposal INTEGER NOT NULL REFERENCES fcp_proposal (id) ON DELETE CASCADE,
+    fk_target_comment INTEGER UNIQUE NOT NULL REFERENCES issuecomment (id) ON DELETE CASCADE,
+    body VARCHAR NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    retry_after TIMESTAMP NOT NULL
+);
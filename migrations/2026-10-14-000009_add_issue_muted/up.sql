@@ -0,0 +1 @@
+ALTER TABLE issue ADD COLUMN muted BOOLEAN NOT NULL DEFAULT FALSE;
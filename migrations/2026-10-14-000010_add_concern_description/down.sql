@@ -0,0 +1 @@
+ALTER TABLE fcp_concern DROP COLUMN description;
@@ -0,0 +1 @@
+ALTER TABLE fcp_concern ADD COLUMN description VARCHAR;
@@ -0,0 +1,4 @@
+CREATE TABLE repo_scrape_cursors (
+    repository VARCHAR PRIMARY KEY,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,9 @@
+CREATE TABLE archived_proposals (
+    id SERIAL PRIMARY KEY,
+    fk_issue INTEGER UNIQUE NOT NULL REFERENCES issue (id) ON DELETE CASCADE,
+    fk_initiator INTEGER NOT NULL REFERENCES githubuser (id),
+    fk_initiating_comment INTEGER NOT NULL REFERENCES issuecomment (id),
+    disposition VARCHAR NOT NULL,
+    fk_bot_tracking_comment INTEGER NOT NULL REFERENCES issuecomment (id),
+    archived_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE fcp_concern DROP COLUMN fk_overridden_by;
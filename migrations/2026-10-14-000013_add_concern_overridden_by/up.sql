@@ -0,0 +1 @@
+ALTER TABLE fcp_concern ADD COLUMN fk_overridden_by INTEGER REFERENCES githubuser (id);
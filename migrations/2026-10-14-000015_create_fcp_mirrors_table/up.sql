@@ -0,0 +1,7 @@
+CREATE TABLE fcp_mirrors (
+    id SERIAL PRIMARY KEY,
+    fk_proposal INTEGER NOT NULL REFERENCES fcp_proposal (id) ON DELETE CASCADE,
+    fk_issue INTEGER NOT NULL REFERENCES issue (id) ON DELETE CASCADE,
+    fk_mirror_comment INTEGER NOT NULL REFERENCES issuecomment (id),
+    UNIQUE (fk_proposal, fk_issue)
+);
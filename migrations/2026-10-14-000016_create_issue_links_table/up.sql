@@ -0,0 +1,9 @@
+CREATE TABLE issue_links (
+    id SERIAL PRIMARY KEY,
+    fk_issue INTEGER NOT NULL REFERENCES issue (id) ON DELETE CASCADE,
+    linked_repository VARCHAR NOT NULL,
+    linked_number INTEGER NOT NULL,
+    linked_is_pull_request BOOLEAN NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE (fk_issue, linked_repository, linked_number)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE issue_reactions (
+    fk_issue INTEGER PRIMARY KEY REFERENCES issue (id) ON DELETE CASCADE,
+    thumbs_up INTEGER NOT NULL,
+    thumbs_down INTEGER NOT NULL,
+    hooray INTEGER NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
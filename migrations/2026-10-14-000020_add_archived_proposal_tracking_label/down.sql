@@ -0,0 +1 @@
+ALTER TABLE archived_proposals DROP COLUMN tracking_label;
@@ -0,0 +1 @@
+ALTER TABLE archived_proposals ADD COLUMN tracking_label VARCHAR;
@@ -9,12 +9,20 @@
 //!
 //! * `DATABASE_URL`: postgres database URL
 //! * `DATABASE_POOL_SIZE`: number of connections to maintain in the pool
+//! * `DATABASE_POOL_TIMEOUT_SECS`: how long to wait for a connection from the pool before giving
+//!   up, when every connection is in use (defaults to 10)
 //! * `GITHUB_ACCESS_TOKEN`: your access token from GitHub. See
 //!   [this page](https://help.github.com/articles/creating-an-access-token-for-command-line-use/)
 //!   for more information. You shouldn't need to check any of the boxes for granting scopes when
-//!   creating it.
+//!   creating it. This can be left empty when authenticating as a GitHub App.
 //! * `GITHUB_USER_AGENT`: the UA string to send to GitHub (they request that you send your GitHub
-//!   username or the app name you registered for the client ID)
+//!   username or the app name you registered for the client ID). This can't be empty.
+//! * `GITHUB_ACCEPT`: the media type to request from the GitHub API (defaults to
+//!   `application/vnd.github.v3`)
+//! * `GITHUB_REACTIONS_ACCEPT`: the media type to request from the reactions API (defaults to
+//!   `application/vnd.github.squirrel-girl-preview+json`)
+//! * `GITHUB_TIMELINE_ACCEPT`: the media type to request from the issue timeline API (defaults to
+//!   `application/vnd.github.mockingbird-preview+json`)
 //! * `GITHUB_WEBHOOK_SECRETS`: a comma-delimited string of the secrets used for any ingestion
 //!   webhooks. The webhook handler will attempt to validate any POST'd webhook against each secret
 //!   until it either finds a matching one or runs out.
@@ -23,12 +31,102 @@
 //!   not defined, logging will default to `info!()` and above.
 //! * `GITHUB_SCRAPE_INTERVAL`: time (in minutes) to wait in between GitHub scrapes (scraping is
 //!   disabled if this environment variable is omitted)
+//! * `GITHUB_SCRAPE_WORKERS`: number of repositories to scrape concurrently (defaults to 1)
+//! * `GITHUB_SCRAPE_JITTER_PERCENT`: how far (as a percentage, up to 50) the wait between
+//!   scrapes may randomly stray from `GITHUB_SCRAPE_INTERVAL`, so that several instances don't
+//!   scrape GitHub in lockstep (defaults to 10)
+//! * `GITHUB_SCRAPE_TIMELINES`: whether scrapes also fetch the timeline of each updated issue, to
+//!   record which other issues and pull requests reference it -- either `true` or `false`
+//!   (defaults to `false`, as it takes a request per issue)
+//! * `GITHUB_SCRAPE_REACTIONS`: whether scrapes also count the :+1:, :-1: and :tada:
+//!   reactions on each updated open issue, for ranking them at `/hot-issues` -- either `true`
+//!   or `false` (defaults to `false`, as it takes a request per issue)
+//! * `GITHUB_USERS`: a comma-delimited list of GitHub users whose own repositories are scraped
+//!   along with those of the rust-lang organizations
+//! * `BACKFILL_SINCE`: a date (e.g. `2018-01-31`) from which `rfcbot --backfill` scrapes all
+//!   issues and comments, for new deployments or to fill a gap in the history
+//! * `CORS_ALLOWED_ORIGINS`: a comma-delimited list of origins which may make cross-origin
+//!   requests to the read-only endpoints, or `*` to allow any origin (CORS headers are not sent
+//!   if this environment variable is omitted)
+//! * `LOG_REQUESTS`: whether the web server logs the method, path, status and duration of each
+//!   request it handles -- either `true` or `false` (defaults to `false`)
+//! * `FCP_DIGEST_ISSUE`: an issue (e.g. `rust-lang/rfcbot-rs#1`) on which rfcbot will keep a
+//!   comment up to date listing every running final comment period (no digest is kept if this
+//!   environment variable is omitted)
+//! * `GITHUB_APP_ID`, `GITHUB_APP_INSTALLATION_ID`, `GITHUB_APP_PRIVATE_KEY_PATH`: if all three
+//!   are set, rfcbot authenticates as the installation of a GitHub App instead of using
+//!   `GITHUB_ACCESS_TOKEN`. The private key is the PEM file downloaded from the App's settings.
+//! * `FCP_DURATION_DAYS`: how many days a final comment period lasts, either as a single number
+//!   or per disposition, e.g. `merge=10,close=7,postpone=7` (dispositions which aren't listed,
+//!   and the whole variable if omitted, default to 10 days)
+//! * `REPO_FCP_DURATION_DAYS`: a semicolon-delimited list of repositories with final comment
+//!   periods of their own length, given in the same form as `FCP_DURATION_DAYS`, e.g.
+//!   `rust-lang/rfcs:merge=14;rust-lang/cargo:7` (dispositions which aren't listed, and
+//!   repositories which aren't listed at all, use `FCP_DURATION_DAYS`)
+//! * `PRIVILEGED_COMMANDS`: a comma-delimited list of commands (e.g. `cancel,waive`) which only
+//!   the users in `RFCBOT_ADMINS` may run (every command but `fcp-end-now` and `reviewed-for`,
+//!   which are always privileged, is open to subteam members if omitted)
+//! * `RFCBOT_ADMINS`: a comma-delimited list of GitHub logins allowed to run privileged commands
+//!   and to resolve other people's concerns
+//! * `ADMIN_TOKEN`: a secret which must be sent as `Authorization: token <ADMIN_TOKEN>` to use
+//!   the administrative endpoints, like `POST /fcp/reconcile/<id>`,
+//!   `POST /fcp/force-close/<id>` and `POST /preview-command` (they're disabled if this
+//!   environment variable is omitted)
+//! * `IGNORED_AUTHORS`: a comma-delimited list of GitHub logins, e.g. other bots, whose comments
+//!   are never parsed for commands
+//! * `RFCBOT_LOGIN`: the GitHub login rfcbot posts as, whose comments are also ignored (defaults
+//!   to `rfcbot`)
+//! * `MIN_DISTINCT_REVIEWERS`: number of team members other than the proposer who must have
+//!   reviewed a proposal, or had their review waived, before its final comment period can start
+//!   (defaults to 0)
+//! * `FCP_REQUIRES_ALL_REVIEWS`: whether a proposal waits for a majority of its reviews, and all
+//!   but two of them, before its final comment period starts (defaults to `true`). If `false`,
+//!   only unresolved concerns and `MIN_DISTINCT_REVIEWERS` hold it back
+//! * `COMMENT_FOOTERS_PATH`: a JSON file mapping repositories (e.g. `rust-lang/rfcs`) to
+//!   markdown appended to rfcbot's comments there, such as links to contribution guides. A
+//!   `default` entry is used for repositories which aren't listed (no footers are added if this
+//!   environment variable is omitted)
+//! * `COMMENT_TEMPLATES_PATH`: a JSON file mapping kinds of comment (e.g. `fcp_proposed`) to
+//!   templates replacing rfcbot's default wording. `{placeholder}`s are filled in when the
+//!   comment is posted; every template can use `{default}` for the built-in text, and the
+//!   `fcp_proposed` template must keep the review checklist with `{reviewers}` or `{default}`
+//!   (the built-in wording is used if this environment variable is omitted)
+//! * `NOTIFY_WEBHOOK_URL`: a URL which rfcbot will `POST` a JSON summary to whenever a proposal
+//!   enters or leaves its final comment period, e.g. for a chat integration; it's sent in the
+//!   background and given 10 seconds to answer (nothing is sent if this environment variable is
+//!   omitted)
+//! * `ERROR_REPORT_URL`: a URL which rfcbot will `POST` a JSON description of an error to
+//!   whenever processing commands or evaluating proposals fails, e.g. for an error tracker;
+//!   like `NOTIFY_WEBHOOK_URL` it's sent in the background with a 10 second timeout (errors are
+//!   only logged if this environment variable is omitted)
+//! * `RESET_REVIEWS_ON_PUSH`: whether pushing new commits to a pull request marks the reviews
+//!   of its pending proposal as outstanding again -- either `true` or `false` (defaults to
+//!   `false`)
+//! * `AUTO_MERGE_ON_FCP`: whether rfcbot merges a pull request itself once its final comment
+//!   period to merge has finished -- either `true` or `false` (defaults to `false`)
+//! * `STATUS_EMOJI`: whether the status comment of a proposal starts with an emoji showing its
+//!   state, to make it easier to pick out among many -- either `true` or `false` (defaults to
+//!   `false`)
+//! * `REVIEW_VIA_REACTION`: whether a reviewer's :+1: reaction on a proposal's status comment
+//!   counts as their review, as well as checking their box -- either `true` or `false` (defaults
+//!   to `false`)
+//! * `TRACKING_LABEL`: a label put on issues while they have an open proposal, e.g. to show them
+//!   on a project board (`@rfcbot track` picks a different one for a proposal; no label is added
+//!   if this environment variable is omitted)
+//! * `REQUIRE_LABEL_FOR_FCP`: a label an issue must already have for rfcbot to accept a proposal
+//!   on it, e.g. `T-lang` (proposals are accepted on any issue if this environment variable is
+//!   omitted)
+//! * `MAX_CONCERNS`: the most unresolved concerns a proposal can have, after which rfcbot refuses
+//!   to add more (there's no limit if this environment variable is omitted)
 //! * `POST_COMMENTS`: whether to post RFC bot comments on issues -- either `true` or `false`. Be
 //!   very careful setting to true when testing -- it will post comments using whatever account is
 //!   associated with the GitHub API key you provide.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
+use std::fs;
+
+use chrono::{DateTime, NaiveDate, Utc};
 
 pub const RFC_BOT_MENTION: &str = "@rfcbot";
 pub const GH_ORGS: [&str; 3] = ["rust-lang", "rust-lang-nursery", "rust-lang-deprecated"];
@@ -52,28 +150,183 @@ lazy_static! {
 pub struct Config {
     pub db_url: String,
     pub db_pool_size: u32,
+    pub db_pool_timeout_secs: u64,
     pub github_access_token: String,
     pub github_user_agent: String,
+    pub github_accept: String,
+    pub github_reactions_accept: String,
+    pub github_timeline_accept: String,
     pub github_webhook_secrets: Vec<String>,
     pub github_interval_mins: Option<u64>,
+    pub github_scrape_workers: usize,
+    pub github_scrape_jitter_percent: u32,
+    pub github_scrape_timelines: bool,
+    pub github_scrape_reactions: bool,
+    pub github_users: Vec<String>,
+    pub backfill_since: Option<DateTime<Utc>>,
+    pub github_app: Option<GitHubAppConfig>,
+    pub cors_allowed_origins: Vec<String>,
+    pub log_requests: bool,
+    pub fcp_digest_issue: Option<(String, i32)>,
+    pub fcp_duration_days: FcpDurations,
+    pub repo_fcp_duration_days: BTreeMap<String, FcpDurations>,
+    pub min_distinct_reviewers: usize,
+    pub fcp_requires_all_reviews: bool,
+    pub privileged_commands: BTreeSet<String>,
+    pub admins: BTreeSet<String>,
+    pub admin_token: Option<String>,
+    pub ignored_authors: BTreeSet<String>,
+    pub repo_comment_footers: BTreeMap<String, String>,
+    pub comment_templates: BTreeMap<String, String>,
+    pub reset_reviews_on_push: bool,
+    pub notify_webhook_url: Option<String>,
+    pub error_report_url: Option<String>,
+    pub auto_merge_on_fcp: bool,
+    pub status_emoji: bool,
+    pub review_via_reaction: bool,
+    pub tracking_label: Option<String>,
+    pub require_label_for_fcp: Option<String>,
+    pub max_concerns: Option<usize>,
     pub post_comments: bool,
 }
 
 impl Config {
-    pub fn check(&self) -> bool {
-        !self.db_url.is_empty()
-            && !self.github_access_token.is_empty()
-            && !self.github_user_agent.is_empty()
+    /// Variables which were set, but left empty when we need a value.
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let mut missing = vec![];
+        if self.db_url.trim().is_empty() {
+            missing.push(DB_URL);
+        }
+        if self.db_pool_size == 0 {
+            missing.push(DB_POOL_SIZE);
+        }
+        if self.github_access_token.trim().is_empty() && self.github_app.is_none() {
+            missing.push(GITHUB_TOKEN);
+        }
+        if self.github_user_agent.trim().is_empty() {
+            missing.push(GITHUB_UA);
+        }
+        if self.github_webhook_secrets.iter().all(|s| s.is_empty()) {
+            missing.push(GITHUB_WEBHOOK_SECRETS);
+        }
+        missing
+    }
+
+    /// How long final comment periods last in a repository.
+    pub fn fcp_durations_for(&self, repo: &str) -> &FcpDurations {
+        self.repo_fcp_duration_days
+            .get(repo)
+            .unwrap_or(&self.fcp_duration_days)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FcpDurations {
+    pub merge: i64,
+    pub close: i64,
+    pub postpone: i64,
+}
+
+impl Default for FcpDurations {
+    fn default() -> Self {
+        FcpDurations {
+            merge: DEFAULT_FCP_DURATION_DAYS,
+            close: DEFAULT_FCP_DURATION_DAYS,
+            postpone: DEFAULT_FCP_DURATION_DAYS,
+        }
     }
 }
 
+#[derive(Debug)]
+pub struct GitHubAppConfig {
+    pub app_id: u64,
+    pub installation_id: u64,
+    pub private_key: Vec<u8>,
+}
+
 const DB_URL: &str = "DATABASE_URL";
 const DB_POOL_SIZE: &str = "DATABASE_POOL_SIZE";
+const DB_POOL_TIMEOUT: &str = "DATABASE_POOL_TIMEOUT_SECS";
 const GITHUB_TOKEN: &str = "GITHUB_ACCESS_TOKEN";
 const GITHUB_WEBHOOK_SECRETS: &str = "GITHUB_WEBHOOK_SECRETS";
 const GITHUB_UA: &str = "GITHUB_USER_AGENT";
+const GITHUB_ACCEPT: &str = "GITHUB_ACCEPT";
+const GITHUB_REACTIONS_ACCEPT: &str = "GITHUB_REACTIONS_ACCEPT";
+const GITHUB_TIMELINE_ACCEPT: &str = "GITHUB_TIMELINE_ACCEPT";
 const GITHUB_INTERVAL: &str = "GITHUB_SCRAPE_INTERVAL";
+const GITHUB_WORKERS: &str = "GITHUB_SCRAPE_WORKERS";
+const GITHUB_JITTER: &str = "GITHUB_SCRAPE_JITTER_PERCENT";
+const GITHUB_TIMELINES: &str = "GITHUB_SCRAPE_TIMELINES";
+const GITHUB_REACTIONS: &str = "GITHUB_SCRAPE_REACTIONS";
+const GITHUB_USERS: &str = "GITHUB_USERS";
+const BACKFILL_SINCE: &str = "BACKFILL_SINCE";
+const GITHUB_APP_ID: &str = "GITHUB_APP_ID";
+const GITHUB_APP_INSTALLATION_ID: &str = "GITHUB_APP_INSTALLATION_ID";
+const GITHUB_APP_PRIVATE_KEY_PATH: &str = "GITHUB_APP_PRIVATE_KEY_PATH";
 const POST_COMMENTS: &str = "POST_COMMENTS";
+const CORS_ALLOWED_ORIGINS: &str = "CORS_ALLOWED_ORIGINS";
+const LOG_REQUESTS: &str = "LOG_REQUESTS";
+const FCP_DIGEST_ISSUE: &str = "FCP_DIGEST_ISSUE";
+const MIN_DISTINCT_REVIEWERS: &str = "MIN_DISTINCT_REVIEWERS";
+const FCP_REQUIRES_ALL_REVIEWS: &str = "FCP_REQUIRES_ALL_REVIEWS";
+const FCP_DURATION_DAYS: &str = "FCP_DURATION_DAYS";
+const REPO_FCP_DURATION_DAYS: &str = "REPO_FCP_DURATION_DAYS";
+const PRIVILEGED_COMMANDS: &str = "PRIVILEGED_COMMANDS";
+const RFCBOT_ADMINS: &str = "RFCBOT_ADMINS";
+const ADMIN_TOKEN: &str = "ADMIN_TOKEN";
+const IGNORED_AUTHORS: &str = "IGNORED_AUTHORS";
+const RFCBOT_LOGIN: &str = "RFCBOT_LOGIN";
+const COMMENT_FOOTERS_PATH: &str = "COMMENT_FOOTERS_PATH";
+const COMMENT_TEMPLATES_PATH: &str = "COMMENT_TEMPLATES_PATH";
+const RESET_REVIEWS_ON_PUSH: &str = "RESET_REVIEWS_ON_PUSH";
+const NOTIFY_WEBHOOK_URL: &str = "NOTIFY_WEBHOOK_URL";
+const ERROR_REPORT_URL: &str = "ERROR_REPORT_URL";
+const AUTO_MERGE_ON_FCP: &str = "AUTO_MERGE_ON_FCP";
+const STATUS_EMOJI: &str = "STATUS_EMOJI";
+const REVIEW_VIA_REACTION: &str = "REVIEW_VIA_REACTION";
+const TRACKING_LABEL: &str = "TRACKING_LABEL";
+const REQUIRE_LABEL_FOR_FCP: &str = "REQUIRE_LABEL_FOR_FCP";
+const MAX_CONCERNS: &str = "MAX_CONCERNS";
+
+/// The tables created by the migrations, which rfcbot expects to find in the database.
+const EXPECTED_TABLES: [&str; 21] = [
+    "archived_proposals",
+    "fcp_concern",
+    "fcp_mirrors",
+    "fcp_proposal",
+    "fcp_review_request",
+    "githubsync",
+    "githubuser",
+    "issue",
+    "issue_links",
+    "issue_reactions",
+    "issuecomment",
+    "memberships",
+    "milestone",
+    "pending_comment_posts",
+    "poll",
+    "poll_response_request",
+    "proposed_command",
+    "pullrequest",
+    "repo_scrape_cursors",
+    "rfc_feedback_request",
+    "teams",
+];
+
+/// The entry in `COMMENT_FOOTERS_PATH` used for repositories without their own footer.
+pub const DEFAULT_COMMENT_FOOTER: &str = "default";
+
+const DEFAULT_FCP_DURATION_DAYS: i64 = 10;
+
+const DEFAULT_GITHUB_JITTER_PERCENT: u32 = 10;
+
+const DEFAULT_GITHUB_ACCEPT: &str = "application/vnd.github.v3";
+
+/// The reactions API is still a preview, so it needs its own media type.
+const DEFAULT_REACTIONS_ACCEPT: &str = "application/vnd.github.squirrel-girl-preview+json";
+
+/// As is the timeline API.
+const DEFAULT_TIMELINE_ACCEPT: &str = "application/vnd.github.mockingbird-preview+json";
 
 // this is complex, but we'll shortly need a lot more config items
 // so checking them automagically seems like a nice solution
@@ -102,9 +355,26 @@ pub fn init() -> Result<Config, Vec<&'static str>> {
         let db_url = vars.remove(DB_URL).unwrap();
         let db_pool_size = vars.remove(DB_POOL_SIZE).unwrap().parse::<u32>();
         let db_pool_size = ok_or!(db_pool_size, throw!(vec![DB_POOL_SIZE]));
+        let db_pool_timeout_secs = if let Ok(val) = env::var(DB_POOL_TIMEOUT) {
+            let secs = val.parse::<u64>().ok().filter(|&s| s > 0);
+            ok_or!(secs.ok_or(()), throw!(vec![DB_POOL_TIMEOUT]))
+        } else {
+            10
+        };
 
         let gh_token = vars.remove(GITHUB_TOKEN).unwrap();
         let gh_ua = vars.remove(GITHUB_UA).unwrap();
+        // GitHub rejects requests without a user agent
+        if gh_ua.trim().is_empty() {
+            throw!(vec![GITHUB_UA]);
+        }
+
+        let gh_accept =
+            env::var(GITHUB_ACCEPT).unwrap_or_else(|_| DEFAULT_GITHUB_ACCEPT.to_string());
+        let gh_reactions_accept = env::var(GITHUB_REACTIONS_ACCEPT)
+            .unwrap_or_else(|_| DEFAULT_REACTIONS_ACCEPT.to_string());
+        let gh_timeline_accept = env::var(GITHUB_TIMELINE_ACCEPT)
+            .unwrap_or_else(|_| DEFAULT_TIMELINE_ACCEPT.to_string());
 
         let gh_interval = if let Ok(val) = env::var(GITHUB_INTERVAL) {
             Some(ok_or!(val.parse::<u64>(), throw!(vec![GITHUB_INTERVAL])))
@@ -112,19 +382,213 @@ pub fn init() -> Result<Config, Vec<&'static str>> {
             None
         };
 
+        let gh_workers = if let Ok(val) = env::var(GITHUB_WORKERS) {
+            ok_or!(val.parse::<usize>(), throw!(vec![GITHUB_WORKERS]))
+        } else {
+            1
+        };
+
+        let gh_jitter = if let Ok(val) = env::var(GITHUB_JITTER) {
+            let percent = val
+                .parse::<u32>()
+                .ok()
+                .filter(|&p| p <= crate::utils::MAX_JITTER_PERCENT);
+            ok_or!(percent.ok_or(()), throw!(vec![GITHUB_JITTER]))
+        } else {
+            DEFAULT_GITHUB_JITTER_PERCENT
+        };
+
+        let gh_timelines = if let Ok(val) = env::var(GITHUB_TIMELINES) {
+            ok_or!(val.parse::<bool>(), throw!(vec![GITHUB_TIMELINES]))
+        } else {
+            false
+        };
+
+        let gh_reactions = if let Ok(val) = env::var(GITHUB_REACTIONS) {
+            ok_or!(val.parse::<bool>(), throw!(vec![GITHUB_REACTIONS]))
+        } else {
+            false
+        };
+
+        let backfill_since = if let Ok(val) = env::var(BACKFILL_SINCE) {
+            let date = NaiveDate::parse_from_str(val.trim(), "%Y-%m-%d");
+            let date = ok_or!(date, throw!(vec![BACKFILL_SINCE]));
+            Some(DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+        } else {
+            None
+        };
+
+        let github_app = github_app()?;
+
         let post_comments = vars.remove(POST_COMMENTS).unwrap().parse::<bool>();
         let post_comments = ok_or!(post_comments, throw!(vec![POST_COMMENTS]));
 
         let webhook_secrets = vars.remove(GITHUB_WEBHOOK_SECRETS).unwrap();
         let webhook_secrets = webhook_secrets.split(',').map(String::from).collect();
 
+        let github_users = comma_list(GITHUB_USERS);
+        let cors_allowed_origins = comma_list(CORS_ALLOWED_ORIGINS);
+
+        let log_requests = if let Ok(val) = env::var(LOG_REQUESTS) {
+            ok_or!(val.parse::<bool>(), throw!(vec![LOG_REQUESTS]))
+        } else {
+            false
+        };
+        let privileged_commands = comma_list(PRIVILEGED_COMMANDS);
+        let admins = comma_list(RFCBOT_ADMINS);
+        let admin_token = env::var(ADMIN_TOKEN).ok().filter(|token| !token.is_empty());
+
+        // rfcbot shouldn't act on its own comments either
+        let mut ignored_authors: BTreeSet<String> = comma_list(IGNORED_AUTHORS);
+        ignored_authors.insert(env::var(RFCBOT_LOGIN).unwrap_or_else(|_| "rfcbot".to_string()));
+
+        let fcp_digest_issue = if let Ok(val) = env::var(FCP_DIGEST_ISSUE) {
+            let mut parts = val.splitn(2, '#');
+            let repo = parts.next().unwrap_or_default().to_string();
+            let number = parts.next().map(str::parse::<i32>);
+            match number {
+                Some(Ok(number)) if !repo.is_empty() => Some((repo, number)),
+                _ => throw!(vec![FCP_DIGEST_ISSUE]),
+            }
+        } else {
+            None
+        };
+
+        let fcp_duration_days = if let Ok(val) = env::var(FCP_DURATION_DAYS) {
+            ok_or!(
+                parse_fcp_durations(&val, &FcpDurations::default()).ok_or(()),
+                throw!(vec![FCP_DURATION_DAYS])
+            )
+        } else {
+            FcpDurations::default()
+        };
+
+        let repo_fcp_duration_days = if let Ok(val) = env::var(REPO_FCP_DURATION_DAYS) {
+            ok_or!(
+                parse_repo_fcp_durations(&val, &fcp_duration_days).ok_or(()),
+                throw!(vec![REPO_FCP_DURATION_DAYS])
+            )
+        } else {
+            BTreeMap::new()
+        };
+
+        let min_distinct_reviewers = if let Ok(val) = env::var(MIN_DISTINCT_REVIEWERS) {
+            ok_or!(val.parse::<usize>(), throw!(vec![MIN_DISTINCT_REVIEWERS]))
+        } else {
+            0
+        };
+
+        let fcp_requires_all_reviews = if let Ok(val) = env::var(FCP_REQUIRES_ALL_REVIEWS) {
+            ok_or!(val.parse::<bool>(), throw!(vec![FCP_REQUIRES_ALL_REVIEWS]))
+        } else {
+            true
+        };
+
+        let repo_comment_footers = if let Ok(path) = env::var(COMMENT_FOOTERS_PATH) {
+            let footers = fs::read(path)
+                .ok()
+                .and_then(|file| serde_json::from_slice(&file).ok());
+            ok_or!(footers.ok_or(()), throw!(vec![COMMENT_FOOTERS_PATH]))
+        } else {
+            BTreeMap::new()
+        };
+
+        let comment_templates = if let Ok(path) = env::var(COMMENT_TEMPLATES_PATH) {
+            let templates = fs::read(path)
+                .ok()
+                .and_then(|file| serde_json::from_slice(&file).ok())
+                .filter(keeps_review_boxes);
+            ok_or!(templates.ok_or(()), throw!(vec![COMMENT_TEMPLATES_PATH]))
+        } else {
+            BTreeMap::new()
+        };
+
+        let reset_reviews_on_push = if let Ok(val) = env::var(RESET_REVIEWS_ON_PUSH) {
+            ok_or!(val.parse::<bool>(), throw!(vec![RESET_REVIEWS_ON_PUSH]))
+        } else {
+            false
+        };
+
+        let notify_webhook_url = env::var(NOTIFY_WEBHOOK_URL)
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+
+        let error_report_url = env::var(ERROR_REPORT_URL)
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+
+        let auto_merge_on_fcp = if let Ok(val) = env::var(AUTO_MERGE_ON_FCP) {
+            ok_or!(val.parse::<bool>(), throw!(vec![AUTO_MERGE_ON_FCP]))
+        } else {
+            false
+        };
+
+        let status_emoji = if let Ok(val) = env::var(STATUS_EMOJI) {
+            ok_or!(val.parse::<bool>(), throw!(vec![STATUS_EMOJI]))
+        } else {
+            false
+        };
+
+        let review_via_reaction = if let Ok(val) = env::var(REVIEW_VIA_REACTION) {
+            ok_or!(val.parse::<bool>(), throw!(vec![REVIEW_VIA_REACTION]))
+        } else {
+            false
+        };
+
+        let tracking_label = env::var(TRACKING_LABEL)
+            .ok()
+            .filter(|label| !label.trim().is_empty());
+
+        let require_label_for_fcp = env::var(REQUIRE_LABEL_FOR_FCP)
+            .ok()
+            .filter(|label| !label.trim().is_empty());
+
+        let max_concerns = if let Ok(val) = env::var(MAX_CONCERNS) {
+            Some(ok_or!(val.parse::<usize>(), throw!(vec![MAX_CONCERNS])))
+        } else {
+            None
+        };
+
         Ok(Config {
             db_url,
             db_pool_size,
+            db_pool_timeout_secs,
             github_access_token: gh_token,
             github_user_agent: gh_ua,
+            github_accept: gh_accept,
+            github_reactions_accept: gh_reactions_accept,
+            github_timeline_accept: gh_timeline_accept,
             github_webhook_secrets: webhook_secrets,
             github_interval_mins: gh_interval,
+            github_scrape_workers: gh_workers,
+            github_scrape_jitter_percent: gh_jitter,
+            github_scrape_timelines: gh_timelines,
+            github_scrape_reactions: gh_reactions,
+            github_users,
+            backfill_since,
+            github_app,
+            cors_allowed_origins,
+            log_requests,
+            fcp_digest_issue,
+            fcp_duration_days,
+            repo_fcp_duration_days,
+            min_distinct_reviewers,
+            fcp_requires_all_reviews,
+            privileged_commands,
+            admins,
+            admin_token,
+            ignored_authors,
+            repo_comment_footers,
+            comment_templates,
+            reset_reviews_on_push,
+            notify_webhook_url,
+            error_report_url,
+            auto_merge_on_fcp,
+            status_emoji,
+            review_via_reaction,
+            tracking_label,
+            require_label_for_fcp,
+            max_concerns,
             post_comments,
         })
     } else {
@@ -135,3 +599,334 @@ pub fn init() -> Result<Config, Vec<&'static str>> {
             .collect())
     }
 }
+
+/// Checks everything rfcbot needs before it starts work, so that a misconfigured deployment
+/// stops with an explanation instead of panicking partway through a scrape.
+pub fn validate() -> Result<(), String> {
+    use diesel::prelude::*;
+
+    let config =
+        init().map_err(|vars| format!("Missing or malformed environment variables: {:?}", vars))?;
+    let missing = config.missing_fields();
+    if !missing.is_empty() {
+        throw!(format!(
+            "Environment variables can't be empty: {:?}",
+            missing
+        ));
+    }
+
+    let conn = diesel::pg::PgConnection::establish(&config.db_url)
+        .map_err(|why| format!("Unable to connect to the database: {}", why))?;
+    let existing = existing_tables(&conn)
+        .map_err(|why| format!("Unable to list the database's tables: {}", why))?;
+    let missing = missing_tables(&existing);
+    if !missing.is_empty() {
+        throw!(format!(
+            "Database tables are missing, have the migrations been run? {:?}",
+            missing
+        ));
+    }
+
+    ok_or!(crate::github::GH.check_token(), why => throw!(format!(
+        "Unable to authenticate with GitHub, check the access token or app credentials: {:?}",
+        why
+    )));
+
+    Ok(())
+}
+
+fn existing_tables(conn: &diesel::pg::PgConnection) -> diesel::QueryResult<BTreeSet<String>> {
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    #[derive(QueryableByName)]
+    struct Table {
+        #[sql_type = "Text"]
+        name: String,
+    }
+
+    let tables = diesel::sql_query(
+        "SELECT table_name::text AS name FROM information_schema.tables \
+         WHERE table_schema = current_schema()",
+    )
+    .load::<Table>(conn)?;
+    Ok(tables.into_iter().map(|t| t.name).collect())
+}
+
+fn missing_tables(existing: &BTreeSet<String>) -> Vec<&'static str> {
+    EXPECTED_TABLES
+        .iter()
+        .cloned()
+        .filter(|table| !existing.contains(*table))
+        .collect()
+}
+
+/// Reads an optional comma-delimited environment variable, ignoring empty entries.
+fn comma_list<C: std::iter::FromIterator<String> + Default>(var: &str) -> C {
+    env::var(var)
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// GitHub App authentication is optional, but if any of its variables are set they all need to be.
+fn github_app() -> Result<Option<GitHubAppConfig>, Vec<&'static str>> {
+    let app_vars = [
+        GITHUB_APP_ID,
+        GITHUB_APP_INSTALLATION_ID,
+        GITHUB_APP_PRIVATE_KEY_PATH,
+    ];
+    let values = app_vars
+        .iter()
+        .map(|var| env::var(var).ok())
+        .collect::<Vec<_>>();
+
+    if values.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+
+    let missing = app_vars
+        .iter()
+        .zip(&values)
+        .filter(|&(_, v)| v.is_none())
+        .map(|(&k, _)| k)
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        throw!(missing);
+    }
+
+    let app_id = values[0].as_ref().unwrap().parse::<u64>();
+    let app_id = ok_or!(app_id, throw!(vec![GITHUB_APP_ID]));
+    let installation_id = values[1].as_ref().unwrap().parse::<u64>();
+    let installation_id = ok_or!(installation_id, throw!(vec![GITHUB_APP_INSTALLATION_ID]));
+    let private_key = fs::read(values[2].as_ref().unwrap());
+    let private_key = ok_or!(private_key, throw!(vec![GITHUB_APP_PRIVATE_KEY_PATH]));
+
+    Ok(Some(GitHubAppConfig {
+        app_id,
+        installation_id,
+        private_key,
+    }))
+}
+
+/// Parses either a single duration for every disposition, or a comma-delimited list of
+/// `disposition=days` pairs, taking any dispositions which aren't listed from `defaults`.
+fn parse_fcp_durations(val: &str, defaults: &FcpDurations) -> Option<FcpDurations> {
+    let positive = |days: &str| days.trim().parse::<i64>().ok().filter(|&d| d > 0);
+
+    if let Some(days) = positive(val) {
+        return Some(FcpDurations {
+            merge: days,
+            close: days,
+            postpone: days,
+        });
+    }
+
+    let mut durations = defaults.clone();
+    for entry in val.split(',') {
+        let mut parts = entry.splitn(2, '=');
+        let disposition = parts.next()?.trim();
+        let days = positive(parts.next()?)?;
+        match disposition {
+            "merge" => durations.merge = days,
+            "close" => durations.close = days,
+            "postpone" => durations.postpone = days,
+            _ => return None,
+        }
+    }
+    Some(durations)
+}
+
+/// Parses a semicolon-delimited list of `owner/repo:durations` entries.
+fn parse_repo_fcp_durations(
+    val: &str,
+    defaults: &FcpDurations,
+) -> Option<BTreeMap<String, FcpDurations>> {
+    let mut repos = BTreeMap::new();
+    for entry in val.split(';').filter(|e| !e.trim().is_empty()) {
+        let mut parts = entry.splitn(2, ':');
+        let repo = parts.next()?.trim();
+        if !repo.contains('/') {
+            return None;
+        }
+        let durations = parse_fcp_durations(parts.next()?, defaults)?;
+        repos.insert(repo.to_string(), durations);
+    }
+    Some(repos)
+}
+
+/// Reviews are read back out of the checklist in the status comment, so a template for it mustn't
+/// leave the checklist out.
+fn keeps_review_boxes(templates: &BTreeMap<String, String>) -> bool {
+    templates.get("fcp_proposed").map_or(true, |t| {
+        t.contains("{reviewers}") || t.contains("{default}")
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    /// A complete configuration, for tests which need one without reading the environment.
+    pub fn config() -> Config {
+        Config {
+            db_url: "postgres://localhost/dashboard".to_string(),
+            db_pool_size: 4,
+            db_pool_timeout_secs: 10,
+            github_access_token: "token".to_string(),
+            github_user_agent: "rfcbot".to_string(),
+            github_accept: DEFAULT_GITHUB_ACCEPT.to_string(),
+            github_reactions_accept: DEFAULT_REACTIONS_ACCEPT.to_string(),
+            github_timeline_accept: DEFAULT_TIMELINE_ACCEPT.to_string(),
+            github_webhook_secrets: vec!["secret".to_string()],
+            github_interval_mins: None,
+            github_scrape_workers: 1,
+            github_scrape_jitter_percent: 0,
+            github_scrape_timelines: false,
+            github_scrape_reactions: false,
+            github_users: vec![],
+            backfill_since: None,
+            github_app: None,
+            cors_allowed_origins: vec![],
+            log_requests: false,
+            fcp_digest_issue: None,
+            fcp_duration_days: FcpDurations::default(),
+            repo_fcp_duration_days: BTreeMap::new(),
+            min_distinct_reviewers: 1,
+            fcp_requires_all_reviews: true,
+            privileged_commands: BTreeSet::new(),
+            admins: BTreeSet::new(),
+            admin_token: None,
+            ignored_authors: BTreeSet::new(),
+            repo_comment_footers: BTreeMap::new(),
+            comment_templates: BTreeMap::new(),
+            reset_reviews_on_push: false,
+            notify_webhook_url: None,
+            error_report_url: None,
+            auto_merge_on_fcp: false,
+            status_emoji: false,
+            review_via_reaction: false,
+            tracking_label: None,
+            require_label_for_fcp: None,
+            max_concerns: None,
+            post_comments: false,
+        }
+    }
+
+    #[test]
+    fn complete_config_is_valid() {
+        assert!(config().missing_fields().is_empty());
+    }
+
+    #[test]
+    fn empty_fields_are_reported() {
+        let config = Config {
+            db_url: String::new(),
+            db_pool_size: 0,
+            github_access_token: " ".to_string(),
+            github_webhook_secrets: vec![String::new()],
+            ..config()
+        };
+        assert_eq!(
+            config.missing_fields(),
+            vec![DB_URL, DB_POOL_SIZE, GITHUB_TOKEN, GITHUB_WEBHOOK_SECRETS]
+        );
+    }
+
+    #[test]
+    fn app_credentials_replace_the_token() {
+        let config = Config {
+            github_access_token: String::new(),
+            github_app: Some(GitHubAppConfig {
+                app_id: 1,
+                installation_id: 2,
+                private_key: vec![],
+            }),
+            ..config()
+        };
+        assert!(config.missing_fields().is_empty());
+    }
+
+    #[test]
+    fn missing_tables_are_reported() {
+        let mut existing = EXPECTED_TABLES
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<BTreeSet<_>>();
+        assert!(missing_tables(&existing).is_empty());
+
+        existing.remove("pending_comment_posts");
+        existing.remove("teams");
+        existing.insert("__diesel_schema_migrations".to_string());
+        assert_eq!(
+            missing_tables(&existing),
+            vec!["pending_comment_posts", "teams"]
+        );
+    }
+
+    #[test]
+    fn proposal_templates_keep_the_checklist() {
+        let templates = |t: &str| btreemap! { "fcp_proposed".to_string() => t.to_string() };
+        assert!(keeps_review_boxes(&BTreeMap::new()));
+        assert!(keeps_review_boxes(&templates(
+            "Please review:\n{reviewers}"
+        )));
+        assert!(keeps_review_boxes(&templates("{default}\n\nThanks!")));
+        assert!(!keeps_review_boxes(&templates("Please review.")));
+    }
+
+    #[test]
+    fn fcp_durations_parsed() {
+        assert_eq!(
+            parse_fcp_durations("7", &FcpDurations::default()),
+            Some(FcpDurations {
+                merge: 7,
+                close: 7,
+                postpone: 7,
+            })
+        );
+        assert_eq!(
+            parse_fcp_durations("close=5, postpone = 14", &FcpDurations::default()),
+            Some(FcpDurations {
+                merge: DEFAULT_FCP_DURATION_DAYS,
+                close: 5,
+                postpone: 14,
+            })
+        );
+        assert_eq!(parse_fcp_durations("0", &FcpDurations::default()), None);
+        assert_eq!(parse_fcp_durations("merge", &FcpDurations::default()), None);
+        assert_eq!(
+            parse_fcp_durations("squash=3", &FcpDurations::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn repo_fcp_durations_fall_back_to_the_global_ones() {
+        let global = FcpDurations {
+            merge: 10,
+            close: 7,
+            postpone: 7,
+        };
+        let repos = parse_repo_fcp_durations("rust-lang/rfcs:merge=14; rust-lang/cargo:3", &global)
+            .unwrap();
+        let config = Config {
+            fcp_duration_days: global.clone(),
+            repo_fcp_duration_days: repos,
+            ..test::config()
+        };
+
+        let rfcs = config.fcp_durations_for("rust-lang/rfcs");
+        assert_eq!((rfcs.merge, rfcs.close), (14, 7));
+        assert_eq!(config.fcp_durations_for("rust-lang/cargo").merge, 3);
+        assert_eq!(config.fcp_durations_for("rust-lang/rust"), &global);
+
+        assert_eq!(parse_repo_fcp_durations("rust-lang/rfcs", &global), None);
+        assert_eq!(parse_repo_fcp_durations("rfcs:7", &global), None);
+    }
+}
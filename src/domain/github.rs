@@ -21,6 +21,46 @@ pub struct GitHubSyncPartial {
     pub message: Option<String>,
 }
 
+/// An issue or pull request which referenced `fk_issue`, found in the issue's timeline.
+#[derive(Clone, Debug, Eq, Insertable, PartialEq)]
+#[table_name = "issue_links"]
+pub struct IssueLinkPartial {
+    pub fk_issue: i32,
+    pub linked_repository: String,
+    pub linked_number: i32,
+    pub linked_is_pull_request: bool,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Queryable)]
+pub struct IssueLink {
+    pub id: i32,
+    pub fk_issue: i32,
+    pub linked_repository: String,
+    pub linked_number: i32,
+    pub linked_is_pull_request: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// How many of the reactions on an issue (not on its comments) are :+1:, :-1: and :tada:.
+#[derive(AsChangeset, Clone, Debug, Eq, Insertable, PartialEq, Queryable, Serialize)]
+#[table_name = "issue_reactions"]
+pub struct IssueReactions {
+    pub fk_issue: i32,
+    pub thumbs_up: i32,
+    pub thumbs_down: i32,
+    pub hooray: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Where the next scrape of a repository picks up from.
+#[derive(Clone, Debug, Insertable, Queryable)]
+#[table_name = "repo_scrape_cursors"]
+pub struct RepoScrapeCursor {
+    pub repository: String,
+    pub updated_at: NaiveDateTime,
+}
+
 #[derive(
     AsChangeset,
     Clone,
@@ -110,6 +150,7 @@ impl IssuePartial {
             updated_at: self.updated_at,
             labels: self.labels,
             repository: self.repository,
+            muted: false,
         }
     }
 }
@@ -135,6 +176,8 @@ pub struct Issue {
     pub updated_at: NaiveDateTime,
     pub labels: Vec<String>,
     pub repository: String,
+    /// Set with `@rfcbot mute` to stop rfcbot commenting on the issue.
+    pub muted: bool,
 }
 
 #[derive(
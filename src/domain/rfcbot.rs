@@ -1,6 +1,7 @@
 use chrono::NaiveDateTime;
 
 use super::schema::*;
+use crate::error::{DashError, DashResult};
 
 #[derive(Clone, Debug, Eq, Ord, Insertable, PartialEq, PartialOrd)]
 #[table_name = "poll"]
@@ -40,7 +41,11 @@ pub struct NewFcpProposal<'a> {
     pub disposition: &'a str,
     pub fk_bot_tracking_comment: i32,
     pub fcp_start: Option<NaiveDateTime>,
-    pub fcp_closed: bool,
+    pub snooze_until: Option<NaiveDateTime>,
+    pub state: &'a str,
+    pub almost_ready_notified: bool,
+    pub fcp_end_override: Option<NaiveDateTime>,
+    pub tracking_label: Option<&'a str>,
 }
 
 #[derive(Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd, Serialize)]
@@ -75,7 +80,148 @@ pub struct FcpProposal {
     pub disposition: String,
     pub fk_bot_tracking_comment: i32,
     pub fcp_start: Option<NaiveDateTime>,
-    pub fcp_closed: bool,
+    pub snooze_until: Option<NaiveDateTime>,
+    pub state: String,
+    pub almost_ready_notified: bool,
+    pub fcp_end_override: Option<NaiveDateTime>,
+    /// Put on the issue while the proposal is open, e.g. for a project board.
+    pub tracking_label: Option<String>,
+}
+
+impl FcpProposal {
+    pub fn state(&self) -> DashResult<ProposalState> { ProposalState::from_str(&self.state) }
+
+    /// Move the proposal to `next`, failing if that isn't reachable from its current state.
+    pub fn transition(&mut self, next: ProposalState) -> DashResult<()> {
+        self.state = self.state()?.transition(next)?.repr().to_string();
+        Ok(())
+    }
+
+    /// Close the proposal from whatever state it's in, for an administrator unsticking it. This
+    /// is the one change of state which doesn't go through `ProposalState::transition`.
+    pub fn force_close(&mut self) -> DashResult<()> {
+        if self.state()? == ProposalState::Cancelled {
+            throw!(DashError::Misc(Some(format!(
+                "proposal {} was cancelled and can't be closed",
+                self.id
+            ))));
+        }
+        self.state = ProposalState::Closed.repr().to_string();
+        Ok(())
+    }
+}
+
+/// What's kept of a proposal which was cancelled because its issue was closed, so that it can be
+/// restored if the issue is reopened.
+#[derive(Clone, Debug, Eq, Insertable, PartialEq)]
+#[table_name = "archived_proposals"]
+pub struct NewArchivedProposal<'a> {
+    pub fk_issue: i32,
+    pub fk_initiator: i32,
+    pub fk_initiating_comment: i32,
+    pub disposition: &'a str,
+    pub fk_bot_tracking_comment: i32,
+    pub archived_at: NaiveDateTime,
+    pub tracking_label: Option<&'a str>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Queryable)]
+pub struct ArchivedProposal {
+    pub id: i32,
+    pub fk_issue: i32,
+    pub fk_initiator: i32,
+    pub fk_initiating_comment: i32,
+    pub disposition: String,
+    pub fk_bot_tracking_comment: i32,
+    pub archived_at: NaiveDateTime,
+    pub tracking_label: Option<String>,
+}
+
+/// A read-only copy of a proposal's status kept in a comment on another issue.
+#[derive(Clone, Debug, Eq, Insertable, PartialEq)]
+#[table_name = "fcp_mirrors"]
+pub struct NewFcpMirror {
+    pub fk_proposal: i32,
+    pub fk_issue: i32,
+    pub fk_mirror_comment: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Queryable)]
+pub struct FcpMirror {
+    pub id: i32,
+    pub fk_proposal: i32,
+    pub fk_issue: i32,
+    pub fk_mirror_comment: i32,
+}
+
+/// Where a proposal is in its lifecycle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProposalState {
+    /// Waiting on reviews or for concerns to be resolved.
+    Pending,
+    /// In its final comment period.
+    InFcp,
+    /// The final comment period has finished.
+    Closed,
+    /// Cancelled by a team member, or because its issue was closed. This is never stored: a
+    /// cancelled proposal is deleted (and archived, if its issue was closed) straight away, so
+    /// it's only seen by what announces the cancellation.
+    Cancelled,
+    /// Set aside with `@rfcbot hold`, FCP won't start until it's taken off hold.
+    OnHold,
+}
+
+const STATE_REPR_PENDING: &str = "pending";
+const STATE_REPR_IN_FCP: &str = "in-fcp";
+const STATE_REPR_CLOSED: &str = "closed";
+const STATE_REPR_CANCELLED: &str = "cancelled";
+const STATE_REPR_ON_HOLD: &str = "on-hold";
+
+impl ProposalState {
+    pub fn repr(self) -> &'static str {
+        match self {
+            ProposalState::Pending => STATE_REPR_PENDING,
+            ProposalState::InFcp => STATE_REPR_IN_FCP,
+            ProposalState::Closed => STATE_REPR_CLOSED,
+            ProposalState::Cancelled => STATE_REPR_CANCELLED,
+            ProposalState::OnHold => STATE_REPR_ON_HOLD,
+        }
+    }
+
+    pub fn from_str(string: &str) -> DashResult<Self> {
+        Ok(match string {
+            STATE_REPR_PENDING => ProposalState::Pending,
+            STATE_REPR_IN_FCP => ProposalState::InFcp,
+            STATE_REPR_CLOSED => ProposalState::Closed,
+            STATE_REPR_CANCELLED => ProposalState::Cancelled,
+            STATE_REPR_ON_HOLD => ProposalState::OnHold,
+            _ => throw!(DashError::Misc(None)),
+        })
+    }
+
+    /// The state after moving to `next`, if that's a valid move.
+    pub fn transition(self, next: Self) -> DashResult<Self> {
+        use self::ProposalState::*;
+
+        let valid = match (self, next) {
+            (Pending, InFcp) | (Pending, OnHold) | (OnHold, Pending) => true,
+            // a new concern takes a proposal back out of FCP
+            (InFcp, Pending) | (InFcp, Closed) | (InFcp, OnHold) => true,
+            (Cancelled, _) => false,
+            (_, Cancelled) => true,
+            _ => false,
+        };
+
+        if !valid {
+            throw!(DashError::Misc(Some(format!(
+                "a proposal can't move from {} to {}",
+                self.repr(),
+                next.repr()
+            ))))
+        }
+
+        Ok(next)
+    }
 }
 
 #[derive(Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd, Serialize)]
@@ -84,17 +230,25 @@ pub struct NewFcpReviewRequest {
     pub fk_proposal: i32,
     pub fk_reviewer: i32,
     pub reviewed: bool,
+    pub waived: bool,
 }
 
 #[derive(
     AsChangeset, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Queryable, Serialize,
 )]
 #[table_name = "fcp_review_request"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct FcpReviewRequest {
     pub id: i32,
     pub fk_proposal: i32,
     pub fk_reviewer: i32,
     pub reviewed: bool,
+    pub waived: bool,
+    /// The login of whoever recorded this review on the reviewer's behalf, if someone did. This
+    /// is the only record of it, as rfcbot keeps no audit log of commands.
+    pub recorded_by: Option<String>,
+    /// A short remark the reviewer left with their review, e.g. `concern-free`.
+    pub note: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd)]
@@ -105,9 +259,14 @@ pub struct NewFcpConcern<'a> {
     pub fk_resolved_comment: Option<i32>,
     pub name: &'a str,
     pub fk_initiating_comment: i32,
+    pub fk_directed_at: Option<i32>,
+    pub description: Option<&'a str>,
+    pub fk_overridden_by: Option<i32>,
 }
 
-#[derive(AsChangeset, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Queryable)]
+#[derive(
+    AsChangeset, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Queryable, Serialize,
+)]
 #[table_name = "fcp_concern"]
 pub struct FcpConcern {
     pub id: i32,
@@ -116,6 +275,10 @@ pub struct FcpConcern {
     pub fk_resolved_comment: Option<i32>,
     pub name: String,
     pub fk_initiating_comment: i32,
+    pub fk_directed_at: Option<i32>,
+    pub description: Option<String>,
+    /// The admin who resolved the concern on its author's behalf, if anyone did.
+    pub fk_overridden_by: Option<i32>,
 }
 
 #[derive(Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd)]
@@ -136,3 +299,105 @@ pub struct FeedbackRequest {
     pub fk_issue: i32,
     pub fk_feedback_comment: Option<i32>,
 }
+
+#[derive(Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd)]
+#[table_name = "proposed_command"]
+pub struct NewProposedCommand<'a> {
+    pub fk_issue: i32,
+    pub fk_initiator: i32,
+    pub fk_initiating_comment: i32,
+    pub disposition: &'a str,
+}
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Queryable)]
+#[table_name = "proposed_command"]
+pub struct ProposedCommand {
+    pub id: i32,
+    pub fk_issue: i32,
+    pub fk_initiator: i32,
+    pub fk_initiating_comment: i32,
+    pub disposition: String,
+}
+
+#[derive(AsChangeset, Clone, Debug, Eq, Insertable, Ord, PartialEq, PartialOrd)]
+#[table_name = "pending_comment_posts"]
+pub struct NewPendingCommentPost<'a> {
+    pub fk_proposal: i32,
+    pub fk_target_comment: i32,
+    pub body: &'a str,
+    pub attempts: i32,
+    pub retry_after: NaiveDateTime,
+}
+
+/// A status comment update which GitHub rejected, to be tried again later.
+#[derive(AsChangeset, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Queryable)]
+#[table_name = "pending_comment_posts"]
+pub struct PendingCommentPost {
+    pub id: i32,
+    pub fk_proposal: i32,
+    pub fk_target_comment: i32,
+    pub body: String,
+    pub attempts: i32,
+    pub retry_after: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProposalState::{self, *};
+
+    #[test]
+    fn valid_state_transitions() {
+        assert_eq!(Pending.transition(InFcp).unwrap(), InFcp);
+        assert_eq!(InFcp.transition(Pending).unwrap(), Pending);
+        assert_eq!(InFcp.transition(Closed).unwrap(), Closed);
+        assert_eq!(Pending.transition(OnHold).unwrap(), OnHold);
+        assert_eq!(OnHold.transition(Pending).unwrap(), Pending);
+        for &state in &[Pending, InFcp, Closed, OnHold] {
+            assert_eq!(state.transition(Cancelled).unwrap(), Cancelled);
+        }
+    }
+
+    #[test]
+    fn invalid_state_transitions() {
+        assert!(Pending.transition(Closed).is_err());
+        assert!(Pending.transition(Pending).is_err());
+        assert!(Closed.transition(InFcp).is_err());
+        assert!(Closed.transition(Pending).is_err());
+        assert!(OnHold.transition(InFcp).is_err());
+        assert!(OnHold.transition(Closed).is_err());
+        assert!(Cancelled.transition(Pending).is_err());
+        assert!(Cancelled.transition(Cancelled).is_err());
+    }
+
+    #[test]
+    fn force_close_skips_the_transitions() {
+        use super::FcpProposal;
+
+        let mut proposal = FcpProposal {
+            id: 1,
+            fk_issue: 1,
+            fk_initiator: 1,
+            fk_initiating_comment: 1,
+            disposition: "merge".to_string(),
+            fk_bot_tracking_comment: 1,
+            fcp_start: None,
+            snooze_until: None,
+            state: Pending.repr().to_string(),
+            almost_ready_notified: false,
+            fcp_end_override: None,
+            tracking_label: None,
+        };
+        proposal.force_close().unwrap();
+        assert_eq!(proposal.state().unwrap(), Closed);
+
+        proposal.state = Cancelled.repr().to_string();
+        assert!(proposal.force_close().is_err());
+    }
+
+    #[test]
+    fn states_round_trip() {
+        for &state in &[Pending, InFcp, Closed, Cancelled, OnHold] {
+            assert_eq!(ProposalState::from_str(state.repr()).unwrap(), state);
+        }
+    }
+}
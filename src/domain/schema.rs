@@ -1,3 +1,59 @@
+table! {
+    /// Representation of the `archived_proposals` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    archived_proposals (id) {
+        /// The `id` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `fk_issue` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_issue -> Int4,
+        /// The `fk_initiator` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_initiator -> Int4,
+        /// The `fk_initiating_comment` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_initiating_comment -> Int4,
+        /// The `disposition` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        disposition -> Varchar,
+        /// The `fk_bot_tracking_comment` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_bot_tracking_comment -> Int4,
+        /// The `archived_at` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        archived_at -> Timestamp,
+        /// The `tracking_label` column of the `archived_proposals` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tracking_label -> Nullable<Varchar>,
+    }
+}
+
 table! {
     /// Representation of the `fcp_concern` table.
     ///
@@ -39,6 +95,56 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         fk_initiating_comment -> Int4,
+        /// The `fk_directed_at` column of the `fcp_concern` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_directed_at -> Nullable<Int4>,
+        /// The `description` column of the `fcp_concern` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        description -> Nullable<Varchar>,
+        /// The `fk_overridden_by` column of the `fcp_concern` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_overridden_by -> Nullable<Int4>,
+    }
+}
+
+table! {
+    /// Representation of the `fcp_mirrors` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    fcp_mirrors (id) {
+        /// The `id` column of the `fcp_mirrors` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `fk_proposal` column of the `fcp_mirrors` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_proposal -> Int4,
+        /// The `fk_issue` column of the `fcp_mirrors` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_issue -> Int4,
+        /// The `fk_mirror_comment` column of the `fcp_mirrors` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_mirror_comment -> Int4,
     }
 }
 
@@ -89,12 +195,36 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         fcp_start -> Nullable<Timestamp>,
-        /// The `fcp_closed` column of the `fcp_proposal` table.
+        /// The `snooze_until` column of the `fcp_proposal` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        snooze_until -> Nullable<Timestamp>,
+        /// The `state` column of the `fcp_proposal` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        state -> Varchar,
+        /// The `almost_ready_notified` column of the `fcp_proposal` table.
         ///
         /// Its SQL type is `Bool`.
         ///
         /// (Automatically generated by Diesel.)
-        fcp_closed -> Bool,
+        almost_ready_notified -> Bool,
+        /// The `fcp_end_override` column of the `fcp_proposal` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fcp_end_override -> Nullable<Timestamp>,
+        /// The `tracking_label` column of the `fcp_proposal` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tracking_label -> Nullable<Varchar>,
     }
 }
 
@@ -127,6 +257,24 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         reviewed -> Bool,
+        /// The `waived` column of the `fcp_review_request` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        waived -> Bool,
+        /// The `recorded_by` column of the `fcp_review_request` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        recorded_by -> Nullable<Varchar>,
+        /// The `note` column of the `fcp_review_request` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        note -> Nullable<Varchar>,
     }
 }
 
@@ -277,6 +425,94 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         repository -> Varchar,
+        /// The `muted` column of the `issue` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        muted -> Bool,
+    }
+}
+
+table! {
+    /// Representation of the `issue_links` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    issue_links (id) {
+        /// The `id` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `fk_issue` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_issue -> Int4,
+        /// The `linked_repository` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        linked_repository -> Varchar,
+        /// The `linked_number` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        linked_number -> Int4,
+        /// The `linked_is_pull_request` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        linked_is_pull_request -> Bool,
+        /// The `created_at` column of the `issue_links` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    /// Representation of the `issue_reactions` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    issue_reactions (fk_issue) {
+        /// The `fk_issue` column of the `issue_reactions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_issue -> Int4,
+        /// The `thumbs_up` column of the `issue_reactions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        thumbs_up -> Int4,
+        /// The `thumbs_down` column of the `issue_reactions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        thumbs_down -> Int4,
+        /// The `hooray` column of the `issue_reactions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        hooray -> Int4,
+        /// The `updated_at` column of the `issue_reactions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
     }
 }
 
@@ -442,6 +678,50 @@ table! {
     }
 }
 
+table! {
+    /// Representation of the `pending_comment_posts` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    pending_comment_posts (id) {
+        /// The `id` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `fk_proposal` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_proposal -> Int4,
+        /// The `fk_target_comment` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_target_comment -> Int4,
+        /// The `body` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        body -> Varchar,
+        /// The `attempts` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        attempts -> Int4,
+        /// The `retry_after` column of the `pending_comment_posts` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        retry_after -> Timestamp,
+    }
+}
+
 table! {
     /// Representation of the `poll` table.
     ///
@@ -536,6 +816,44 @@ table! {
     }
 }
 
+table! {
+    /// Representation of the `proposed_command` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    proposed_command (id) {
+        /// The `id` column of the `proposed_command` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `fk_issue` column of the `proposed_command` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_issue -> Int4,
+        /// The `fk_initiator` column of the `proposed_command` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_initiator -> Int4,
+        /// The `fk_initiating_comment` column of the `proposed_command` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fk_initiating_comment -> Int4,
+        /// The `disposition` column of the `proposed_command` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        disposition -> Varchar,
+    }
+}
+
 table! {
     /// Representation of the `pullrequest` table.
     ///
@@ -646,6 +964,26 @@ table! {
     }
 }
 
+table! {
+    /// Representation of the `repo_scrape_cursors` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    repo_scrape_cursors (repository) {
+        /// The `repository` column of the `repo_scrape_cursors` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        repository -> Varchar,
+        /// The `updated_at` column of the `repo_scrape_cursors` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     /// Representation of the `rfc_feedback_request` table.
     ///
@@ -716,40 +1054,57 @@ table! {
     }
 }
 
+joinable!(archived_proposals -> githubuser (fk_initiator));
+joinable!(archived_proposals -> issue (fk_issue));
 joinable!(fcp_concern -> fcp_proposal (fk_proposal));
 joinable!(fcp_concern -> githubuser (fk_initiator));
+joinable!(fcp_mirrors -> fcp_proposal (fk_proposal));
+joinable!(fcp_mirrors -> issue (fk_issue));
 joinable!(fcp_proposal -> githubuser (fk_initiator));
 joinable!(fcp_proposal -> issue (fk_issue));
 joinable!(fcp_review_request -> fcp_proposal (fk_proposal));
 joinable!(fcp_review_request -> githubuser (fk_reviewer));
 joinable!(issue -> milestone (fk_milestone));
+joinable!(issue_links -> issue (fk_issue));
+joinable!(issue_reactions -> issue (fk_issue));
 joinable!(issuecomment -> githubuser (fk_user));
 joinable!(issuecomment -> issue (fk_issue));
 joinable!(memberships -> githubuser (fk_member));
 joinable!(memberships -> teams (fk_team));
 joinable!(milestone -> githubuser (fk_creator));
+joinable!(pending_comment_posts -> fcp_proposal (fk_proposal));
+joinable!(pending_comment_posts -> issuecomment (fk_target_comment));
 joinable!(poll -> githubuser (fk_initiator));
 joinable!(poll -> issue (fk_issue));
 joinable!(poll_response_request -> githubuser (fk_respondent));
 joinable!(poll_response_request -> poll (fk_poll));
+joinable!(proposed_command -> githubuser (fk_initiator));
+joinable!(proposed_command -> issue (fk_issue));
 joinable!(pullrequest -> githubuser (fk_assignee));
 joinable!(pullrequest -> milestone (fk_milestone));
 joinable!(rfc_feedback_request -> issue (fk_issue));
 joinable!(rfc_feedback_request -> issuecomment (fk_feedback_comment));
 
 allow_tables_to_appear_in_same_query!(
+    archived_proposals,
     fcp_concern,
+    fcp_mirrors,
     fcp_proposal,
     fcp_review_request,
     githubsync,
     githubuser,
     issue,
+    issue_links,
+    issue_reactions,
     issuecomment,
     memberships,
     milestone,
+    pending_comment_posts,
     poll,
     poll_response_request,
+    proposed_command,
     pullrequest,
+    repo_scrape_cursors,
     rfc_feedback_request,
     teams,
 );
@@ -3,6 +3,9 @@
 use std::convert::From;
 use std::io;
 
+use rocket::http::Status;
+use rocket::response::{self, Responder, Response};
+use rocket::Request;
 use rocket_contrib::templates::handlebars;
 
 pub type DashResult<T> = std::result::Result<T, DashError>;
@@ -12,9 +15,12 @@ pub enum DashError {
     Reqwest(reqwest::Error),
     Io(io::Error),
     Serde(serde_json::error::Error),
-    R2d2(diesel::r2d2::PoolError),
+    /// No database connection became free before the pool's timeout, which is the only way
+    /// r2d2 fails to hand one out.
+    DbUnavailable(diesel::r2d2::PoolError),
     DieselError(diesel::result::Error),
     Template(handlebars::RenderError),
+    Crypto(openssl::error::ErrorStack),
     Misc(Option<String>),
 }
 
@@ -22,6 +28,10 @@ impl From<handlebars::RenderError> for DashError {
     fn from(e: handlebars::RenderError) -> Self { DashError::Template(e) }
 }
 
+impl From<openssl::error::ErrorStack> for DashError {
+    fn from(e: openssl::error::ErrorStack) -> Self { DashError::Crypto(e) }
+}
+
 impl From<reqwest::Error> for DashError {
     fn from(e: reqwest::Error) -> Self { DashError::Reqwest(e) }
 }
@@ -35,9 +45,53 @@ impl From<serde_json::error::Error> for DashError {
 }
 
 impl From<diesel::r2d2::PoolError> for DashError {
-    fn from(e: diesel::r2d2::PoolError) -> Self { DashError::R2d2(e) }
+    fn from(e: diesel::r2d2::PoolError) -> Self { DashError::DbUnavailable(e) }
 }
 
 impl From<diesel::result::Error> for DashError {
     fn from(e: diesel::result::Error) -> Self { DashError::DieselError(e) }
 }
+
+/// Routes answer 503 while the database is overloaded, so that clients know to try again later.
+/// Any other error is logged and answered with a 500, as rocket does for errors by default.
+impl<'r> Responder<'r> for DashError {
+    fn respond_to(self, _: &Request<'_>) -> response::Result<'r> {
+        match self {
+            DashError::DbUnavailable(why) => {
+                warn!("No database connection available for a request: {:?}", why);
+                Response::build()
+                    .status(Status::ServiceUnavailable)
+                    .raw_header("Retry-After", "30")
+                    .ok()
+            }
+            why => {
+                error!("Request failed: {:?}", why);
+                Err(Status::InternalServerError)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn exhausted_pool_is_unavailable() {
+        crate::utils::setup_test_env();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = crate::new_pool(&db_url, 1, Duration::from_millis(100)).unwrap();
+        let _held = pool.get().unwrap();
+
+        match pool.get().map_err(DashError::from) {
+            Err(DashError::DbUnavailable(_)) => {}
+            other => panic!(
+                "expected the pool to be exhausted, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+    }
+}
@@ -1,18 +1,26 @@
 // Copyright 2016 Adam Perry. Dual-licensed MIT and Apache 2.0 (see LICENSE files for details).
 
+use std::cell::Cell;
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
 use std::thread::sleep;
 use std::time::Duration;
 use std::u32;
 
 use chrono::{DateTime, Utc};
-use reqwest::{self, header::HeaderMap, Response, StatusCode};
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use reqwest::{self, header::HeaderMap, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
 
-use crate::config::CONFIG;
+use crate::config::{GitHubAppConfig, CONFIG};
 use crate::domain::github::GitHubUser;
 use crate::error::{DashError, DashResult};
-use crate::github::models::{CommentFromJson, IssueFromJson, PullRequestFromJson, PullRequestUrls};
+use crate::github::models::{
+    CommentFromJson, CrossReference, IssueEventFromJson, IssueFromJson, LabelEvent, LabelFromJson,
+    PullRequestFromJson, PullRequestUrls, ReactionFromJson, SearchResultsFromJson,
+    TimelineEventFromJson,
+};
 
 pub const BASE_URL: &str = "https://api.github.com";
 
@@ -22,28 +30,121 @@ type ParameterMap = BTreeMap<&'static str, String>;
 
 const PER_PAGE: u32 = 100;
 
+/// The GitHub Apps installation token endpoint is also still a preview.
+const MACHINE_MAN_PREVIEW: &str = "application/vnd.github.machine-man-preview+json";
+
+/// Installation tokens are replaced this long before they expire, so that a request
+/// doesn't race the expiry.
+const TOKEN_REFRESH_MARGIN_MINS: i64 = 5;
+
+/// How many times a rate limited request is retried before its response is returned as-is.
+const MAX_RETRIES: u32 = 3;
+
+/// The longest rfcbot waits for a rate limit to pass, whatever GitHub asks for.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// When the rate limit hit by a request which couldn't wait for it passes, as a Unix timestamp.
+static RATE_LIMITED_UNTIL: AtomicI64 = AtomicI64::new(0);
+
+thread_local! {
+    /// Whether rate limited requests on this thread wait and retry, see `no_rate_limit_waits`.
+    static WAIT_FOR_RATE_LIMITS: Cell<bool> = Cell::new(true);
+}
+
+/// Until the returned guard is dropped, rate limited requests on this thread give up straight
+/// away instead of waiting, for code holding a lock which others would be stuck behind. The
+/// wait is left to `wait_for_rate_limit`.
+pub fn no_rate_limit_waits() -> NoRateLimitWaits {
+    NoRateLimitWaits(WAIT_FOR_RATE_LIMITS.with(|wait| wait.replace(false)))
+}
+
+pub struct NoRateLimitWaits(bool);
+
+impl Drop for NoRateLimitWaits {
+    fn drop(&mut self) { WAIT_FOR_RATE_LIMITS.with(|wait| wait.set(self.0)) }
+}
+
+/// Waits for the rate limit hit by a request which gave up under `no_rate_limit_waits`, if it
+/// hasn't passed yet.
+pub fn wait_for_rate_limit() {
+    let remaining = RATE_LIMITED_UNTIL.load(Ordering::SeqCst) - Utc::now().timestamp();
+    if remaining > 0 {
+        let wait = Duration::from_secs(remaining as u64).min(MAX_RATE_LIMIT_WAIT);
+        warn!(
+            "Waiting {}s for GitHub's rate limit to pass",
+            wait.as_secs()
+        );
+        sleep(wait);
+    }
+}
+
+/// The delay before retrying a rate limited request on this thread, or `None` if it gives up
+/// because it can't wait. The request after it waits instead.
+fn retry_delay(delay: Option<Duration>, now: DateTime<Utc>) -> Option<Duration> {
+    let delay = delay?;
+    if WAIT_FOR_RATE_LIMITS.with(Cell::get) {
+        return Some(delay);
+    }
+
+    RATE_LIMITED_UNTIL.fetch_max(now.timestamp() + delay.as_secs() as i64, Ordering::SeqCst);
+    None
+}
+
+/// What posting a comment did on GitHub.
+#[derive(Debug, PartialEq)]
+pub enum PostOutcome<C = CommentFromJson> {
+    /// A new comment was created.
+    Created(C),
+    /// An existing comment was given a new body.
+    Edited(C),
+    /// The existing comment already had this body, so nothing was sent.
+    Unchanged,
+}
+
+impl<C> PostOutcome<C> {
+    /// The comment GitHub sent back, if anything was actually posted.
+    pub fn comment(self) -> Option<C> {
+        match self {
+            PostOutcome::Created(c) | PostOutcome::Edited(c) => Some(c),
+            PostOutcome::Unchanged => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Client {
     client: reqwest::Client,
     rate_limit: u32,
     rate_limit_timeout: DateTime<Utc>,
+    installation_token: Mutex<Option<InstallationToken>>,
+    login: Mutex<Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl InstallationToken {
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at - now < chrono::Duration::minutes(TOKEN_REFRESH_MARGIN_MINS)
+    }
 }
 
 impl Client {
     pub fn new() -> Self {
-        let mut headers = HeaderMap::new();
-        if !CONFIG.github_access_token.trim().is_empty() {
-            headers.insert(
-                "Authorization",
-                format!("token {}", CONFIG.github_access_token)
-                    .parse()
-                    .unwrap(),
-            );
-        }
-        headers.insert("User-Agent", CONFIG.github_user_agent.parse().unwrap());
-        headers.insert("Time-Zone", "UTC".parse().unwrap());
-        headers.insert("Accept", "application/vnd.github.v3".parse().unwrap());
-        headers.insert("Connection", "close".parse().unwrap());
+        // GitHub App tokens expire, so those are added to each request instead
+        let access_token = if CONFIG.github_app.is_none() {
+            Some(CONFIG.github_access_token.as_str())
+        } else {
+            None
+        };
+        let headers = default_headers(
+            access_token,
+            &CONFIG.github_user_agent,
+            &CONFIG.github_accept,
+        );
         Client {
             client: reqwest::Client::builder()
                 .default_headers(headers)
@@ -51,26 +152,48 @@ impl Client {
                 .unwrap(),
             rate_limit: u32::MAX,
             rate_limit_timeout: Utc::now(),
+            installation_token: Mutex::new(None),
+            login: Mutex::new(None),
         }
     }
 
     pub fn org_repos(&self, org: &str) -> DashResult<Vec<String>> {
         let url = format!("{}/orgs/{}/repos", BASE_URL, org);
         let vals: Vec<serde_json::Value> = self.get_models(&url, None)?;
+        repo_names(org, vals)
+    }
 
-        let mut repos = Vec::new();
-        for v in vals {
-            if let Some(v) = v.as_object() {
-                if let Some(n) = v.get("name") {
-                    if let Some(s) = n.as_str() {
-                        repos.push(format!("{}/{}", org, s));
-                        continue;
-                    }
-                }
-            }
-            throw!(DashError::Misc(None))
+    /// The repositories owned by a user, for deployments watching repos outside of an org.
+    pub fn user_repos(&self, user: &str) -> DashResult<Vec<String>> {
+        let url = format!("{}/users/{}/repos", BASE_URL, user);
+        let vals: Vec<serde_json::Value> = self.get_models(
+            &url,
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+        )?;
+        repo_names(user, vals)
+    }
+
+    /// The current name of a repository. Renamed repositories redirect to their new name, which
+    /// is followed.
+    pub fn repo_full_name(&self, repo: &str) -> DashResult<String> {
+        #[derive(Deserialize)]
+        struct Repo {
+            full_name: String,
         }
-        Ok(repos)
+
+        let url = format!("{}/repos/{}", BASE_URL, repo);
+        let found: Repo = self.get(&url, None)?.error_for_status()?.json()?;
+        Ok(found.full_name)
+    }
+
+    /// Makes a cheap authenticated request, failing if GitHub doesn't accept our credentials.
+    /// The rate limit endpoint doesn't count against the rate limit.
+    pub fn check_token(&self) -> DashResult<()> {
+        let url = format!("{}/rate_limit", BASE_URL);
+        self.get(&url, None)?.error_for_status()?;
+        Ok(())
     }
 
     pub fn issues_since(&self, repo: &str, start: DateTime<Utc>) -> DashResult<Vec<IssueFromJson>> {
@@ -78,7 +201,7 @@ impl Client {
             &format!("{}/repos/{}/issues", BASE_URL, repo),
             Some(&btreemap! {
                 "state" => "all".to_string(),
-                "since" => format!("{:?}", start),
+                "since" => since_param(start),
                 "per_page" => format!("{}", PER_PAGE),
                 "direction" => "asc".to_string()
             }),
@@ -95,22 +218,60 @@ impl Client {
             Some(&btreemap! {
                 "sort" => "created".to_string(),
                 "direction" => "asc".to_string(),
-                "since" => format!("{:?}", start),
+                "since" => since_param(start),
                 "per_page" => format!("{}", PER_PAGE)
             }),
         )
     }
 
+    pub fn issue_reactions(&self, repo: &str, issue_num: i32) -> DashResult<Vec<ReactionFromJson>> {
+        self.get_models_accepting(
+            &format!("{}/repos/{}/issues/{}/reactions", BASE_URL, repo, issue_num),
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+            Some(&CONFIG.github_reactions_accept),
+        )
+    }
+
+    /// The issues and pull requests matching a search query, e.g.
+    /// `repo:rust-lang/rfcs is:open label:final-comment-period`.
+    pub fn search_issues(&self, query: &str) -> DashResult<Vec<IssueFromJson>> {
+        let mut res = self.get(
+            &format!("{}/search/issues", BASE_URL),
+            Some(&btreemap! {
+                "q" => query.to_string(),
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+        )?;
+        let mut issues = res.json::<SearchResultsFromJson<_>>()?.items;
+        while let Some(url) = Self::next_page(res.headers()) {
+            sleep(Duration::from_millis(DELAY));
+            res = self.get(&url, None)?;
+            issues.extend(res.json::<SearchResultsFromJson<_>>()?.items);
+        }
+        Ok(issues)
+    }
+
     fn get_models<M: DeserializeOwned>(
         &self,
         start_url: &str,
         params: Option<&ParameterMap>,
     ) -> DashResult<Vec<M>> {
-        let mut res = self.get(start_url, params)?;
+        self.get_models_accepting(start_url, params, None)
+    }
+
+    fn get_models_accepting<M: DeserializeOwned>(
+        &self,
+        start_url: &str,
+        params: Option<&ParameterMap>,
+        accept: Option<&str>,
+    ) -> DashResult<Vec<M>> {
+        let mut res = self.get_accepting(start_url, params, accept)?;
         let mut models: Vec<M> = res.json()?;
         while let Some(url) = Self::next_page(res.headers()) {
             sleep(Duration::from_millis(DELAY));
-            res = self.get(&url, None)?;
+            res = self.get_accepting(&url, None, accept)?;
             models.extend(res.json::<Vec<M>>()?);
         }
         Ok(models)
@@ -147,9 +308,220 @@ impl Client {
         None
     }
 
-    pub fn close_issue(&self, repo: &str, issue_num: i32) -> DashResult<()> {
+    /// The other issues and pull requests which have referenced this one, from its timeline.
+    pub fn issue_timeline(&self, repo: &str, issue_num: i32) -> DashResult<Vec<CrossReference>> {
+        let url = format!("{}/repos/{}/issues/{}/timeline", BASE_URL, repo, issue_num);
+        let events: Vec<TimelineEventFromJson> = self.get_models_accepting(
+            &url,
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+            Some(&CONFIG.github_timeline_accept),
+        )?;
+        Ok(events
+            .into_iter()
+            .filter_map(TimelineEventFromJson::cross_reference)
+            .collect())
+    }
+
+    fn patch(&self, url: &str, payload: &str) -> DashResult<Response> {
+        self.send(|| self.client.patch(url).body(payload.to_string()))
+    }
+
+    fn post(&self, url: &str, payload: &str) -> DashResult<Response> {
+        self.send(|| self.client.post(url).body(payload.to_string()))
+    }
+
+    fn put(&self, url: &str, payload: &str) -> DashResult<Response> {
+        self.send(|| self.client.put(url).body(payload.to_string()))
+    }
+
+    fn delete(&self, url: &str) -> DashResult<Response> { self.send(|| self.client.delete(url)) }
+
+    fn get(&self, url: &str, params: Option<&ParameterMap>) -> DashResult<Response> {
+        self.get_accepting(url, params, None)
+    }
+
+    fn get_accepting(
+        &self,
+        url: &str,
+        params: Option<&ParameterMap>,
+        accept: Option<&str>,
+    ) -> DashResult<Response> {
+        debug!("GETing: {}", &url);
+        self.send(|| {
+            let mut builder = self.client.get(url);
+            if let Some(params) = params {
+                builder = builder.query(params);
+            }
+            if let Some(accept) = accept {
+                builder = builder.header("Accept", accept);
+            }
+            builder
+        })
+    }
+
+    /// Sends the request built by `request`, building and sending it again if GitHub says
+    /// we've been rate limited.
+    fn send<F>(&self, request: F) -> DashResult<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let authorization = self.authorization()?;
+        let request = || match authorization {
+            Some(ref authorization) => request().header("Authorization", authorization.as_str()),
+            None => request(),
+        };
+
+        Ok(retrying(
+            || request().send(),
+            |res| {
+                let now = Utc::now();
+                retry_delay(rate_limit_delay(res.status(), res.headers(), now), now)
+            },
+            sleep,
+        )?)
+    }
+
+    /// The `Authorization` header to send when running as a GitHub App, refreshing the
+    /// installation token if it's about to expire. Personal access tokens are already part
+    /// of the default headers.
+    fn authorization(&self) -> DashResult<Option<String>> {
+        let app = match CONFIG.github_app {
+            Some(ref app) => app,
+            None => return Ok(None),
+        };
+
+        let mut cached = self.installation_token.lock().unwrap();
+        let now = Utc::now();
+        if cached.as_ref().map_or(true, |t| t.needs_refresh(now)) {
+            *cached = Some(self.new_installation_token(app, now)?);
+        }
+
+        Ok(cached.as_ref().map(|t| format!("token {}", t.token)))
+    }
+
+    fn new_installation_token(
+        &self,
+        app: &GitHubAppConfig,
+        now: DateTime<Utc>,
+    ) -> DashResult<InstallationToken> {
+        info!("requesting a new GitHub App installation token");
+        let url = format!(
+            "{}/app/installations/{}/access_tokens",
+            BASE_URL, app.installation_id
+        );
+        let bearer = format!("Bearer {}", app_jwt(app, now)?);
+
+        let res = retrying(
+            || {
+                self.client
+                    .post(&url)
+                    .header("Authorization", bearer.as_str())
+                    .header("Accept", MACHINE_MAN_PREVIEW)
+                    .send()
+            },
+            |res| {
+                let now = Utc::now();
+                retry_delay(rate_limit_delay(res.status(), res.headers(), now), now)
+            },
+            sleep,
+        )?;
+
+        Ok(res.error_for_status()?.json()?)
+    }
+}
+
+/// The requests the nag code makes to GitHub, so that tests can run it against a fake.
+pub trait GithubClient {
+    fn add_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()>;
+
+    fn remove_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()>;
+
+    fn issue_labels(&self, repo: &str, issue_num: i32) -> DashResult<Vec<String>>;
+
+    fn comments_of_issue(&self, repo: &str, issue_num: i32) -> DashResult<Vec<CommentFromJson>>;
+
+    fn new_comment(&self, repo: &str, issue_num: i32, text: &str) -> DashResult<CommentFromJson>;
+
+    fn edit_comment(&self, repo: &str, comment_num: i32, text: &str)
+        -> DashResult<CommentFromJson>;
+
+    fn close_issue(&self, repo: &str, issue_num: i32) -> DashResult<()>;
+
+    /// The logins of everyone who reacted to a comment with `reaction`, e.g. `+1`.
+    fn comment_reaction_users(
+        &self,
+        repo: &str,
+        comment_id: i32,
+        reaction: &str,
+    ) -> DashResult<Vec<String>>;
+
+    /// Puts an issue in the milestone with the given number.
+    fn set_milestone(&self, repo: &str, issue_num: i32, milestone: i32) -> DashResult<()>;
+
+    /// Merges a pull request with the given method (`merge`, `squash` or `rebase`). Returns
+    /// `false` if GitHub won't merge it as it stands, e.g. because it has conflicts.
+    fn merge_pr(&self, repo: &str, pr_num: i32, method: &str) -> DashResult<bool>;
+
+    /// The labels added to and removed from an issue, oldest first.
+    fn issue_events(&self, repo: &str, issue_num: i32) -> DashResult<Vec<LabelEvent>>;
+
+    fn get_comment(&self, repo: &str, comment_num: i32) -> DashResult<CommentFromJson>;
+
+    /// The user we're authenticated as.
+    fn current_user(&self) -> DashResult<GitHubUser>;
+
+    /// The login we post as, which is only looked up once. GitHub Apps aren't users, so they
+    /// don't have one to look up.
+    fn login(&self) -> DashResult<String>;
+
+    fn get_user(&self, name: &str) -> DashResult<GitHubUser>;
+
+    /// Every reaction to a comment, whoever made it.
+    fn comment_reactions(&self, repo: &str, comment_id: i32) -> DashResult<Vec<ReactionFromJson>>;
+
+    fn delete_comment_reaction(
+        &self,
+        repo: &str,
+        comment_id: i32,
+        reaction_id: i32,
+    ) -> DashResult<()>;
+
+    /// Deletes `login`'s reactions from every comment on an issue, e.g. when rfcbot is being
+    /// removed from a repository, returning how many were deleted.
+    fn clear_reactions(&self, repo: &str, issue_num: i32, login: &str) -> DashResult<usize> {
+        let comments = self.comments_of_issue(repo, issue_num)?;
+        clear_own_reactions(
+            comments.iter().map(|comment| comment.id),
+            login,
+            |comment_id| self.comment_reactions(repo, comment_id),
+            |comment_id, reaction_id| self.delete_comment_reaction(repo, comment_id, reaction_id),
+        )
+    }
+
+    /// Create a comment, or edit `existing` (an id and its current body) if there is one and
+    /// its body differs from `text`.
+    fn post_comment(
+        &self,
+        repo: &str,
+        issue_num: i32,
+        existing: Option<(i32, &str)>,
+        text: &str,
+    ) -> DashResult<PostOutcome> {
+        post_or_edit(
+            existing,
+            text,
+            || self.new_comment(repo, issue_num, text),
+            |id| self.edit_comment(repo, id, text),
+        )
+    }
+}
+
+impl GithubClient for Client {
+    fn set_milestone(&self, repo: &str, issue_num: i32, milestone: i32) -> DashResult<()> {
         let url = format!("{}/repos/{}/issues/{}", BASE_URL, repo, issue_num);
-        let payload = serde_json::to_string(&btreemap!("state" => "closed"))?;
+        let payload = serde_json::to_string(&btreemap!("milestone" => milestone))?;
         let mut res = self.patch(&url, &payload)?;
 
         if StatusCode::OK != res.status() {
@@ -159,7 +531,67 @@ impl Client {
         Ok(())
     }
 
-    pub fn add_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()> {
+    fn merge_pr(&self, repo: &str, pr_num: i32, method: &str) -> DashResult<bool> {
+        let (url, payload) = merge_request(repo, pr_num, method)?;
+        let mut res = self.put(&url, &payload)?;
+
+        match res.status() {
+            StatusCode::OK => Ok(true),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::CONFLICT => {
+                info!("{}#{} can't be merged: {}", repo, pr_num, res.text()?);
+                Ok(false)
+            }
+            _ => throw!(DashError::Misc(Some(res.text()?))),
+        }
+    }
+
+    fn issue_events(&self, repo: &str, issue_num: i32) -> DashResult<Vec<LabelEvent>> {
+        let url = format!("{}/repos/{}/issues/{}/events", BASE_URL, repo, issue_num);
+        let events: Vec<IssueEventFromJson> = self.get_models(
+            &url,
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+        )?;
+        Ok(events
+            .into_iter()
+            .filter_map(IssueEventFromJson::label_change)
+            .collect())
+    }
+
+    fn get_comment(&self, repo: &str, comment_num: i32) -> DashResult<CommentFromJson> {
+        let url = format!(
+            "{}/repos/{}/issues/comments/{}",
+            BASE_URL, repo, comment_num
+        );
+        Ok(self.get(&url, None)?.error_for_status()?.json()?)
+    }
+
+    fn current_user(&self) -> DashResult<GitHubUser> {
+        let url = format!("{}/user", BASE_URL);
+        Ok(self.get(&url, None)?.error_for_status()?.json()?)
+    }
+
+    fn login(&self) -> DashResult<String> {
+        if CONFIG.github_app.is_some() {
+            throw!(DashError::Misc(Some(
+                "GitHub Apps can't look up their own login".to_string()
+            )));
+        }
+
+        let mut cached = self.login.lock().unwrap();
+        if cached.is_none() {
+            *cached = Some(self.current_user()?.login);
+        }
+        Ok(cached.clone().unwrap_or_default())
+    }
+
+    fn get_user(&self, name: &str) -> DashResult<GitHubUser> {
+        let url = format!("{}/users/{}", BASE_URL, name);
+        Ok(self.get(&url, None)?.error_for_status()?.json()?)
+    }
+
+    fn add_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()> {
         let url = format!("{}/repos/{}/issues/{}/labels", BASE_URL, repo, issue_num);
         let payload = serde_json::to_string(&[label])?;
 
@@ -172,7 +604,7 @@ impl Client {
         Ok(())
     }
 
-    pub fn remove_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()> {
+    fn remove_label(&self, repo: &str, issue_num: i32, label: &str) -> DashResult<()> {
         let url = format!(
             "{}/repos/{}/issues/{}/labels/{}",
             BASE_URL, repo, issue_num, label
@@ -186,18 +618,33 @@ impl Client {
         Ok(())
     }
 
-    pub fn new_comment(
-        &self,
-        repo: &str,
-        issue_num: i32,
-        text: &str,
-    ) -> DashResult<CommentFromJson> {
+    fn issue_labels(&self, repo: &str, issue_num: i32) -> DashResult<Vec<String>> {
+        let url = format!("{}/repos/{}/issues/{}/labels", BASE_URL, repo, issue_num);
+        let labels: Vec<LabelFromJson> = self.get_models(
+            &url,
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+        )?;
+        Ok(labels.into_iter().map(|l| l.name).collect())
+    }
+
+    fn comments_of_issue(&self, repo: &str, issue_num: i32) -> DashResult<Vec<CommentFromJson>> {
+        self.get_models(
+            &format!("{}/repos/{}/issues/{}/comments", BASE_URL, repo, issue_num),
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+        )
+    }
+
+    fn new_comment(&self, repo: &str, issue_num: i32, text: &str) -> DashResult<CommentFromJson> {
         let url = format!("{}/repos/{}/issues/{}/comments", BASE_URL, repo, issue_num);
         let payload = serde_json::to_string(&btreemap!("body" => text))?;
         Ok(self.post(&url, &payload)?.error_for_status()?.json()?)
     }
 
-    pub fn edit_comment(
+    fn edit_comment(
         &self,
         repo: &str,
         comment_num: i32,
@@ -211,29 +658,677 @@ impl Client {
         Ok(self.patch(&url, &payload)?.error_for_status()?.json()?)
     }
 
-    pub fn get_user(&self, name: &str) -> DashResult<GitHubUser> {
-        let url = format!("{}/users/{}", BASE_URL, name);
-        Ok(self.get(&url, None)?.error_for_status()?.json()?)
+    fn close_issue(&self, repo: &str, issue_num: i32) -> DashResult<()> {
+        let url = format!("{}/repos/{}/issues/{}", BASE_URL, repo, issue_num);
+        let payload = serde_json::to_string(&btreemap!("state" => "closed"))?;
+        let mut res = self.patch(&url, &payload)?;
+
+        if StatusCode::OK != res.status() {
+            throw!(DashError::Misc(Some(res.text()?)))
+        }
+
+        Ok(())
+    }
+
+    fn comment_reaction_users(
+        &self,
+        repo: &str,
+        comment_id: i32,
+        reaction: &str,
+    ) -> DashResult<Vec<String>> {
+        let reactions = self.get_models_accepting(
+            &format!(
+                "{}/repos/{}/issues/comments/{}/reactions",
+                BASE_URL, repo, comment_id
+            ),
+            Some(&btreemap! {
+                "content" => reaction.to_string(),
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+            Some(&CONFIG.github_reactions_accept),
+        )?;
+        Ok(reaction_logins(reactions, reaction))
+    }
+
+    fn comment_reactions(&self, repo: &str, comment_id: i32) -> DashResult<Vec<ReactionFromJson>> {
+        self.get_models_accepting(
+            &format!(
+                "{}/repos/{}/issues/comments/{}/reactions",
+                BASE_URL, repo, comment_id
+            ),
+            Some(&btreemap! {
+                "per_page" => format!("{}", PER_PAGE)
+            }),
+            Some(&CONFIG.github_reactions_accept),
+        )
+    }
+
+    fn delete_comment_reaction(
+        &self,
+        repo: &str,
+        comment_id: i32,
+        reaction_id: i32,
+    ) -> DashResult<()> {
+        let url = format!(
+            "{}/repos/{}/issues/comments/{}/reactions/{}",
+            BASE_URL, repo, comment_id, reaction_id
+        );
+        let mut res = self.delete(&url)?;
+
+        if StatusCode::NO_CONTENT != res.status() {
+            throw!(DashError::Misc(Some(res.text()?)))
+        }
+
+        Ok(())
     }
+}
 
-    fn patch(&self, url: &str, payload: &str) -> Result<Response, reqwest::Error> {
-        self.client.patch(url).body(payload.to_string()).send()
+/// The headers sent with every request.
+fn default_headers(access_token: Option<&str>, user_agent: &str, accept: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(token) = access_token.filter(|t| !t.trim().is_empty()) {
+        headers.insert("Authorization", format!("token {}", token).parse().unwrap());
     }
+    headers.insert("User-Agent", user_agent.parse().unwrap());
+    headers.insert("Time-Zone", "UTC".parse().unwrap());
+    headers.insert("Accept", accept.parse().unwrap());
+    headers.insert("Connection", "close".parse().unwrap());
+    headers
+}
+
+/// Creates the short-lived JWT a GitHub App uses to authenticate as itself.
+fn app_jwt(app: &GitHubAppConfig, now: DateTime<Utc>) -> DashResult<String> {
+    let header = base64_url(br#"{"alg":"RS256","typ":"JWT"}"#);
+    // backdated to allow for clock drift, GitHub won't accept tokens living over ten minutes
+    let claims = json!({
+        "iat": now.timestamp() - 60,
+        "exp": now.timestamp() + 9 * 60,
+        "iss": app.app_id,
+    });
+    let claims = base64_url(serde_json::to_string(&claims)?.as_bytes());
+    let message = format!("{}.{}", header, claims);
 
-    fn post(&self, url: &str, payload: &str) -> Result<Response, reqwest::Error> {
-        self.client.post(url).body(payload.to_string()).send()
+    let key = PKey::private_key_from_pem(&app.private_key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(message.as_bytes())?;
+    let signature = base64_url(&signer.sign_to_vec()?);
+
+    Ok(format!("{}.{}", message, signature))
+}
+
+/// The unpadded URL-safe base64 used by JWTs.
+fn base64_url(bytes: &[u8]) -> String {
+    openssl::base64::encode_block(bytes)
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+/// Who reacted with `content`, in the order they reacted. GitHub filters by the content we ask
+/// for, but that's no reason to count another reaction if it slips through.
+fn reaction_logins(reactions: Vec<ReactionFromJson>, content: &str) -> Vec<String> {
+    let mut reactions = reactions
+        .into_iter()
+        .filter(|r| r.content == content)
+        .collect::<Vec<_>>();
+    reactions.sort_by_key(|r| r.created_at);
+    reactions.into_iter().map(|r| r.user.login).collect()
+}
+
+/// Formats the repositories listed by GitHub as `owner/repo`.
+/// Deletes the reactions `login` made to each of `comments`, leaving everyone else's alone.
+fn clear_own_reactions<C, R, D>(
+    comments: C,
+    login: &str,
+    mut reactions: R,
+    mut delete: D,
+) -> DashResult<usize>
+where
+    C: IntoIterator<Item = i32>,
+    R: FnMut(i32) -> DashResult<Vec<ReactionFromJson>>,
+    D: FnMut(i32, i32) -> DashResult<()>,
+{
+    let mut deleted = 0;
+    for comment_id in comments {
+        for reaction in reactions(comment_id)? {
+            if reaction.user.login == login {
+                delete(comment_id, reaction.id)?;
+                deleted += 1;
+            }
+        }
     }
+    Ok(deleted)
+}
 
-    fn delete(&self, url: &str) -> Result<Response, reqwest::Error> {
-        self.client.delete(url).send()
+fn repo_names(owner: &str, vals: Vec<serde_json::Value>) -> DashResult<Vec<String>> {
+    let mut repos = Vec::new();
+    for v in vals {
+        if let Some(v) = v.as_object() {
+            if let Some(n) = v.get("name") {
+                if let Some(s) = n.as_str() {
+                    repos.push(format!("{}/{}", owner, s));
+                    continue;
+                }
+            }
+        }
+        throw!(DashError::Misc(None))
     }
+    Ok(repos)
+}
 
-    fn get(&self, url: &str, params: Option<&ParameterMap>) -> Result<Response, reqwest::Error> {
-        debug!("GETing: {}", &url);
-        let mut builder = self.client.get(url);
-        if let Some(params) = params {
-            builder = builder.query(params);
+/// The URL and body of a request to merge a pull request.
+fn merge_request(repo: &str, pr_num: i32, method: &str) -> DashResult<(String, String)> {
+    let url = format!("{}/repos/{}/pulls/{}/merge", BASE_URL, repo, pr_num);
+    let payload = serde_json::to_string(&btreemap!("merge_method" => method))?;
+    Ok((url, payload))
+}
+
+/// The `since` query parameter selecting things updated at or after `start`.
+pub fn since_param(start: DateTime<Utc>) -> String { format!("{:?}", start) }
+
+fn post_or_edit<C>(
+    existing: Option<(i32, &str)>,
+    text: &str,
+    create: impl FnOnce() -> DashResult<C>,
+    edit: impl FnOnce(i32) -> DashResult<C>,
+) -> DashResult<PostOutcome<C>> {
+    match existing {
+        Some((_, body)) if body == text => Ok(PostOutcome::Unchanged),
+        Some((id, _)) => Ok(PostOutcome::Edited(edit(id)?)),
+        None => Ok(PostOutcome::Created(create()?)),
+    }
+}
+
+fn retrying<R, E, S, D, W>(mut send: S, delay: D, mut wait: W) -> Result<R, E>
+where
+    S: FnMut() -> Result<R, E>,
+    D: Fn(&R) -> Option<Duration>,
+    W: FnMut(Duration),
+{
+    let mut res = send()?;
+    for attempt in 1..=MAX_RETRIES {
+        match delay(&res) {
+            Some(d) => {
+                warn!(
+                    "Rate limited by GitHub, retrying in {}s (attempt {} of {})",
+                    d.as_secs(),
+                    attempt,
+                    MAX_RETRIES
+                );
+                wait(d);
+                res = send()?;
+            }
+            None => break,
+        }
+    }
+    Ok(res)
+}
+
+/// How long to wait before retrying a request which got this response, if it was rate limited.
+///
+/// Secondary rate limits come with a `Retry-After` header; running out of the hourly quota is
+/// signalled by a zero `X-RateLimit-Remaining` and a `X-RateLimit-Reset` timestamp.
+fn rate_limit_delay(
+    status: StatusCode,
+    headers: &HeaderMap,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+
+    let secs = match header("Retry-After") {
+        Some(secs) => secs,
+        None => match (header("X-RateLimit-Remaining"), header("X-RateLimit-Reset")) {
+            (Some(0), Some(reset)) => reset - now.timestamp(),
+            _ => return None,
+        },
+    };
+    Some(Duration::from_secs(secs.max(0) as u64).min(MAX_RATE_LIMIT_WAIT))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for &(name, value) in pairs {
+            headers.insert(name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn installation_token_refreshed_before_expiry() {
+        let now = Utc.timestamp(1_000_000, 0);
+        let token = |expires_in_mins| InstallationToken {
+            token: "t".to_string(),
+            expires_at: now + chrono::Duration::minutes(expires_in_mins),
+        };
+
+        assert!(!token(60).needs_refresh(now));
+        assert!(!token(TOKEN_REFRESH_MARGIN_MINS).needs_refresh(now));
+        assert!(token(TOKEN_REFRESH_MARGIN_MINS - 1).needs_refresh(now));
+        assert!(token(0).needs_refresh(now));
+        assert!(token(-10).needs_refresh(now));
+    }
+
+    #[test]
+    fn configured_accept_header_sent() {
+        let headers = default_headers(None, "rfcbot", "application/vnd.github.v4+json");
+        assert_eq!(headers["Accept"], "application/vnd.github.v4+json");
+        assert_eq!(headers["User-Agent"], "rfcbot");
+        assert!(!headers.contains_key("Authorization"));
+
+        let headers = default_headers(Some("t0ken"), "rfcbot", "application/vnd.github.v3");
+        assert_eq!(headers["Accept"], "application/vnd.github.v3");
+        assert_eq!(headers["Authorization"], "token t0ken");
+    }
+
+    #[test]
+    fn merge_request_shape() {
+        let (url, payload) = merge_request("rust-lang/rust", 42, "squash").unwrap();
+        assert_eq!(
+            url,
+            "https://api.github.com/repos/rust-lang/rust/pulls/42/merge"
+        );
+        assert_eq!(payload, r#"{"merge_method":"squash"}"#);
+    }
+
+    #[test]
+    fn jwt_base64_is_url_safe_and_unpadded() {
+        assert_eq!(base64_url(&[0xfb, 0xff]), "-_8");
+        assert_eq!(base64_url(b"rfcbot"), "cmZjYm90");
+    }
+
+    #[test]
+    fn reaction_users_parsed() {
+        let reactions: Vec<ReactionFromJson> = serde_json::from_str(
+            r#"[
+                {
+                    "id": 2,
+                    "user": { "id": 2, "login": "thor" },
+                    "content": "+1",
+                    "created_at": "2018-06-21T06:28:54Z"
+                },
+                {
+                    "id": 3,
+                    "user": { "id": 3, "login": "loki" },
+                    "content": "-1",
+                    "created_at": "2018-06-20T06:28:54Z"
+                },
+                {
+                    "id": 1,
+                    "user": { "id": 1, "login": "hulk", "type": "User" },
+                    "content": "+1",
+                    "created_at": "2018-06-20T06:28:54Z"
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(reaction_logins(reactions, "+1"), vec!["hulk", "thor"]);
+    }
+
+    #[test]
+    fn issue_events_parsed() {
+        let events: Vec<IssueEventFromJson> = serde_json::from_str(
+            r#"[
+                {
+                    "event": "labeled",
+                    "actor": { "id": 1, "login": "thor" },
+                    "label": { "name": "T-lang", "color": "bfd4f2" },
+                    "created_at": "2018-06-20T06:28:54Z"
+                },
+                {
+                    "event": "closed",
+                    "actor": { "id": 1, "login": "thor" },
+                    "created_at": "2018-06-21T06:28:54Z"
+                },
+                {
+                    "event": "unlabeled",
+                    "actor": null,
+                    "label": { "name": "T-libs", "color": "bfd4f2" },
+                    "created_at": "2018-06-22T06:28:54Z"
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let changes = events
+            .into_iter()
+            .filter_map(IssueEventFromJson::label_change)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                LabelEvent {
+                    label: "T-lang".to_string(),
+                    added: true,
+                    actor: Some("thor".to_string()),
+                    created_at: Utc.ymd(2018, 6, 20).and_hms(6, 28, 54),
+                },
+                LabelEvent {
+                    label: "T-libs".to_string(),
+                    added: false,
+                    actor: None,
+                    created_at: Utc.ymd(2018, 6, 22).and_hms(6, 28, 54),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn timeline_cross_references_parsed() {
+        let events: Vec<TimelineEventFromJson> = serde_json::from_str(
+            r#"[
+                {
+                    "event": "cross-referenced",
+                    "actor": { "id": 1, "login": "thor" },
+                    "created_at": "2018-06-20T06:28:54Z",
+                    "source": {
+                        "type": "issue",
+                        "issue": {
+                            "number": 123,
+                            "title": "Implement the RFC",
+                            "repository_url": "https://api.github.com/repos/rust-lang/rust",
+                            "pull_request": {
+                                "url": "https://api.github.com/repos/rust-lang/rust/pulls/123",
+                                "merged_at": null
+                            }
+                        }
+                    }
+                },
+                {
+                    "event": "committed",
+                    "sha": "abc123"
+                },
+                {
+                    "event": "cross-referenced",
+                    "created_at": "2018-06-21T06:28:54Z",
+                    "source": {
+                        "type": "issue",
+                        "issue": {
+                            "number": 7,
+                            "repository_url": "https://api.github.com/repos/rust-lang/rfcs"
+                        }
+                    }
+                },
+                {
+                    "event": "labeled",
+                    "created_at": "2018-06-22T06:28:54Z",
+                    "label": { "name": "T-lang", "color": "bfd4f2" }
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let references = events
+            .into_iter()
+            .filter_map(TimelineEventFromJson::cross_reference)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            references,
+            vec![
+                CrossReference {
+                    repository: "rust-lang/rust".to_string(),
+                    number: 123,
+                    is_pull_request: true,
+                    created_at: Utc.ymd(2018, 6, 20).and_hms(6, 28, 54),
+                },
+                CrossReference {
+                    repository: "rust-lang/rfcs".to_string(),
+                    number: 7,
+                    is_pull_request: false,
+                    created_at: Utc.ymd(2018, 6, 21).and_hms(6, 28, 54),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_results_parsed() {
+        let results: SearchResultsFromJson<IssueFromJson> = serde_json::from_str(
+            r#"{
+                "total_count": 1,
+                "incomplete_results": false,
+                "items": [
+                    {
+                        "number": 2113,
+                        "user": { "id": 1, "login": "thor" },
+                        "assignee": null,
+                        "state": "open",
+                        "title": "Add a hammer",
+                        "body": null,
+                        "labels": [
+                            { "name": "final-comment-period", "color": "f9e189" },
+                            { "name": "disposition-merge", "color": "008800" }
+                        ],
+                        "milestone": null,
+                        "locked": false,
+                        "comments": 12,
+                        "pull_request": {
+                            "url": "https://api.github.com/repos/rust-lang/rfcs/pulls/2113"
+                        },
+                        "closed_at": null,
+                        "created_at": "2018-06-20T06:28:54Z",
+                        "updated_at": "2018-06-21T06:28:54Z",
+                        "comments_url": "https://api.github.com/repos/rust-lang/rfcs/issues/2113/comments",
+                        "score": 1.0
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(results.items.len(), 1);
+        let (issue, milestone) = results
+            .items
+            .into_iter()
+            .next()
+            .unwrap()
+            .with_repo("rust-lang/rfcs");
+        assert_eq!(issue.number, 2113);
+        assert!(issue.open);
+        assert!(issue.is_pull_request);
+        assert_eq!(
+            issue.labels,
+            vec!["final-comment-period", "disposition-merge"]
+        );
+        assert!(milestone.is_none());
+    }
+
+    #[test]
+    fn rate_limit_delay_from_headers() {
+        let now = Utc.timestamp(1_000, 0);
+
+        let retry_after = headers(&[("Retry-After", "30")]);
+        assert_eq!(
+            rate_limit_delay(StatusCode::FORBIDDEN, &retry_after, now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            rate_limit_delay(StatusCode::TOO_MANY_REQUESTS, &retry_after, now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(rate_limit_delay(StatusCode::OK, &retry_after, now), None);
+
+        let exhausted = headers(&[
+            ("X-RateLimit-Remaining", "0"),
+            ("X-RateLimit-Reset", "1060"),
+        ]);
+        assert_eq!(
+            rate_limit_delay(StatusCode::FORBIDDEN, &exhausted, now),
+            Some(Duration::from_secs(60))
+        );
+
+        let remaining = headers(&[
+            ("X-RateLimit-Remaining", "12"),
+            ("X-RateLimit-Reset", "1060"),
+        ]);
+        assert_eq!(
+            rate_limit_delay(StatusCode::FORBIDDEN, &remaining, now),
+            None
+        );
+
+        let next_week = headers(&[("Retry-After", "604800")]);
+        assert_eq!(
+            rate_limit_delay(StatusCode::FORBIDDEN, &next_week, now),
+            Some(MAX_RATE_LIMIT_WAIT)
+        );
+    }
+
+    #[test]
+    fn rate_limits_wait_for_later_without_waits() {
+        let now = Utc.timestamp(1_000, 0);
+        let delay = Some(Duration::from_secs(30));
+        assert_eq!(retry_delay(delay, now), delay);
+
+        {
+            let _no_waits = no_rate_limit_waits();
+            assert_eq!(retry_delay(delay, now), None);
         }
-        builder.send()
+        assert!(RATE_LIMITED_UNTIL.load(Ordering::SeqCst) >= 1_030);
+        assert_eq!(retry_delay(delay, now), delay);
+    }
+
+    #[test]
+    fn current_user_parsed() {
+        let user: GitHubUser = serde_json::from_str(
+            r#"{"login": "rfcbot", "id": 25931738, "type": "User", "site_admin": false}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            user,
+            GitHubUser {
+                id: 25931738,
+                login: "rfcbot".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn only_own_reactions_are_cleared() {
+        let reaction = |id, login: &str| ReactionFromJson {
+            id,
+            user: GitHubUser {
+                id,
+                login: login.to_string(),
+            },
+            content: "+1".to_string(),
+            created_at: Utc::now(),
+        };
+        let mut deleted = vec![];
+
+        let cleared = clear_own_reactions(
+            vec![1, 2, 3],
+            "rfcbot",
+            |comment_id| {
+                Ok(match comment_id {
+                    1 => vec![reaction(10, "rfcbot"), reaction(11, "thor")],
+                    2 => vec![reaction(20, "loki")],
+                    _ => vec![reaction(30, "rfcbot")],
+                })
+            },
+            |comment_id, reaction_id| {
+                deleted.push((comment_id, reaction_id));
+                Ok(())
+            },
+        );
+
+        assert_eq!(cleared.unwrap(), 2);
+        assert_eq!(deleted, vec![(1, 10), (3, 30)]);
+    }
+
+    #[test]
+    fn repo_names_parsed() {
+        let vals = serde_json::from_str(r#"[{"name": "dotfiles", "id": 1}, {"name": "blog"}]"#);
+        assert_eq!(
+            repo_names("octocat", vals.unwrap()).unwrap(),
+            vec!["octocat/dotfiles", "octocat/blog"]
+        );
+
+        let vals = serde_json::from_str(r#"[{"id": 1}]"#);
+        assert!(repo_names("octocat", vals.unwrap()).is_err());
+    }
+
+    #[test]
+    fn new_comments_are_created() {
+        let outcome = post_or_edit(None, "hi", || Ok(7), |_| panic!("nothing to edit"));
+        assert_eq!(outcome.unwrap(), PostOutcome::Created(7));
+    }
+
+    #[test]
+    fn changed_comments_are_edited() {
+        let outcome = post_or_edit(
+            Some((7, "hello")),
+            "hi",
+            || panic!("the comment exists"),
+            Ok,
+        );
+        assert_eq!(outcome.unwrap(), PostOutcome::Edited(7));
+    }
+
+    #[test]
+    fn unchanged_comments_are_not_sent() {
+        let outcome: DashResult<PostOutcome<i32>> = post_or_edit(
+            Some((7, "hi")),
+            "hi",
+            || panic!("the comment exists"),
+            |_| panic!("the comment is unchanged"),
+        );
+        let outcome = outcome.unwrap();
+        assert_eq!(outcome, PostOutcome::Unchanged);
+        assert_eq!(outcome.comment(), None);
+    }
+
+    #[test]
+    fn rate_limited_delete_is_retried() {
+        let now = Utc.timestamp(1_000, 0);
+        let mut responses = vec![
+            (StatusCode::NO_CONTENT, HeaderMap::new()),
+            (StatusCode::FORBIDDEN, headers(&[("Retry-After", "2")])),
+        ];
+        let mut waited = Vec::new();
+
+        let res: Result<_, ()> = retrying(
+            || Ok(responses.pop().unwrap()),
+            |&(status, ref headers)| rate_limit_delay(status, headers, now),
+            |d| waited.push(d),
+        );
+
+        assert_eq!(res.unwrap().0, StatusCode::NO_CONTENT);
+        assert!(responses.is_empty());
+        assert_eq!(waited, vec![Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn retries_give_up_eventually() {
+        let now = Utc.timestamp(1_000, 0);
+        let mut sent = 0;
+        let mut waits = 0;
+
+        let res: Result<_, ()> = retrying(
+            || {
+                sent += 1;
+                Ok((StatusCode::FORBIDDEN, headers(&[("Retry-After", "1")])))
+            },
+            |&(status, ref headers)| rate_limit_delay(status, headers, now),
+            |_| waits += 1,
+        );
+
+        assert_eq!(res.unwrap().0, StatusCode::FORBIDDEN);
+        assert_eq!(sent, MAX_RETRIES + 1);
+        assert_eq!(waits, MAX_RETRIES);
     }
 }
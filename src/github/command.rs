@@ -1,6 +1,9 @@
+use std::borrow::Cow;
 use std::collections::BTreeSet;
 use std::fmt;
 
+use chrono::NaiveDate;
+
 use crate::config::RFC_BOT_MENTION;
 use crate::error::{DashError, DashResult};
 use crate::teams::{RfcbotConfig, TeamLabel};
@@ -83,6 +86,108 @@ fn parse_command_text<'a>(command: &'a str, subcommand: &'a str) -> &'a str {
     command[name_start..].trim()
 }
 
+/// Each line of `text`, paired with everything after it.
+fn lines_with_following(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        Some(match current.find('\n') {
+            Some(idx) => {
+                rest = Some(&current[idx + 1..]);
+                (&current[..idx], &current[idx + 1..])
+            }
+            None => {
+                rest = None;
+                (current, "")
+            }
+        })
+    })
+}
+
+/// Splits the first line, trimmed, off of `text`.
+fn split_first_line(text: &str) -> (&str, &str) {
+    match text.find('\n') {
+        Some(idx) => (text[..idx].trim(), &text[idx + 1..]),
+        None => (text.trim(), ""),
+    }
+}
+
+/// Parses the name of a concern for the `concern` and `resolve` subcommands, returning it
+/// with the text of the comment following it.
+///
+/// The name is normally the remainder of the command line, but if nothing follows
+/// the keyword we fall back to the next line of the comment (unless that line is
+/// itself another bot command). Empty names are rejected.
+fn parse_concern_name<'a>(
+    command: &'a str,
+    subcommand: &'a str,
+    following: &'a str,
+) -> DashResult<(&'a str, &'a str)> {
+    let (next_line, after_next_line) = split_first_line(following);
+    let name = parse_command_text(command, subcommand);
+    let (name, following) = if name.is_empty() && !next_line.starts_with(RFC_BOT_MENTION) {
+        (next_line, after_next_line)
+    } else {
+        (name, following)
+    };
+
+    if name.is_empty() {
+        info!("rejecting `{}` command without a concern name", subcommand);
+        throw!(DashError::Misc(Some(
+            "no concern name specified".to_string()
+        )));
+    }
+
+    Ok((name, following))
+}
+
+/// Parses the description which can follow a concern's name: a block of quoted (`>`) or
+/// indented lines, or a fenced code block. It ends at a blank line, another bot command, or
+/// the first line which isn't part of the block.
+fn parse_concern_description(following: &str) -> Option<&str> {
+    let mut end = 0;
+    let mut in_fence = false;
+    for line in following.split('\n') {
+        let trimmed = line.trim();
+        if in_fence {
+            in_fence = !trimmed.starts_with("```");
+        } else if trimmed.starts_with("```") {
+            in_fence = true;
+        } else if trimmed.is_empty()
+            || trimmed.starts_with(RFC_BOT_MENTION)
+            || !(trimmed.starts_with('>') || line.starts_with(char::is_whitespace))
+        {
+            break;
+        }
+        end = (end + line.len() + 1).min(following.len());
+    }
+
+    Some(following[..end].trim_end()).filter(|description| !description.is_empty())
+}
+
+/// Splits an optional trailing `@reviewer` mention off of a concern name, e.g.
+/// `foo bar @alice` is the concern `foo bar` directed at `alice`.
+fn split_directed_mention(name: &str) -> (&str, Option<&str>) {
+    if let Some(idx) = name.rfind(char::is_whitespace) {
+        let (rest, mention) = (name[..idx].trim_end(), name[idx..].trim_start());
+        if mention.len() > 1 && mention.starts_with('@') && !rest.is_empty() {
+            return (rest, Some(&mention[1..]));
+        }
+    }
+    (name, None)
+}
+
+/// Splits a trailing `milestone <number>` off of a tracking label, e.g. `board milestone 3`.
+fn split_milestone(text: &str) -> (&str, Option<i32>) {
+    let mut words = text.rsplitn(3, char::is_whitespace);
+    if let (Some(number), Some("milestone")) = (words.next(), words.next()) {
+        if let Ok(milestone) = number.parse() {
+            return (words.next().unwrap_or_default().trim_end(), Some(milestone));
+        }
+    }
+    (text, None)
+}
+
 fn strip_prefix<'h>(haystack: &'h str, prefix: &str) -> &'h str {
     haystack
         .find(prefix)
@@ -115,8 +220,27 @@ fn match_team_candidate<'a>(
 /// postpone ::= "postpone" | "postponed" | "postponing" | "postpones" ;
 /// cancel ::= "cancel | "canceled" | "canceling" | "cancels" ;
 /// review ::= "reviewed" | "review" | "reviewing" | "reviews" ;
+/// reviewed_by ::= "reviewed-by" ;
+/// reviewed_for ::= "reviewed-for" ;
+/// unreview ::= "unreviewed" | "unreview" | "unreviewing" | "unreviews" ;
 /// concern ::= "concern" | "concerned" | "concerning" | "concerns" ;
 /// resolve ::= "resolve" | "resolved" | "resolving" | "resolves" ;
+/// resolve_all ::= "resolve-all" ;
+/// waive ::= "waive" | "waived" | "waiving" | "waives" ;
+/// confirm ::= "confirm" | "confirmed" | "confirming" | "confirms" ;
+/// snooze ::= "snooze" | "snoozed" | "snoozing" | "snoozes" ;
+/// version ::= "version" ;
+/// id ::= "id" ;
+/// history ::= "history" ;
+/// next ::= "next" ;
+/// mute ::= "mute" | "muted" | "muting" | "mutes" ;
+/// unmute ::= "unmute" | "unmuted" | "unmuting" | "unmutes" ;
+/// restore ::= "restore" | "restored" | "restoring" | "restores" ;
+/// hold ::= "hold" | "held" | "holding" | "holds" ;
+/// unhold ::= "unhold" | "unheld" | "unholding" | "unholds" ;
+/// track ::= "track" | "tracked" | "tracking" | "tracks" ;
+/// mirror ::= "mirror" | "mirrored" | "mirroring" | "mirrors" ;
+/// date ::= YYYY "-" MM "-" DD ;
 /// poll ::=  "ask" | "asked" | "asking" | "asks" |
 ///          "poll" | "polled" | "polling" | "polls" |
 ///          "query" | "queried" | "querying" | "queries" |
@@ -130,17 +254,35 @@ fn match_team_candidate<'a>(
 /// team_target ::= team_label | team_label_simple | team_ping ;
 ///
 /// line_remainder ::= .+$ ;
+/// next_line ::= ^.+$ ;
+/// concern_name ::= line_remainder | "\n" next_line ;
+/// concern_description ::= ("\n" quoted_line)+ | ("\n" indented_line)+ | "\n" fenced_block ;
 /// ws_separated ::= ... ;
 ///
-/// subcommand ::= merge | close | postpone | cancel | review
-///              | concern line_remainder
-///              | resolve line_remainder
+/// subcommand ::= merge | close | postpone | cancel | reviewed_by | unreview | confirm
+///              | version | id | history | next | mute | unmute | restore | hold | unhold
+///              | review [line_remainder]
+///              | concern concern_name ["@" username] [concern_description]
+///              | "concerns"
+///              | "feedback"
+///              | resolve concern_name
+///              | resolve_all
+///              | waive "@"? username
+///              | reviewed_for "@"? username
+///              | snooze days
+///              | track label ["milestone" number]
+///              | mirror "#"? number
 ///              | poll [team_target]* line_remainder
 ///              ;
 ///
 /// invocation ::= "fcp" subcommand
-///              | "pr" subcommand
+///              | "pr" [subcommand]
+///              | "fcp-" (merge | close | postpone | cancel)
 ///              | "f?" ws_separated
+///              | "f-done" ws_separated
+///              | "fcp-end" date
+///              | "fcp-end-now"
+///              | "echo" line_remainder
 ///              | subcommand
 ///              ;
 ///
@@ -149,6 +291,7 @@ fn parse_fcp_subcommand<'a>(
     setup: &'a RfcbotConfig,
     command: &'a str,
     subcommand: &'a str,
+    following: &'a str,
     fcp_context: bool,
 ) -> DashResult<RfcBotCommand<'a>> {
     Ok(match subcommand {
@@ -170,21 +313,149 @@ fn parse_fcp_subcommand<'a>(
         // Parse a FCP cancel command:
         "cancel" | "canceled" | "canceling" | "cancels" => RfcBotCommand::FcpCancel,
 
+        // Parse a request to list who has reviewed:
+        "reviewed-by" => RfcBotCommand::ListReviewed,
+
         // Parse a FCP reviewed command:
-        "reviewed" | "review" | "reviewing" | "reviews" => RfcBotCommand::Reviewed,
+        "reviewed" | "review" | "reviewing" | "reviews" => {
+            let note = parse_command_text(command, subcommand);
+            RfcBotCommand::Reviewed(Some(note).filter(|note| !note.is_empty()))
+        }
+
+        // Parse a review recorded on someone else's behalf:
+        "reviewed-for" => {
+            let user = parse_command_text(command, subcommand)
+                .split_whitespace()
+                .next()
+                .map(|user| user.trim_start_matches('@'))
+                .unwrap_or_default();
+
+            if user.is_empty() {
+                throw!(DashError::Misc(Some("no user specified".to_string())));
+            }
+
+            RfcBotCommand::ReviewedFor(user)
+        }
+
+        // Parse a FCP unreviewed command:
+        "unreviewed" | "unreview" | "unreviewing" | "unreviews" => RfcBotCommand::Unreviewed,
+
+        // Parse a request to list the concerns, which is `concerns` without a name:
+        "concerns" if parse_command_text(command, subcommand).is_empty() => {
+            debug!("Parsed command as ListConcerns");
+            RfcBotCommand::ListConcerns
+        }
+
+        // Parse a request to list the feedback requests, which is `feedback` on its own:
+        "feedback" if parse_command_text(command, subcommand).is_empty() => {
+            RfcBotCommand::ListFeedback
+        }
 
         // Parse a FCP concern command:
         "concern" | "concerned" | "concerning" | "concerns" => {
             debug!("Parsed command as NewConcern");
-            RfcBotCommand::NewConcern(parse_command_text(command, subcommand))
+            let (name, following) = parse_concern_name(command, subcommand, following)?;
+            let (name, directed_at) = split_directed_mention(name);
+            RfcBotCommand::NewConcern(name, directed_at, parse_concern_description(following))
         }
 
+        // Parse a request to resolve all of the author's concerns:
+        "resolve-all" => RfcBotCommand::ResolveAll,
+
         // Parse a FCP resolve command:
         "resolve" | "resolved" | "resolving" | "resolves" => {
             debug!("Parsed command as ResolveConcern");
-            RfcBotCommand::ResolveConcern(parse_command_text(command, subcommand))
+            RfcBotCommand::ResolveConcern(parse_concern_name(command, subcommand, following)?.0)
+        }
+
+        // Parse a FCP waive command:
+        "waive" | "waived" | "waiving" | "waives" => {
+            debug!("Parsed command as Waive");
+            let user = parse_command_text(command, subcommand)
+                .split_whitespace()
+                .next()
+                .map(|user| user.trim_start_matches('@'))
+                .unwrap_or_default();
+
+            if user.is_empty() {
+                throw!(DashError::Misc(Some("no user specified".to_string())));
+            }
+
+            RfcBotCommand::Waive(user)
         }
 
+        // Parse a snooze of reminder nags:
+        "snooze" | "snoozed" | "snoozing" | "snoozes" => {
+            debug!("Parsed command as Snooze");
+            let days = parse_command_text(command, subcommand)
+                .split_whitespace()
+                .next()
+                .and_then(|days| days.parse::<i64>().ok())
+                .filter(|&days| days > 0);
+
+            match days {
+                Some(days) => RfcBotCommand::Snooze(days),
+                None => throw!(DashError::Misc(Some(
+                    "no snooze length specified".to_string()
+                ))),
+            }
+        }
+
+        // Parse a request for the bot's version and configuration:
+        "version" => RfcBotCommand::Version,
+
+        // Parse a request for the proposal's id:
+        "id" => RfcBotCommand::ProposalId,
+
+        // Parse a request for the proposal's timeline:
+        "history" => RfcBotCommand::History,
+
+        // Parse a request for what's left to do on the proposal:
+        "next" => RfcBotCommand::NextSteps,
+
+        // Parse a request to stop or resume commenting on the issue:
+        "mute" | "muted" | "muting" | "mutes" => RfcBotCommand::Mute,
+        "unmute" | "unmuted" | "unmuting" | "unmutes" => RfcBotCommand::Unmute,
+
+        // Parse a request to set the proposal aside, or to pick it back up:
+        "hold" | "held" | "holding" | "holds" => RfcBotCommand::Hold,
+        "unhold" | "unheld" | "unholding" | "unholds" => RfcBotCommand::Unhold,
+
+        // Parse a request for a tracking label (and milestone):
+        "track" | "tracked" | "tracking" | "tracks" => {
+            debug!("Parsed command as Track");
+            let (label, milestone) = split_milestone(parse_command_text(command, subcommand));
+            if label.is_empty() {
+                throw!(DashError::Misc(Some(
+                    "no tracking label specified".to_string()
+                )));
+            }
+
+            RfcBotCommand::Track { label, milestone }
+        }
+
+        // Parse a request to show the proposal's status on another issue:
+        "mirror" | "mirrored" | "mirroring" | "mirrors" => {
+            debug!("Parsed command as Mirror");
+            let number = parse_command_text(command, subcommand)
+                .split_whitespace()
+                .next()
+                .and_then(|number| number.trim_start_matches('#').parse::<i32>().ok());
+
+            match number {
+                Some(number) => RfcBotCommand::Mirror(number),
+                None => throw!(DashError::Misc(Some(
+                    "no issue to mirror to specified".to_string()
+                ))),
+            }
+        }
+
+        // Parse a request to bring back a proposal which was cancelled when the issue closed:
+        "restore" | "restored" | "restoring" | "restores" => RfcBotCommand::Restore,
+
+        // Parse a confirmation of a queued FCP proposal:
+        "confirm" | "confirmed" | "confirming" | "confirms" => RfcBotCommand::Confirm,
+
         // Parse a StartPoll command:
         "ask" | "asked" | "asking" | "asks" | "poll" | "polled" | "polling" | "polls" | "query"
         | "queried" | "querying" | "queries" | "inquire" | "inquired" | "inquiring"
@@ -214,24 +485,88 @@ fn parse_fcp_subcommand<'a>(
     })
 }
 
-fn from_invocation_line<'a>(
+/// Zero-width characters which come along when a command is copied and pasted.
+const ZERO_WIDTH_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// HTML entities GitHub sometimes leaves in comment bodies. `&amp;` comes last so that
+/// escaped entities are only decoded once.
+const HTML_ENTITIES: &[(&str, &str)] = &[
+    ("&#64;", "@"),
+    ("&#x40;", "@"),
+    ("&commat;", "@"),
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&quot;", "\""),
+    ("&#39;", "'"),
+    ("&amp;", "&"),
+];
+
+/// Undoes the mangling that keeps a comment's bot mentions from being recognized: HTML
+/// entities and zero-width characters.
+pub fn normalize_command_text(body: &str) -> Cow<'_, str> {
+    if !body.contains(ZERO_WIDTH_CHARS) && !body.contains('&') {
+        return Cow::Borrowed(body);
+    }
+
+    let mut body = body.replace(ZERO_WIDTH_CHARS, "");
+    for (entity, decoded) in HTML_ENTITIES {
+        if body.contains(entity) {
+            body = body.replace(entity, decoded);
+        }
+    }
+    Cow::Owned(body)
+}
+
+/// Splits a line into the whitespace separated tokens following the bot mention (and an
+/// optional colon). Returns `None` if the line doesn't invoke rfcbot.
+pub fn tokenize_command_line(line: &str) -> Option<Vec<&str>> {
+    let line = line.trim();
+    if !line.starts_with(RFC_BOT_MENTION) {
+        return None;
+    }
+
+    let tokens = line[RFC_BOT_MENTION.len()..]
+        .trim_start()
+        .trim_start_matches(':')
+        .split_whitespace()
+        .collect::<Vec<_>>();
+
+    if tokens.is_empty() {
+        None
+    } else {
+        Some(tokens)
+    }
+}
+
+/// Parses the tokens of an invocation line into a command. Arguments which are free
+/// text, like concern names or poll questions, are taken from the original `command` line
+/// so that their whitespace is preserved, or from the text `following` the line.
+pub fn parse_invocation<'a>(
     setup: &'a RfcbotConfig,
     command: &'a str,
+    tokens: &[&'a str],
+    following: &'a str,
 ) -> DashResult<RfcBotCommand<'a>> {
-    let mut tokens = command
-        .trim_start_matches(RFC_BOT_MENTION)
-        .trim()
-        .trim_start_matches(':')
-        .trim()
-        .split_whitespace();
+    let mut tokens = tokens.iter().cloned();
     let invocation = tokens.next().ok_or(DashError::Misc(None))?;
     match invocation {
+        // `pr` means the same as `fcp` (whether or not the issue is a pull request), except
+        // that on its own it proposes to merge
         "fcp" | "pr" => {
-            let subcommand = tokens.next().ok_or(DashError::Misc(None))?;
+            let subcommand = match tokens.next() {
+                Some(subcommand) => subcommand,
+                None if invocation == "pr" => "merge",
+                None => throw!(DashError::Misc(None)),
+            };
 
             debug!("Parsed command as new FCP proposal");
 
-            parse_fcp_subcommand(setup, command, subcommand, true)
+            parse_fcp_subcommand(setup, command, subcommand, following, true)
+        }
+        // people often run the two together, e.g. `fcp-merge`
+        "fcp-merge" | "fcp-close" | "fcp-postpone" | "fcp-cancel" => {
+            let subcommand = invocation.trim_start_matches("fcp-");
+            parse_fcp_subcommand(setup, command, subcommand, following, true)
         }
         "f?" => {
             let user = tokens
@@ -244,7 +579,36 @@ fn from_invocation_line<'a>(
 
             Ok(RfcBotCommand::FeedbackRequest(&user[1..]))
         }
-        _ => parse_fcp_subcommand(setup, command, invocation, false),
+        "f-done" => {
+            let user = tokens
+                .next()
+                .map(|user| user.trim_start_matches('@'))
+                .unwrap_or_default();
+
+            if user.is_empty() {
+                throw!(DashError::Misc(Some("no user specified".to_string())));
+            }
+
+            Ok(RfcBotCommand::FeedbackDone(user))
+        }
+        "fcp-end" => {
+            let date = tokens.next().unwrap_or_default();
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| DashError::Misc(Some(format!("not a date: {}", date))))?;
+
+            Ok(RfcBotCommand::SetFcpEnd(date))
+        }
+        "fcp-end-now" => Ok(RfcBotCommand::EndFcpNow),
+        "echo" => {
+            let text = parse_command_text(command, invocation);
+            let tokens = tokens.collect::<Vec<_>>();
+            let parsed = parse_invocation(setup, text, &tokens, following)
+                .ok()
+                .map(Box::new);
+
+            Ok(RfcBotCommand::Echo { text, parsed })
+        }
+        _ => parse_fcp_subcommand(setup, command, invocation, following, false),
     }
 }
 
@@ -252,10 +616,55 @@ fn from_invocation_line<'a>(
 pub enum RfcBotCommand<'a> {
     FcpPropose(FcpDisposition),
     FcpCancel,
-    Reviewed,
-    NewConcern(&'a str),
+    /// An optional note to show with the review, e.g. `concern-free`.
+    Reviewed(Option<&'a str>),
+    ListReviewed,
+    Unreviewed,
+    /// Records a review for the named reviewer, e.g. one they gave in a meeting.
+    ReviewedFor(&'a str),
+    /// A concern's name, who it's directed at, and its description.
+    NewConcern(&'a str, Option<&'a str>, Option<&'a str>),
     ResolveConcern(&'a str),
+    ResolveAll,
+    ListConcerns,
+    Waive(&'a str),
+    /// Hides the proposal from its reviewers' nags (`/api/<user>` and `/fcp/<user>`) for this
+    /// many days. That's its only effect: the FCP still starts and finishes as usual.
+    Snooze(i64),
+    /// Sets the day a final comment period ends, instead of it lasting the usual length.
+    SetFcpEnd(NaiveDate),
+    /// Finishes a running final comment period straight away.
+    EndFcpNow,
+    Confirm,
+    Version,
+    ProposalId,
+    /// Replies with what has happened to the proposal so far, followed by who has reviewed it.
+    History,
+    /// Replies with what the proposal is waiting on before it's done.
+    NextSteps,
+    /// Stops rfcbot commenting on the issue. Only `Unmute` is processed while it's muted.
+    Mute,
+    Unmute,
+    /// Brings back the proposal which was cancelled when the issue was closed.
+    Restore,
+    /// Keeps the proposal out of its final comment period until it's taken off hold.
+    Hold,
+    Unhold,
+    /// Changes the proposal's tracking label, and optionally puts the issue in a milestone.
+    Track {
+        label: &'a str,
+        milestone: Option<i32>,
+    },
+    /// Keeps a read-only copy of the proposal's status on another issue, by its number.
+    Mirror(i32),
     FeedbackRequest(&'a str),
+    FeedbackDone(&'a str),
+    ListFeedback,
+    /// Reports how the rest of the line would be parsed, without running it.
+    Echo {
+        text: &'a str,
+        parsed: Option<Box<RfcBotCommand<'a>>>,
+    },
     StartPoll {
         teams: BTreeSet<&'a str>,
         question: &'a str,
@@ -263,17 +672,57 @@ pub enum RfcBotCommand<'a> {
 }
 
 impl<'a> RfcBotCommand<'a> {
+    /// The name used to refer to this kind of command in configuration, e.g. when listing
+    /// `PRIVILEGED_COMMANDS`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RfcBotCommand::FcpPropose(_) => "propose",
+            RfcBotCommand::FcpCancel => "cancel",
+            RfcBotCommand::Reviewed(_) => "reviewed",
+            RfcBotCommand::ListReviewed => "reviewed-by",
+            RfcBotCommand::Unreviewed => "unreviewed",
+            RfcBotCommand::ReviewedFor(_) => "reviewed-for",
+            RfcBotCommand::NewConcern(..) => "concern",
+            RfcBotCommand::ResolveConcern(_) => "resolve",
+            RfcBotCommand::ResolveAll => "resolve-all",
+            RfcBotCommand::ListConcerns => "concerns",
+            RfcBotCommand::Waive(_) => "waive",
+            RfcBotCommand::Snooze(_) => "snooze",
+            RfcBotCommand::SetFcpEnd(_) => "fcp-end",
+            RfcBotCommand::EndFcpNow => "fcp-end-now",
+            RfcBotCommand::Confirm => "confirm",
+            RfcBotCommand::Version => "version",
+            RfcBotCommand::ProposalId => "id",
+            RfcBotCommand::History => "history",
+            RfcBotCommand::NextSteps => "next",
+            RfcBotCommand::Mute => "mute",
+            RfcBotCommand::Unmute => "unmute",
+            RfcBotCommand::Restore => "restore",
+            RfcBotCommand::Hold => "hold",
+            RfcBotCommand::Unhold => "unhold",
+            RfcBotCommand::Track { .. } => "track",
+            RfcBotCommand::Mirror(_) => "mirror",
+            RfcBotCommand::FeedbackRequest(_) => "feedback",
+            RfcBotCommand::FeedbackDone(_) => "feedback-done",
+            RfcBotCommand::ListFeedback => "feedback-list",
+            RfcBotCommand::Echo { .. } => "echo",
+            RfcBotCommand::StartPoll { .. } => "poll",
+        }
+    }
+
+    /// Parses every command in a comment, which should first be passed through
+    /// `normalize_command_text`.
     pub fn from_str_all(
         setup: &'a RfcbotConfig,
         command: &'a str,
     ) -> impl Iterator<Item = RfcBotCommand<'a>> {
-        // Get the tokens for each command line (starts with a bot mention)
-        command
-            .lines()
-            .map(str::trim)
-            .filter(|&l| l.starts_with(RFC_BOT_MENTION))
-            .map(move |l| from_invocation_line(setup, l))
-            .filter_map(Result::ok)
+        // Get the tokens for each command line (starts with a bot mention), paired with
+        // the rest of the comment for commands whose arguments can follow the line
+        lines_with_following(command).filter_map(move |(l, following)| {
+            let l = l.trim();
+            let tokens = tokenize_command_line(l)?;
+            parse_invocation(setup, l, &tokens, following).ok()
+        })
     }
 }
 
@@ -305,7 +754,7 @@ foobar
             vec![
                 RfcBotCommand::ResolveConcern("CONCERN_NAME"),
                 RfcBotCommand::FcpCancel,
-                RfcBotCommand::NewConcern("foobar"),
+                RfcBotCommand::NewConcern("foobar", None, None),
             ]
         );
     }
@@ -329,11 +778,44 @@ foobar
             vec![
                 RfcBotCommand::ResolveConcern("CONCERN_NAME"),
                 RfcBotCommand::FcpCancel,
-                RfcBotCommand::NewConcern("foobar"),
+                RfcBotCommand::NewConcern("foobar", None, None),
             ]
         );
     }
 
+    #[test]
+    fn tokenize_whitespace() {
+        assert_eq!(
+            tokenize_command_line("@rfcbot\tfcp\tmerge"),
+            Some(vec!["fcp", "merge"])
+        );
+        assert_eq!(
+            tokenize_command_line("   @rfcbot:  fcp   merge   "),
+            Some(vec!["fcp", "merge"])
+        );
+        assert_eq!(tokenize_command_line("@rfcbot"), None);
+        assert_eq!(tokenize_command_line("@rfcbot :  "), None);
+        assert_eq!(tokenize_command_line("fcp merge"), None);
+    }
+
+    #[test]
+    fn parse_tab_separated_invocation() {
+        let line = "@rfcbot\tfcp\tmerge";
+        let tokens = tokenize_command_line(line).unwrap();
+        assert_eq!(
+            parse_invocation(&TEST_SETUP, line, &tokens, "").unwrap(),
+            RfcBotCommand::FcpPropose(FcpDisposition::Merge)
+        );
+    }
+
+    #[test]
+    fn unicode_concern_name() {
+        assert_eq!(
+            ensure_take_singleton(parse_commands("@rfcbot concern naïve ünïcode ☃  ")),
+            RfcBotCommand::NewConcern("naïve ünïcode ☃", None, None)
+        );
+    }
+
     #[test]
     fn fix_issue_225() {
         let text = r#"
@@ -353,7 +835,7 @@ foobar
             vec![
                 RfcBotCommand::ResolveConcern("CONCERN_NAME"),
                 RfcBotCommand::FcpCancel,
-                RfcBotCommand::NewConcern("foobar"),
+                RfcBotCommand::NewConcern("foobar", None, None),
             ]
         );
     }
@@ -422,7 +904,39 @@ somemoretext"
             "pr review",
             "pr reviewing"
         ],
-        RfcBotCommand::Reviewed
+        RfcBotCommand::Reviewed(None)
+    );
+
+    test_from_str!(
+        success_fcp_reviewed_with_note,
+        ["reviewed", "fcp reviewed", "pr review"],
+        some_text!("concern-free"),
+        RfcBotCommand::Reviewed(Some("concern-free"))
+    );
+
+    #[test]
+    fn review_notes_end_with_the_line() {
+        assert_eq!(
+            parse_commands("@rfcbot reviewed   with a nit or two  \nthanks!").collect::<Vec<_>>(),
+            vec![RfcBotCommand::Reviewed(Some("with a nit or two"))]
+        );
+    }
+
+    test_from_str!(
+        success_fcp_unreviewed,
+        [
+            "unreviewed",
+            "unreview",
+            "unreviewing",
+            "unreviews",
+            "fcp unreviewed",
+            "fcp unreview",
+            "fcp unreviewing",
+            "pr unreviewed",
+            "pr unreview",
+            "pr unreviewing"
+        ],
+        RfcBotCommand::Unreviewed
     );
 
     test_from_str!(
@@ -445,6 +959,21 @@ somemoretext"
         RfcBotCommand::FcpPropose(FcpDisposition::Merge)
     );
 
+    test_from_str!(
+        success_pr_alone,
+        ["pr", "pr\n\nsome justification"],
+        RfcBotCommand::FcpPropose(FcpDisposition::Merge)
+    );
+
+    #[test]
+    fn fcp_alone_needs_a_subcommand() {
+        assert_eq!(parse_commands("@rfcbot fcp").next(), None);
+        assert_eq!(
+            parse_commands("@rfcbot: fcp\n\nsome justification").next(),
+            None
+        );
+    }
+
     test_from_str!(
         success_fcp_close,
         [
@@ -522,7 +1051,132 @@ somemoretext"
             "pr concerns"
         ],
         some_text!("CONCERN_NAME"),
-        RfcBotCommand::NewConcern("CONCERN_NAME")
+        RfcBotCommand::NewConcern("CONCERN_NAME", None, None)
+    );
+
+    test_from_str!(
+        success_list_concerns,
+        ["concerns", "fcp concerns", "pr concerns"],
+        RfcBotCommand::ListConcerns
+    );
+
+    test_from_str!(
+        success_list_reviewed,
+        ["reviewed-by", "fcp reviewed-by", "pr reviewed-by"],
+        RfcBotCommand::ListReviewed
+    );
+
+    #[test]
+    fn reviewed_by_is_not_reviewed() {
+        assert_eq!(
+            parse_commands("@rfcbot reviewed-by\n@rfcbot reviewed").collect::<Vec<_>>(),
+            vec![RfcBotCommand::ListReviewed, RfcBotCommand::Reviewed(None)]
+        );
+    }
+
+    #[test]
+    fn hyphenated_fcp_commands() {
+        for &(body, disposition) in &[
+            ("@rfcbot fcp-merge", FcpDisposition::Merge),
+            ("@rfcbot fcp-close", FcpDisposition::Close),
+            ("@rfcbot: fcp-postpone", FcpDisposition::Postpone),
+        ] {
+            assert_eq!(
+                ensure_take_singleton(parse_commands(body)),
+                RfcBotCommand::FcpPropose(disposition)
+            );
+        }
+        assert_eq!(
+            ensure_take_singleton(parse_commands("@rfcbot fcp-cancel")),
+            RfcBotCommand::FcpCancel
+        );
+
+        // only the FCP commands themselves can be run together
+        assert_eq!(parse_commands("@rfcbot fcp-reviewed").count(), 0);
+    }
+
+    #[test]
+    fn bare_dispositions_propose_fcp() {
+        for &(body, disposition) in &[
+            ("@rfcbot merge", FcpDisposition::Merge),
+            ("@rfcbot: close", FcpDisposition::Close),
+            ("@rfcbot postpone", FcpDisposition::Postpone),
+        ] {
+            assert_eq!(
+                ensure_take_singleton(parse_commands(body)),
+                RfcBotCommand::FcpPropose(disposition)
+            );
+        }
+    }
+
+    test_from_str!(
+        success_resolve_all,
+        ["resolve-all", "fcp resolve-all", "pr resolve-all"],
+        RfcBotCommand::ResolveAll
+    );
+
+    #[test]
+    fn html_entity_mentions_parse() {
+        for body in &[
+            "&#64;rfcbot merge",
+            "&commat;rfcbot: merge",
+            "&#x40;rfcbot merge",
+        ] {
+            let body = normalize_command_text(body);
+            assert_eq!(
+                ensure_take_singleton(parse_commands(&body)),
+                RfcBotCommand::FcpPropose(FcpDisposition::Merge)
+            );
+        }
+
+        assert_eq!(normalize_command_text("a &amp;#64; b"), "a &#64; b");
+    }
+
+    #[test]
+    fn zero_width_spaces_are_ignored() {
+        let body = normalize_command_text("\u{200B}@rfc\u{200B}bot concern naming\u{FEFF}");
+        assert_eq!(
+            ensure_take_singleton(parse_commands(&body)),
+            RfcBotCommand::NewConcern("naming", None, None)
+        );
+
+        assert!(matches!(
+            normalize_command_text("@rfcbot merge"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn list_concerns_ignores_next_line() {
+        assert_eq!(
+            parse_commands("@rfcbot concerns\nthis isn't a concern name").collect::<Vec<_>>(),
+            vec![RfcBotCommand::ListConcerns]
+        );
+        assert_eq!(
+            parse_commands("@rfcbot concern\nnaming").collect::<Vec<_>>(),
+            vec![RfcBotCommand::NewConcern("naming", None, None)]
+        );
+    }
+
+    test_from_str!(
+        success_concern_directed,
+        ["concern", "fcp concern", "pr concern"],
+        some_text!("CONCERN NAME @thor"),
+        RfcBotCommand::NewConcern("CONCERN NAME", Some("thor"), None)
+    );
+
+    test_from_str!(
+        success_concern_mention_in_name,
+        ["concern", "fcp concern"],
+        some_text!("ask @thor about CONCERN_NAME"),
+        RfcBotCommand::NewConcern("ask @thor about CONCERN_NAME", None, None)
+    );
+
+    test_from_str!(
+        success_concern_only_mention,
+        ["concern", "fcp concern"],
+        some_text!("@thor"),
+        RfcBotCommand::NewConcern("@thor", None, None)
     );
 
     test_from_str!(
@@ -631,6 +1285,186 @@ somemoretext"
         }
     );
 
+    test_from_str!(
+        success_waive,
+        [
+            "waive",
+            "waived",
+            "waiving",
+            "waives",
+            "fcp waive",
+            "fcp waived",
+            "pr waive",
+            "pr waived"
+        ],
+        some_text!("@thor"),
+        RfcBotCommand::Waive("thor")
+    );
+
+    test_from_str!(
+        success_reviewed_for,
+        ["reviewed-for", "fcp reviewed-for", "pr reviewed-for"],
+        some_text!("@thor"),
+        RfcBotCommand::ReviewedFor("thor")
+    );
+
+    #[test]
+    fn reviewed_for_needs_a_user() {
+        assert_eq!(
+            ensure_take_singleton(parse_commands("@rfcbot reviewed-for thor")),
+            RfcBotCommand::ReviewedFor("thor")
+        );
+        assert_eq!(parse_commands("@rfcbot reviewed-for").next(), None);
+        assert_eq!(parse_commands("@rfcbot reviewed-for @").next(), None);
+    }
+
+    #[test]
+    fn reject_waive_without_user() {
+        assert_eq!(parse_commands("@rfcbot waive").next(), None);
+        assert_eq!(parse_commands("@rfcbot fcp waive @").next(), None);
+    }
+
+    test_from_str!(
+        success_snooze,
+        [
+            "snooze",
+            "snoozed",
+            "snoozing",
+            "snoozes",
+            "fcp snooze",
+            "pr snooze"
+        ],
+        some_text!("14 days"),
+        RfcBotCommand::Snooze(14)
+    );
+
+    #[test]
+    fn reject_snooze_without_days() {
+        assert_eq!(parse_commands("@rfcbot snooze").next(), None);
+        assert_eq!(parse_commands("@rfcbot snooze forever").next(), None);
+        assert_eq!(parse_commands("@rfcbot fcp snooze 0").next(), None);
+    }
+
+    test_from_str!(
+        success_version,
+        ["version", "fcp version"],
+        RfcBotCommand::Version
+    );
+
+    test_from_str!(success_mute, ["mute", "fcp mute"], RfcBotCommand::Mute);
+
+    test_from_str!(success_hold, ["hold", "fcp hold"], RfcBotCommand::Hold);
+
+    test_from_str!(
+        success_unhold,
+        ["unhold", "fcp unhold"],
+        RfcBotCommand::Unhold
+    );
+
+    test_from_str!(
+        success_unmute,
+        ["unmute", "fcp unmute"],
+        RfcBotCommand::Unmute
+    );
+
+    test_from_str!(
+        success_restore,
+        ["restore", "fcp restore", "restored"],
+        RfcBotCommand::Restore
+    );
+
+    test_from_str!(
+        success_track,
+        [
+            "track project-board",
+            "fcp track project-board",
+            "tracking  project-board "
+        ],
+        RfcBotCommand::Track {
+            label: "project-board",
+            milestone: None,
+        }
+    );
+
+    test_from_str!(
+        success_track_milestone,
+        ["track lang board milestone 12"],
+        RfcBotCommand::Track {
+            label: "lang board",
+            milestone: Some(12),
+        }
+    );
+
+    test_from_str!(
+        success_history,
+        ["history", "fcp history", "pr history"],
+        RfcBotCommand::History
+    );
+
+    test_from_str!(success_next, ["next", "fcp next"], RfcBotCommand::NextSteps);
+
+    test_from_str!(
+        success_mirror,
+        ["mirror #123", "fcp mirror 123", "mirroring  #123 please"],
+        RfcBotCommand::Mirror(123)
+    );
+
+    #[test]
+    fn mirror_needs_an_issue() {
+        assert!(parse_commands("@rfcbot mirror").next().is_none());
+        assert!(parse_commands("@rfcbot mirror #abc").next().is_none());
+    }
+
+    #[test]
+    fn track_needs_a_label() {
+        assert!(parse_commands("@rfcbot track").next().is_none());
+        assert!(parse_commands("@rfcbot track milestone 12")
+            .next()
+            .is_none());
+        // without a number, "milestone" is just part of the label
+        assert_eq!(
+            ensure_take_singleton(parse_commands("@rfcbot track next milestone")),
+            RfcBotCommand::Track {
+                label: "next milestone",
+                milestone: None,
+            }
+        );
+    }
+
+    #[test]
+    fn fcp_end_takes_a_date() {
+        assert_eq!(
+            ensure_take_singleton(parse_commands("@rfcbot fcp-end 2024-06-01")),
+            RfcBotCommand::SetFcpEnd(NaiveDate::from_ymd(2024, 6, 1))
+        );
+        assert_eq!(parse_commands("@rfcbot fcp-end 2024-13-01").count(), 0);
+        assert_eq!(parse_commands("@rfcbot fcp-end next week").count(), 0);
+        assert_eq!(parse_commands("@rfcbot fcp-end").count(), 0);
+    }
+
+    test_from_str!(
+        success_fcp_end_now,
+        ["fcp-end-now"],
+        RfcBotCommand::EndFcpNow
+    );
+
+    test_from_str!(success_id, ["id", "fcp id"], RfcBotCommand::ProposalId);
+
+    test_from_str!(
+        success_confirm,
+        [
+            "confirm",
+            "confirmed",
+            "confirming",
+            "confirms",
+            "fcp confirm",
+            "fcp confirmed",
+            "pr confirm",
+            "pr confirmed"
+        ],
+        RfcBotCommand::Confirm
+    );
+
     #[test]
     fn success_resolve_mid_body() {
         let body = "someothertext
@@ -652,10 +1486,141 @@ somemoretext";
         assert_eq!(with_colon, RfcBotCommand::ResolveConcern("CONCERN_NAME"));
     }
 
+    #[test]
+    fn reject_empty_concern_name() {
+        assert_eq!(parse_commands("@rfcbot concern").next(), None);
+        assert_eq!(parse_commands("@rfcbot: resolved   ").next(), None);
+        assert_eq!(
+            parse_commands("@rfcbot concern\n@rfcbot resolve").next(),
+            None
+        );
+    }
+
+    #[test]
+    fn concern_without_description() {
+        assert_eq!(
+            parse_commands("@rfcbot concern naming\nI don't like the name.").collect::<Vec<_>>(),
+            vec![RfcBotCommand::NewConcern("naming", None, None)]
+        );
+        assert_eq!(
+            parse_commands("@rfcbot concern naming\n\n> quoted later").collect::<Vec<_>>(),
+            vec![RfcBotCommand::NewConcern("naming", None, None)]
+        );
+    }
+
+    #[test]
+    fn concern_description_blocks() {
+        let quoted = "@rfcbot concern naming\n> It's too long.\n> And too vague.\n\nThanks!";
+        assert_eq!(
+            parse_commands(quoted).collect::<Vec<_>>(),
+            vec![RfcBotCommand::NewConcern(
+                "naming",
+                None,
+                Some("> It's too long.\n> And too vague.")
+            )]
+        );
+
+        let indented =
+            "@rfcbot concern naming @thor\n    too long\n    too vague\n@rfcbot reviewed";
+        assert_eq!(
+            parse_commands(indented).collect::<Vec<_>>(),
+            vec![
+                RfcBotCommand::NewConcern(
+                    "naming",
+                    Some("thor"),
+                    Some("    too long\n    too vague")
+                ),
+                RfcBotCommand::Reviewed(None),
+            ]
+        );
+
+        let fenced = "@rfcbot concern breakage\n```rust\nlet x = 1;\n\n@rfcbot\n```\nafter";
+        assert_eq!(
+            parse_commands(fenced).next(),
+            Some(RfcBotCommand::NewConcern(
+                "breakage",
+                None,
+                Some("```rust\nlet x = 1;\n\n@rfcbot\n```")
+            ))
+        );
+
+        let name_on_next_line = "@rfcbot concern\nnaming\n> too long";
+        assert_eq!(
+            parse_commands(name_on_next_line).collect::<Vec<_>>(),
+            vec![RfcBotCommand::NewConcern(
+                "naming",
+                None,
+                Some("> too long")
+            )]
+        );
+    }
+
+    #[test]
+    fn concern_name_on_next_line() {
+        let text = "@rfcbot concern\nfoo bar\nsomeothertext\n@rfcbot: resolved\n  baz quux";
+
+        assert_eq!(
+            parse_commands(text).collect::<Vec<_>>(),
+            vec![
+                RfcBotCommand::NewConcern("foo bar", None, None),
+                RfcBotCommand::ResolveConcern("baz quux"),
+            ]
+        );
+    }
+
     test_from_str!(
         success_feedback,
         ["f?"],
         some_text!("@bob"),
         RfcBotCommand::FeedbackRequest("bob")
     );
+
+    test_from_str!(
+        success_feedback_done,
+        ["f-done"],
+        some_text!("@bob"),
+        RfcBotCommand::FeedbackDone("bob")
+    );
+
+    test_from_str!(
+        success_list_feedback,
+        ["feedback"],
+        RfcBotCommand::ListFeedback
+    );
+
+    #[test]
+    fn feedback_list_is_not_a_request() {
+        assert_eq!(
+            parse_commands("@rfcbot f? @bob\n@rfcbot feedback").collect::<Vec<_>>(),
+            vec![
+                RfcBotCommand::FeedbackRequest("bob"),
+                RfcBotCommand::ListFeedback,
+            ]
+        );
+        assert_eq!(parse_commands("@rfcbot feedback @bob").next(), None);
+    }
+
+    test_from_str!(
+        success_echo,
+        ["echo concern too-big"],
+        RfcBotCommand::Echo {
+            text: "concern too-big",
+            parsed: Some(Box::new(RfcBotCommand::NewConcern("too-big", None, None))),
+        }
+    );
+
+    test_from_str!(
+        echo_unparseable,
+        ["echo frobnicate"],
+        RfcBotCommand::Echo {
+            text: "frobnicate",
+            parsed: None,
+        }
+    );
+
+    #[test]
+    fn reject_feedback_done_without_user() {
+        assert_eq!(parse_commands("@rfcbot f-done").next(), None);
+        assert_eq!(parse_commands("@rfcbot f-done @").next(), None);
+    }
 }
@@ -9,14 +9,24 @@ pub mod webhooks;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
+use diesel::result::DatabaseErrorKind;
+use diesel::result::Error::DatabaseError;
 
+use crate::config::CONFIG;
 use crate::domain::github::*;
 use crate::domain::schema::*;
 use crate::error::DashResult;
 use crate::DB_POOL;
 
-use self::client::Client;
-use self::models::{CommentFromJson, IssueFromJson, PullRequestFromJson};
+use self::client::{Client, GithubClient};
+use self::command::Label;
+use self::models::{
+    CommentFromJson, CrossReference, IssueFromJson, PullRequestFromJson, ReactionFromJson,
+};
+pub use self::nag::{
+    fcp_end, force_close_proposal, preview_command, reconcile_proposal, reset_reviews,
+    resync_reviews, start_edit_flusher, update_nags_from_body, CommandPreview,
+};
 
 lazy_static! {
     pub static ref GH: Client = Client::new();
@@ -61,6 +71,101 @@ pub fn record_successful_update(ingest_start: NaiveDateTime) -> DashResult<()> {
     Ok(())
 }
 
+/// Where the scrape of `repo` picks up from: the start of its last successful scrape, or
+/// `default` if it hasn't been scraped on its own yet.
+pub fn repo_cursor(repo: &str, default: DateTime<Utc>) -> DashResult<DateTime<Utc>> {
+    let conn = &*DB_POOL.get()?;
+    let updated = repo_scrape_cursors::table
+        .find(repo)
+        .first::<RepoScrapeCursor>(conn)
+        .optional()?;
+
+    Ok(updated.map_or(default, |cursor| DateTime::from_utc(cursor.updated_at, Utc)))
+}
+
+/// Moves `repo`'s cursor up to the start of a scrape which ingested it successfully.
+pub fn record_repo_cursor(repo: &str, ingest_start: NaiveDateTime) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+    let cursor = RepoScrapeCursor {
+        repository: repo.to_string(),
+        updated_at: ingest_start,
+    };
+
+    diesel::insert_into(repo_scrape_cursors::table)
+        .values(&cursor)
+        .on_conflict(repo_scrape_cursors::repository)
+        .do_update()
+        .set(repo_scrape_cursors::updated_at.eq(ingest_start))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Moves everything stored under a configured repository's name over to its new one, for
+/// repositories which were renamed on GitHub. Otherwise their proposals would be left behind on
+/// the old rows while GitHub redirects us to the new name.
+pub fn update_renamed_repos(watched: &[String]) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+    update_renamed_repos_with(conn, watched, |repo| GH.repo_full_name(repo))
+}
+
+fn update_renamed_repos_with<F>(
+    conn: &PgConnection,
+    watched: &[String],
+    full_name: F,
+) -> DashResult<()>
+where
+    F: Fn(&str) -> DashResult<String>,
+{
+    for old in watched {
+        let new = ok_or_continue!(full_name(old), why =>
+            warn!("Unable to look up the current name of {}: {:?}", old, why));
+        if new.eq_ignore_ascii_case(old) {
+            continue;
+        }
+
+        warn!(
+            "{} has been renamed to {}, updating stored references (the configuration should be \
+             updated too)",
+            old, new
+        );
+        ok_or_continue!(rename_repo(conn, old, &new), why => match why {
+            DatabaseError(DatabaseErrorKind::UniqueViolation, _) => warn!(
+                "Unable to move {} to {}, some of its rows are already stored under the new name",
+                old, new
+            ),
+            why => error!("Unable to move {} to {}: {:?}", old, new, why),
+        });
+    }
+
+    Ok(())
+}
+
+/// Renames every reference to a repository in one transaction, so that a conflict with rows
+/// already stored under the new name leaves everything as it was.
+fn rename_repo(conn: &PgConnection, old: &str, new: &str) -> diesel::QueryResult<()> {
+    conn.transaction(|| {
+        diesel::update(issue::table.filter(issue::repository.eq(old)))
+            .set(issue::repository.eq(new))
+            .execute(conn)?;
+        diesel::update(issuecomment::table.filter(issuecomment::repository.eq(old)))
+            .set(issuecomment::repository.eq(new))
+            .execute(conn)?;
+        diesel::update(pullrequest::table.filter(pullrequest::repository.eq(old)))
+            .set(pullrequest::repository.eq(new))
+            .execute(conn)?;
+        diesel::update(milestone::table.filter(milestone::repository.eq(old)))
+            .set(milestone::repository.eq(new))
+            .execute(conn)?;
+        diesel::update(issue_links::table.filter(issue_links::linked_repository.eq(old)))
+            .set(issue_links::linked_repository.eq(new))
+            .execute(conn)?;
+        diesel::update(repo_scrape_cursors::table.filter(repo_scrape_cursors::repository.eq(old)))
+            .set(repo_scrape_cursors::repository.eq(new))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
 pub fn ingest_since(repo: &str, start: DateTime<Utc>) -> DashResult<()> {
     info!("fetching all {} issues and comments since {}", repo, start);
     let issues = GH.issues_since(repo, start)?;
@@ -96,13 +201,35 @@ pub fn ingest_since(repo: &str, start: DateTime<Utc>) -> DashResult<()> {
     debug!("let's insert some stuff in the database");
 
     // make sure we have all of the users to ensure referential integrity
+    let mut issue_numbers = vec![];
+    let mut open_issue_numbers = vec![];
     for issue in issues {
         let issue_number = issue.number;
+        issue_numbers.push(issue_number);
+        if issue.state == "open" {
+            open_issue_numbers.push(issue_number);
+        }
         ok_or!(handle_issue(conn, issue, repo), why =>
             error!("Error processing issue {}#{}: {:?}",
                    repo, issue_number, why));
     }
 
+    if CONFIG.github_scrape_timelines {
+        for issue_number in issue_numbers {
+            ok_or!(ingest_timeline(conn, repo, issue_number), why =>
+                error!("Error processing the timeline of {}#{}: {:?}",
+                       repo, issue_number, why));
+        }
+    }
+
+    if CONFIG.github_scrape_reactions {
+        for issue_number in open_issue_numbers {
+            ok_or!(ingest_reactions(conn, repo, issue_number), why =>
+                error!("Error counting the reactions on {}#{}: {:?}",
+                       repo, issue_number, why));
+        }
+    }
+
     // insert the comments
     for comment in comments {
         let comment_id = comment.id;
@@ -120,6 +247,116 @@ pub fn ingest_since(repo: &str, start: DateTime<Utc>) -> DashResult<()> {
     Ok(())
 }
 
+/// Creates placeholder proposals for the open issues in `repo` which are already in FCP but
+/// aren't tracked, e.g. because they were labelled before rfcbot was deployed there.
+pub fn bootstrap_fcps(repo: &str) -> DashResult<()> {
+    let query = format!("repo:{} is:open label:{}", repo, Label::FCP.as_str());
+    let issues = GH.search_issues(&query)?;
+    info!("found {} issues in FCP in {}", issues.len(), repo);
+
+    let conn = &*DB_POOL.get()?;
+    for issue in issues {
+        let issue_number = issue.number;
+        // the search results might not have been scraped yet, but their bodies' commands are
+        // left alone as they've most likely been acted on by hand
+        ok_or_continue!(store_issue(conn, issue, repo), why =>
+            error!("Unable to store {}#{} while bootstrapping: {:?}", repo, issue_number, why));
+        ok_or_continue!(nag::bootstrap_proposal(repo, issue_number), why =>
+            error!("Unable to bootstrap a proposal for {}#{}: {:?}",
+                   repo, issue_number, why));
+    }
+
+    Ok(())
+}
+
+/// Deletes rfcbot's own reactions from the comments on an issue, returning how many there were.
+pub fn clear_bot_reactions(repo: &str, issue_num: i32) -> DashResult<usize> {
+    let login = GH.login()?;
+    let deleted = GH.clear_reactions(repo, issue_num, &login)?;
+    info!(
+        "deleted {} reactions by {} from {}#{}",
+        deleted, login, repo, issue_num
+    );
+    Ok(deleted)
+}
+
+/// Record the issues and pull requests which have referenced an issue in the database.
+fn ingest_timeline(conn: &PgConnection, repo: &str, issue_number: i32) -> DashResult<()> {
+    let issue_id = issue::table
+        .filter(issue::repository.eq(repo))
+        .filter(issue::number.eq(issue_number))
+        .select(issue::id)
+        .first::<i32>(conn)?;
+    let references = GH.issue_timeline(repo, issue_number)?;
+    handle_cross_references(conn, issue_id, references)
+}
+
+/// Record the tally of the reactions on an issue in the database, replacing the last one.
+fn ingest_reactions(conn: &PgConnection, repo: &str, issue_number: i32) -> DashResult<()> {
+    let issue_id = issue::table
+        .filter(issue::repository.eq(repo))
+        .filter(issue::number.eq(issue_number))
+        .select(issue::id)
+        .first::<i32>(conn)?;
+    let reactions = GH.issue_reactions(repo, issue_number)?;
+    let tally = tally_reactions(issue_id, &reactions, Utc::now().naive_utc());
+
+    diesel::insert_into(issue_reactions::table)
+        .values(&tally)
+        .on_conflict(issue_reactions::fk_issue)
+        .do_update()
+        .set(&tally)
+        .execute(conn)?;
+    Ok(())
+}
+
+fn tally_reactions(
+    issue_id: i32,
+    reactions: &[ReactionFromJson],
+    now: NaiveDateTime,
+) -> IssueReactions {
+    let count = |content: &str| reactions.iter().filter(|r| r.content == content).count() as i32;
+    IssueReactions {
+        fk_issue: issue_id,
+        thumbs_up: count("+1"),
+        thumbs_down: count("-1"),
+        hooray: count("hooray"),
+        updated_at: now,
+    }
+}
+
+pub fn handle_cross_references(
+    conn: &PgConnection,
+    issue_id: i32,
+    references: Vec<CrossReference>,
+) -> DashResult<()> {
+    let links = references
+        .into_iter()
+        .map(|reference| IssueLinkPartial {
+            fk_issue: issue_id,
+            linked_repository: reference.repository,
+            linked_number: reference.number,
+            linked_is_pull_request: reference.is_pull_request,
+            created_at: reference.created_at.naive_utc(),
+        })
+        .collect::<Vec<_>>();
+    if links.is_empty() {
+        return Ok(());
+    }
+
+    // the same issue is often referenced several times, only the first is kept
+    diesel::insert_into(issue_links::table)
+        .values(&links)
+        .on_conflict((
+            issue_links::fk_issue,
+            issue_links::linked_repository,
+            issue_links::linked_number,
+        ))
+        .do_nothing()
+        .execute(conn)?;
+    Ok(())
+}
+
 pub fn handle_pr(conn: &PgConnection, pr: PullRequestFromJson, repo: &str) -> DashResult<()> {
     use crate::domain::schema::pullrequest::dsl::*;
     if let Some(ref assignee) = pr.assignee {
@@ -168,6 +405,25 @@ pub fn handle_comment(conn: &PgConnection, comment: CommentFromJson, repo: &str)
 }
 
 pub fn handle_issue(conn: &PgConnection, issue: IssueFromJson, repo: &str) -> DashResult<()> {
+    let (previous_body, stored) = store_issue(conn, issue, repo)?;
+
+    // commands can be written in the body too, so look again whenever it's edited (but not when
+    // the issue is first seen, which may be long after they were written)
+    if let Some(previous_body) = previous_body.filter(|previous| previous != &stored.body) {
+        ok_or!(nag::update_nags_from_body(&stored, &previous_body), why =>
+            error!("Problem updating FCPs from an issue body: {:?}", &why));
+    }
+
+    Ok(())
+}
+
+/// Upserts an issue along with its users and milestone, returning the body it had before (if it
+/// was already known) and the stored issue.
+fn store_issue(
+    conn: &PgConnection,
+    issue: IssueFromJson,
+    repo: &str,
+) -> DashResult<(Option<String>, Issue)> {
     // user handling
     handle_user(conn, &issue.user)?;
     if let Some(ref assignee) = issue.assignee {
@@ -192,15 +448,22 @@ pub fn handle_issue(conn: &PgConnection, issue: IssueFromJson, repo: &str) -> Da
     {
         use crate::domain::schema::issue::dsl::*;
 
-        diesel::insert_into(issue)
+        let previous_body = issue
+            .filter(repository.eq(&i.repository))
+            .filter(number.eq(i.number))
+            .select(body)
+            .first::<String>(conn)
+            .optional()?;
+
+        let stored = diesel::insert_into(issue)
             .values(&i)
             .on_conflict((repository, number))
             .do_update()
             .set(&i)
-            .execute(conn)?;
-    }
+            .get_result::<Issue>(conn)?;
 
-    Ok(())
+        Ok((previous_body, stored))
+    }
 }
 
 pub fn handle_user(conn: &PgConnection, user: &GitHubUser) -> DashResult<()> {
@@ -218,6 +481,39 @@ mod tests {
     use super::*;
     use std::env;
 
+    #[test]
+    fn reactions_tallied_by_content() {
+        let reaction = |id, content: &str| ReactionFromJson {
+            id,
+            user: GitHubUser {
+                id,
+                login: format!("user{}", id),
+            },
+            content: content.to_string(),
+            created_at: Utc::now(),
+        };
+        let reactions = vec![
+            reaction(1, "+1"),
+            reaction(2, "+1"),
+            reaction(3, "-1"),
+            reaction(4, "hooray"),
+            reaction(5, "heart"),
+            reaction(6, "+1"),
+        ];
+        let now = Utc::now().naive_utc();
+
+        assert_eq!(
+            tally_reactions(7, &reactions, now),
+            IssueReactions {
+                fk_issue: 7,
+                thumbs_up: 3,
+                thumbs_down: 1,
+                hooray: 1,
+                updated_at: now,
+            }
+        );
+    }
+
     #[test]
     fn test_handle_user() {
         crate::utils::setup_test_env();
@@ -255,4 +551,186 @@ mod tests {
             .execute(&conn)
             .expect("Failed to clear database");
     }
+
+    #[test]
+    fn cross_references_become_links() {
+        use chrono::{Datelike, TimeZone};
+
+        crate::utils::setup_test_env();
+        let conn = &*DB_POOL.get().expect("Unable to get a test connection");
+
+        let user = GitHubUser {
+            id: -3,
+            login: "rfcbot-links-test".to_string(),
+        };
+        handle_user(conn, &user).expect("Unable to handle user!");
+
+        let now = Utc::now().naive_utc();
+        let rfc = IssuePartial {
+            number: 1,
+            fk_milestone: None,
+            fk_user: user.id,
+            fk_assignee: None,
+            open: true,
+            is_pull_request: false,
+            title: "An RFC".to_string(),
+            body: String::new(),
+            locked: false,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+            labels: vec![],
+            repository: "rfcbot-rs/links".to_string(),
+        };
+        let inserted = diesel::insert_into(issue::table)
+            .values(&rfc)
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+
+        let reference = |number, day| CrossReference {
+            repository: "rfcbot-rs/implementation".to_string(),
+            number,
+            is_pull_request: true,
+            created_at: Utc.ymd(2018, 6, day).and_hms(0, 0, 0),
+        };
+        let handled = handle_cross_references(
+            conn,
+            inserted.id,
+            vec![reference(5, 1), reference(6, 2), reference(5, 3)],
+        )
+        .and_then(|_| handle_cross_references(conn, inserted.id, vec![reference(6, 4)]));
+        let links = issue_links::table
+            .filter(issue_links::fk_issue.eq(inserted.id))
+            .order(issue_links::linked_number)
+            .load::<IssueLink>(conn);
+
+        // Clean up after ourselves
+        diesel::delete(issue::table.find(inserted.id))
+            .execute(conn)
+            .expect("Failed to clear database");
+        diesel::delete(githubuser::table.find(user.id))
+            .execute(conn)
+            .expect("Failed to clear database");
+
+        handled.expect("Unable to record cross-references");
+        let links = links.expect("Unable to load links");
+        assert_eq!(
+            links
+                .iter()
+                .map(|l| (&*l.linked_repository, l.linked_number, l.created_at.day()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("rfcbot-rs/implementation", 5, 1),
+                ("rfcbot-rs/implementation", 6, 2)
+            ]
+        );
+        assert!(links.iter().all(|l| l.linked_is_pull_request));
+    }
+
+    #[test]
+    fn renamed_repos_are_updated() {
+        crate::utils::setup_test_env();
+        let conn = &*DB_POOL.get().expect("Unable to get a test connection");
+
+        let user = GitHubUser {
+            id: -2,
+            login: "rfcbot-rename-test".to_string(),
+        };
+        handle_user(conn, &user).expect("Unable to handle user!");
+
+        let now = Utc::now().naive_utc();
+        let old_issue = IssuePartial {
+            number: 1,
+            fk_milestone: None,
+            fk_user: user.id,
+            fk_assignee: None,
+            open: true,
+            is_pull_request: false,
+            title: "An issue".to_string(),
+            body: String::new(),
+            locked: false,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+            labels: vec![],
+            repository: "rfcbot-rs/old-name".to_string(),
+        };
+        let inserted = diesel::insert_into(issue::table)
+            .values(&old_issue)
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+        // an issue which stays put, referenced from the renamed repo
+        let linking = diesel::insert_into(issue::table)
+            .values(&IssuePartial {
+                repository: "rfcbot-rs/elsewhere".to_string(),
+                ..old_issue.clone()
+            })
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+        diesel::insert_into(issue_links::table)
+            .values(&IssueLinkPartial {
+                fk_issue: linking.id,
+                linked_repository: "rfcbot-rs/old-name".to_string(),
+                linked_number: 1,
+                linked_is_pull_request: false,
+                created_at: now,
+            })
+            .execute(conn)
+            .expect("Unable to insert test link");
+        // renamed to a repo which already has an issue with the same number
+        let clashing = diesel::insert_into(issue::table)
+            .values(&IssuePartial {
+                repository: "rfcbot-rs/taken-name".to_string(),
+                ..old_issue.clone()
+            })
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+        let clashing_old = diesel::insert_into(issue::table)
+            .values(&IssuePartial {
+                repository: "rfcbot-rs/clashing-name".to_string(),
+                ..old_issue.clone()
+            })
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+
+        let watched = vec![
+            "rfcbot-rs/clashing-name".to_string(),
+            "rfcbot-rs/old-name".to_string(),
+        ];
+        let looked_up = std::cell::RefCell::new(vec![]);
+        let updated = update_renamed_repos_with(conn, &watched, |repo| {
+            looked_up.borrow_mut().push(repo.to_string());
+            Ok(match repo {
+                "rfcbot-rs/old-name" => "rfcbot-rs/new-name".to_string(),
+                "rfcbot-rs/clashing-name" => "rfcbot-rs/taken-name".to_string(),
+                other => other.to_string(),
+            })
+        });
+        let renamed = issue::table.find(inserted.id).first::<Issue>(conn);
+        let links = issue_links::table
+            .filter(issue_links::fk_issue.eq(linking.id))
+            .select(issue_links::linked_repository)
+            .load::<String>(conn);
+        let not_renamed = issue::table.find(clashing_old.id).first::<Issue>(conn);
+
+        // Clean up after ourselves
+        diesel::delete(issue_links::table.filter(issue_links::fk_issue.eq(linking.id)))
+            .execute(conn)
+            .expect("Failed to clear database");
+        for &id in &[inserted.id, linking.id, clashing.id, clashing_old.id] {
+            diesel::delete(issue::table.find(id))
+                .execute(conn)
+                .expect("Failed to clear database");
+        }
+        diesel::delete(githubuser::table.find(user.id))
+            .execute(conn)
+            .expect("Failed to clear database");
+
+        updated.expect("Unable to update renamed repos");
+        // only the configured repos are looked up, and a clash doesn't stop the others
+        assert_eq!(*looked_up.borrow(), watched);
+        assert_eq!(renamed.unwrap().repository, "rfcbot-rs/new-name");
+        assert_eq!(links.unwrap(), vec!["rfcbot-rs/new-name"]);
+        assert_eq!(not_renamed.unwrap().repository, "rfcbot-rs/clashing-name");
+    }
 }
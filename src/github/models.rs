@@ -45,9 +45,96 @@ impl MilestoneFromJson {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct IssueEventFromJson {
+    pub event: String,
+    pub actor: Option<GitHubUser>,
+    pub label: Option<LabelFromJson>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IssueEventFromJson {
+    /// The label change recorded by this event, if it is one.
+    pub fn label_change(self) -> Option<LabelEvent> {
+        let added = match &*self.event {
+            "labeled" => true,
+            "unlabeled" => false,
+            _ => return None,
+        };
+
+        Some(LabelEvent {
+            label: self.label?.name,
+            added,
+            actor: self.actor.map(|a| a.login),
+            created_at: self.created_at,
+        })
+    }
+}
+
+/// An entry in an issue's timeline. Only the parts of cross-references are kept, as every other
+/// kind of event is ignored.
+#[derive(Debug, Deserialize)]
+pub struct TimelineEventFromJson {
+    pub event: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub source: Option<TimelineSourceFromJson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineSourceFromJson {
+    pub issue: Option<TimelineIssueFromJson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineIssueFromJson {
+    pub number: i32,
+    /// e.g. `https://api.github.com/repos/rust-lang/rust`
+    pub repository_url: String,
+    /// Only present on pull requests.
+    pub pull_request: Option<serde_json::Value>,
+}
+
+impl TimelineEventFromJson {
+    /// The issue or pull request which referenced this one, if this event records one.
+    pub fn cross_reference(self) -> Option<CrossReference> {
+        if self.event != "cross-referenced" {
+            return None;
+        }
+
+        let issue = self.source?.issue?;
+        let mut segments = issue.repository_url.rsplit('/');
+        let name = segments.next()?;
+        let owner = segments.next()?;
+        Some(CrossReference {
+            repository: format!("{}/{}", owner, name),
+            number: issue.number,
+            is_pull_request: issue.pull_request.is_some(),
+            created_at: self.created_at?,
+        })
+    }
+}
+
+/// Another issue or pull request mentioning an issue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossReference {
+    pub repository: String,
+    pub number: i32,
+    pub is_pull_request: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A label being added to or removed from an issue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelEvent {
+    pub label: String,
+    pub added: bool,
+    pub actor: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LabelFromJson {
-    name: String,
+    pub name: String,
     color: String,
 }
 
@@ -101,6 +188,12 @@ impl IssueFromJson {
     }
 }
 
+/// The envelope the search API wraps each page of its results in.
+#[derive(Debug, Deserialize)]
+pub struct SearchResultsFromJson<M> {
+    pub items: Vec<M>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CommentFromJson {
     pub id: i32,
@@ -151,6 +244,14 @@ impl CommentFromJson {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReactionFromJson {
+    pub id: i32,
+    pub user: GitHubUser,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PullRequestFromJson {
     pub number: i32,
@@ -1,52 +1,170 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Mutex;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use diesel::pg::PgConnection;
 use diesel::prelude::*;
 
 use itertools::Itertools;
 
 use super::GH;
+use crate::config::{Config, FcpDurations, CONFIG, DEFAULT_COMMENT_FOOTER};
 use crate::domain::github::{GitHubUser, Issue, IssueComment};
 use crate::domain::rfcbot::{
-    FcpConcern, FcpProposal, FcpReviewRequest, FeedbackRequest, NewFcpConcern, NewFcpProposal,
-    NewFcpReviewRequest, NewFeedbackRequest, NewPoll, NewPollResponseRequest, Poll,
-    PollResponseRequest,
+    ArchivedProposal, FcpConcern, FcpMirror, FcpProposal, FcpReviewRequest, FeedbackRequest,
+    NewArchivedProposal, NewFcpConcern, NewFcpMirror, NewFcpProposal, NewFcpReviewRequest,
+    NewFeedbackRequest, NewPendingCommentPost, NewPoll, NewPollResponseRequest, NewProposedCommand,
+    PendingCommentPost, Poll, PollResponseRequest, ProposalState, ProposedCommand,
 };
 use crate::domain::schema::{
-    fcp_concern, fcp_proposal, fcp_review_request, githubuser, issue, issuecomment, poll,
-    poll_response_request,
+    archived_proposals, fcp_concern, fcp_mirrors, fcp_proposal, fcp_review_request, githubuser,
+    issue, issuecomment, pending_comment_posts, poll, poll_response_request, proposed_command,
 };
 use crate::error::{DashError, DashResult};
-use crate::github::models::CommentFromJson;
-use crate::teams::SETUP;
+use crate::github::client::{self, GithubClient, PostOutcome};
+use crate::github::models::LabelEvent;
+use crate::teams::{RfcbotConfig, SETUP};
 use crate::DB_POOL;
 
-use crate::github::command::{FcpDisposition, Label, RfcBotCommand};
+use crate::github::command::{normalize_command_text, FcpDisposition, Label, RfcBotCommand};
 
 impl Issue {
-    fn remove_label(&self, label: Label) {
-        let _ = GH.remove_label(&self.repository, self.number, label.as_str());
+    fn remove_label(&self, label: Label) { self.remove_label_named(label.as_str()) }
+
+    /// Adds the label, succeeding without touching the issue if it's already there.
+    fn add_label(&self, label: Label) -> DashResult<()> { self.add_label_named(label.as_str()) }
+
+    fn remove_label_named(&self, label: &str) {
+        let _ = apply_label(self.current_labels().as_deref(), label, false, || {
+            github().remove_label(&self.repository, self.number, label)
+        });
+    }
+
+    fn add_label_named(&self, label: &str) -> DashResult<()> {
+        apply_label(self.current_labels().as_deref(), label, true, || {
+            github().add_label(&self.repository, self.number, label)
+        })
+    }
+
+    /// Puts the proposal's tracking label, if it has one, on the issue.
+    fn add_tracking_label(&self, proposal: &FcpProposal) -> DashResult<()> {
+        apply_tracking_label(self.current_labels().as_deref(), proposal, |label| {
+            github().add_label(&self.repository, self.number, label)
+        })
+    }
+
+    fn remove_tracking_label(&self, proposal: &FcpProposal) {
+        if let Some(ref label) = proposal.tracking_label {
+            self.remove_label_named(label);
+        }
     }
 
-    fn add_label(&self, label: Label) -> DashResult<()> {
-        GH.add_label(&self.repository, self.number, label.as_str())
+    fn current_labels(&self) -> Option<Vec<String>> {
+        Some(
+            ok_or!(github().issue_labels(&self.repository, self.number), why => {
+                warn!("Unable to fetch labels of {}#{}: {:?}", self.repository, self.number, why);
+                return None;
+            }),
+        )
     }
 
     fn close(&self) {
-        ok_or!(GH.close_issue(&self.repository, self.number), why =>
+        ok_or!(github().close_issue(&self.repository, self.number), why =>
             error!("Unable to close issue {:?}: {:?}", self, why));
     }
 }
 
+/// Runs `change` to add (`present`) or remove a label, unless the issue's `current` labels show
+/// it's already in that state. If we don't know the current labels we try the change anyway.
+fn apply_label<F>(
+    current: Option<&[String]>,
+    label: &str,
+    present: bool,
+    change: F,
+) -> DashResult<()>
+where
+    F: FnOnce() -> DashResult<()>,
+{
+    let has_label = current.map(|labels| labels.iter().any(|l| l == label));
+    if has_label == Some(present) {
+        Ok(())
+    } else {
+        change()
+    }
+}
+
+/// Runs `add` with the proposal's tracking label, unless it doesn't have one or the issue's
+/// `current` labels already include it.
+fn apply_tracking_label<F>(
+    current: Option<&[String]>,
+    proposal: &FcpProposal,
+    add: F,
+) -> DashResult<()>
+where
+    F: FnOnce(&str) -> DashResult<()>,
+{
+    match proposal.tracking_label {
+        Some(ref label) => apply_label(current, label, true, || add(label)),
+        None => Ok(()),
+    }
+}
+
+/// The version of rfcbot that's running, and the commit it was built from if that was
+/// provided at build time.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const COMMIT: Option<&str> = option_env!("RFCBOT_COMMIT");
+
 lazy_static! {
     static ref NAG_LOCK: Mutex<()> = Mutex::new(());
+    static ref STATUS_EDITS: Mutex<EditCoalescer> = Mutex::new(EditCoalescer::new(
+        Duration::seconds(STATUS_EDIT_INTERVAL_SECS)
+    ));
+}
+
+/// Held while the nag code runs, see `lock_nags`.
+struct NagLock {
+    _lock: std::sync::LockResult<std::sync::MutexGuard<'static, ()>>,
+    _no_waits: client::NoRateLimitWaits,
+}
+
+/// Takes `NAG_LOCK`, first waiting for any rate limit to pass. While it's held, rate limited
+/// requests fail rather than wait, so nobody is left waiting on the lock while its holder sleeps.
+fn lock_nags() -> NagLock {
+    client::wait_for_rate_limit();
+    NagLock {
+        _lock: NAG_LOCK.lock(),
+        _no_waits: client::no_rate_limit_waits(),
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// A stand-in for GitHub installed by a test with `test::use_fake_github`.
+    static FAKE_GITHUB: std::cell::Cell<Option<&'static dyn GithubClient>> =
+        std::cell::Cell::new(None);
+}
+
+/// The client the nag code sends its requests to GitHub with.
+fn github() -> &'static dyn GithubClient {
+    match fake_github() {
+        Some(fake) => fake,
+        None => &*GH,
+    }
 }
 
-// TODO check if new subteam label added for existing proposals
+#[cfg(not(test))]
+fn fake_github() -> Option<&'static dyn GithubClient> { None }
+
+#[cfg(test)]
+fn fake_github() -> Option<&'static dyn GithubClient> { FAKE_GITHUB.with(|fake| fake.get()) }
+
+/// Are comments and labels sent to GitHub? They always are when it's a test's fake.
+fn posting_enabled() -> bool { CONFIG.post_comments || fake_github().is_some() }
+
+sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
 
 pub fn update_nags(comment: &IssueComment) -> DashResult<()> {
-    let _in_progress_marker = NAG_LOCK.lock();
+    let _in_progress_marker = lock_nags();
 
     let conn = &*DB_POOL.get()?;
 
@@ -56,17 +174,33 @@ pub fn update_nags(comment: &IssueComment) -> DashResult<()> {
         .find(comment.fk_user)
         .first::<GitHubUser>(conn)?;
 
+    if is_ignored_author(&author.login, &CONFIG.ignored_authors) {
+        debug!("ignoring comment {} from {}", comment.id, author.login);
+        return Ok(());
+    }
+
+    // `RFCBOT_LOGIN` covers us if this lookup fails, e.g. when running as a GitHub App
+    let bot_login = ok_or!(github().login(), why => {
+        debug!("Unable to look up our own login: {:?}", why);
+        String::new()
+    });
+    if is_own_comment(&author.login, &bot_login) {
+        debug!("ignoring our own comment {}", comment.id);
+        return Ok(());
+    }
+
     let subteam_members = subteam_members(&issue)?;
     let all_team_members = all_team_members()?;
 
     // Attempt to parse all commands out of the comment
     let mut any = false;
     let teams = SETUP.read().unwrap();
-    for command in RfcBotCommand::from_str_all(&teams, &comment.body) {
+    let body = normalize_command_text(&comment.body);
+    for command in RfcBotCommand::from_str_all(&teams, &body) {
         any = true;
 
-        if let RfcBotCommand::StartPoll { .. } = command {
-            // Accept poll requests from any known user.
+        if let RfcBotCommand::StartPoll { .. } | RfcBotCommand::Echo { .. } = command {
+            // Accept poll requests and parse checks from any known user.
             if all_team_members.iter().find(|&u| u == &author).is_none() {
                 info!("poll requester ({}) is not a known user", author.login);
                 return Ok(());
@@ -75,6 +209,15 @@ pub fn update_nags(comment: &IssueComment) -> DashResult<()> {
             // Don't accept most bot commands from non-subteam members.
             // Early return because we'll just get here again...
             if subteam_members.iter().find(|&u| u == &author).is_none() {
+                // Proposals from outsiders are queued until a member confirms them.
+                if let RfcBotCommand::FcpPropose(disp) = command {
+                    ok_or!(queue_fcp_proposal(&author, &issue, comment, disp), why => {
+                        let context =
+                            format!("Unable to queue FCP proposal for comment id {}", comment.id);
+                        report_error(&context, &why);
+                    });
+                }
+
                 info!(
                     "command author ({}) doesn't appear in any relevant subteams",
                     author.login
@@ -83,11 +226,42 @@ pub fn update_nags(comment: &IssueComment) -> DashResult<()> {
             }
         }
 
+        // Some commands can be restricted to admins by configuration.
+        if !command_allowed(
+            command.name(),
+            &author.login,
+            &CONFIG.privileged_commands,
+            &CONFIG.admins,
+        ) {
+            info!(
+                "command author ({}) isn't allowed to run `{}`",
+                author.login,
+                command.name()
+            );
+            let refusal = CommentType::CommandRefused {
+                author: &author,
+                command: command.name(),
+            };
+            ok_or!(RfcBotComment::new(&issue, refusal).post(None), why =>
+                error!("Unable to post refusal for comment id {}: {:?}", comment.id, why));
+            continue;
+        }
+
+        if issue.muted && command != RfcBotCommand::Unmute {
+            info!(
+                "ignoring `{}` on muted issue {}#{}",
+                command.name(),
+                issue.repository,
+                issue.number
+            );
+            continue;
+        }
+
         debug!("processing rfcbot command: {:?}", &command);
         let process = command.process(&author, &issue, comment, &subteam_members);
         ok_or!(process, why => {
-            error!("Unable to process command for comment id {}: {:?}",
-                comment.id, why);
+            let context = format!("Unable to process command for comment id {}", comment.id);
+            report_error(&context, &why);
             return Ok(());
         });
 
@@ -105,7 +279,384 @@ pub fn update_nags(comment: &IssueComment) -> DashResult<()> {
     Ok(())
 }
 
-fn update_proposal_review_status(proposal_id: i32) -> DashResult<()> {
+/// Process commands written in an issue's body rather than in a comment, as its author. Only
+/// lines added since `previous_body` are looked at, so an edit doesn't replay older commands.
+///
+/// Proposals need an initiating comment, so the body is stored as a comment of its own with the
+/// negated issue id.
+pub fn update_nags_from_body(issue: &Issue, previous_body: &str) -> DashResult<()> {
+    let comment = body_comment(issue);
+    let added = IssueComment {
+        body: added_lines(previous_body, &comment.body),
+        ..comment.clone()
+    };
+    {
+        let teams = SETUP.read().unwrap();
+        let body = normalize_command_text(&added.body);
+        if RfcBotCommand::from_str_all(&teams, &body).next().is_none() {
+            return Ok(());
+        }
+    }
+
+    let conn = &*DB_POOL.get()?;
+    diesel::insert_into(issuecomment::table)
+        .values(&comment)
+        .on_conflict(issuecomment::id)
+        .do_update()
+        .set(&comment)
+        .execute(conn)?;
+
+    update_nags(&added)
+}
+
+/// The lines of `current` which weren't already in `previous`.
+fn added_lines(previous: &str, current: &str) -> String {
+    let previous = previous.lines().map(str::trim).collect::<BTreeSet<_>>();
+    current
+        .lines()
+        .filter(|line| !previous.contains(line.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn body_comment(issue: &Issue) -> IssueComment {
+    IssueComment {
+        id: -issue.id,
+        fk_issue: issue.id,
+        fk_user: issue.fk_user,
+        body: issue.body.clone(),
+        created_at: issue.created_at,
+        updated_at: issue.updated_at,
+        repository: issue.repository.clone(),
+    }
+}
+
+/// Re-fetch a proposal's status comment from GitHub and re-evaluate it, for when the stored
+/// copy has fallen out of sync (e.g. a box was checked while a webhook was missed).
+pub fn reconcile_proposal(proposal_id: i32) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+
+    let proposal: FcpProposal = fcp_proposal::table.find(proposal_id).first(conn)?;
+    let stored: IssueComment = issuecomment::table
+        .find(proposal.fk_bot_tracking_comment)
+        .first(conn)?;
+
+    let fetched = github().get_comment(&stored.repository, stored.id)?;
+    let reconciled = reconciled_comment(stored, fetched.body, fetched.updated_at.naive_utc());
+    diesel::update(issuecomment::table.find(reconciled.id))
+        .set(&reconciled)
+        .execute(conn)?;
+
+    {
+        let _in_progress_marker = lock_nags();
+        update_proposal_review_status(proposal.id)?;
+        evaluate_nags();
+    }
+
+    Ok(())
+}
+
+/// Re-reads the checked boxes of every pending proposal's status comment as stored, e.g. after
+/// the way they're parsed has changed. Returns how many reviews were newly marked as done; the
+/// status comments themselves are brought up to date by the next evaluation.
+pub fn resync_reviews() -> DashResult<usize> {
+    let _in_progress_marker = lock_nags();
+    let conn = &*DB_POOL.get()?;
+
+    let pending = fcp_proposal::table
+        .filter(fcp_proposal::state.eq(ProposalState::Pending.repr()))
+        .select(fcp_proposal::id)
+        .load::<i32>(conn)?;
+
+    let mut marked = 0;
+    for proposal_id in pending {
+        marked += ok_or_continue!(update_proposal_review_status(proposal_id), why =>
+            error!("Unable to resync the reviews of proposal {}: {:?}", proposal_id, why));
+    }
+    info!("resyncing reviews marked {} as done", marked);
+
+    Ok(marked)
+}
+
+/// Closes a proposal which can't finish on its own, e.g. because its status comment was deleted
+/// and can't be recovered.
+pub fn force_close_proposal(proposal_id: i32) -> DashResult<()> {
+    let _in_progress_marker = lock_nags();
+    let conn = &*DB_POOL.get()?;
+
+    let proposal = force_close(conn, proposal_id)?;
+    let issue: Issue = issue::table.find(proposal.fk_issue).first(conn)?;
+    warn!(
+        "proposal {} on {}#{} was closed by hand",
+        proposal.id, issue.repository, issue.number
+    );
+    issue.remove_tracking_label(&proposal);
+
+    ok_or!(RfcBotComment::new(&issue, CommentType::FcpForceClosed).post(None), why =>
+        warn!("Unable to post the closing comment for proposal {}: {:?}", proposal.id, why));
+
+    Ok(())
+}
+
+/// Marks a proposal as closed without going through its usual transitions.
+fn force_close(conn: &PgConnection, proposal_id: i32) -> DashResult<FcpProposal> {
+    let mut proposal: FcpProposal = fcp_proposal::table.find(proposal_id).first(conn)?;
+    proposal.force_close()?;
+    diesel::update(fcp_proposal::table.find(proposal.id))
+        .set(&proposal)
+        .execute(conn)?;
+
+    Ok(proposal)
+}
+
+/// Starts tracking an FCP which began before rfcbot was watching the repository, i.e. an issue
+/// with the FCP label but no proposal. The placeholder proposal starts when the label was added,
+/// has no reviewers or concerns, and posts a new status comment to track it. Returns whether a
+/// proposal was created.
+pub fn bootstrap_proposal(repo: &str, number: i32) -> DashResult<bool> {
+    let _in_progress_marker = lock_nags();
+    let conn = &*DB_POOL.get()?;
+
+    let issue = issue::table
+        .filter(issue::repository.eq(repo))
+        .filter(issue::number.eq(number))
+        .first::<Issue>(conn)?;
+    if existing_proposal(&issue)?.is_some() {
+        return Ok(false);
+    }
+    if !posting_enabled() {
+        throw!(DashError::Misc(Some(
+            "placeholder proposals need a status comment, but posting is disabled".to_string()
+        )));
+    }
+
+    let events = github().issue_events(repo, number)?;
+    let labelled = last_labelled(&events, Label::FCP.as_str());
+    let fcp_start = labelled
+        .map_or_else(Utc::now, |event| event.created_at)
+        .naive_utc();
+    let initiator = match labelled.and_then(|event| event.actor.as_ref()) {
+        Some(login) => github().get_user(login)?,
+        None => github().current_user()?,
+    };
+    super::handle_user(conn, &initiator)?;
+
+    let disposition = labelled_disposition(&issue.labels);
+    let fcp_end = fcp_start
+        + Duration::days(fcp_length_days(
+            CONFIG.fcp_durations_for(&issue.repository),
+            disposition,
+        ));
+    let status_comment = post_insert_comment(
+        &issue,
+        CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition,
+            reviewers: &[],
+            concerns: &[],
+            teams: &[],
+            min_distinct_reviewers: CONFIG.min_distinct_reviewers,
+            fcp_end: Some(fcp_end),
+            state: shown_state(ProposalState::InFcp),
+        },
+    )?;
+
+    // there's no comment which proposed it, so the status comment stands in for one
+    let proposal = NewFcpProposal {
+        fk_issue: issue.id,
+        fk_initiator: initiator.id,
+        fk_initiating_comment: status_comment.id,
+        fk_bot_tracking_comment: status_comment.id,
+        disposition: disposition.repr(),
+        fcp_start: Some(fcp_start),
+        snooze_until: None,
+        state: ProposalState::InFcp.repr(),
+        almost_ready_notified: false,
+        fcp_end_override: None,
+        tracking_label: CONFIG.tracking_label.as_deref(),
+    };
+    let proposal = diesel::insert_into(fcp_proposal::table)
+        .values(&proposal)
+        .get_result::<FcpProposal>(conn)?;
+    info!(
+        "created placeholder proposal {} for the FCP on {}#{}",
+        proposal.id, repo, number
+    );
+
+    ok_or!(issue.add_tracking_label(&proposal), why =>
+        warn!("Unable to add the tracking label to {}#{}: {:?}", repo, number, why));
+
+    Ok(true)
+}
+
+/// The most recent time `label` was added to an issue, given its label events in chronological
+/// order.
+fn last_labelled<'e>(events: &'e [LabelEvent], label: &str) -> Option<&'e LabelEvent> {
+    events
+        .iter()
+        .rev()
+        .find(|event| event.added && event.label == label)
+}
+
+/// The disposition named by an issue's labels, for FCPs which rfcbot didn't see proposed.
+/// Issues without a disposition label are assumed to be merging.
+fn labelled_disposition(labels: &[String]) -> FcpDisposition {
+    [FcpDisposition::Close, FcpDisposition::Postpone]
+        .iter()
+        .cloned()
+        .find(|disposition| labels.iter().any(|l| l == disposition.label().as_str()))
+        .unwrap_or(FcpDisposition::Merge)
+}
+
+/// New commits on a pull request can invalidate the reviews of its pending proposal, so
+/// everyone but the proposer is asked to review it again.
+pub fn reset_reviews(repo: &str, number: i32) -> DashResult<()> {
+    let _in_progress_marker = lock_nags();
+    let conn = &*DB_POOL.get()?;
+
+    let issue = issue::table
+        .filter(issue::repository.eq(repo))
+        .filter(issue::number.eq(number))
+        .first::<Issue>(conn)
+        .optional()?;
+    let issue = match issue {
+        Some(issue) => issue,
+        None => return Ok(()),
+    };
+
+    let proposal = match existing_proposal(&issue)? {
+        Some(proposal) if proposal.state()? == ProposalState::Pending => proposal,
+        _ => return Ok(()),
+    };
+
+    let reviews = list_review_requests(proposal.id)?;
+    let reset = reviews_to_reset(proposal.fk_initiator, &reviews);
+    if reset.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "resetting {} reviews of proposal {} after a push to {}#{}",
+        reset.len(),
+        proposal.id,
+        repo,
+        number
+    );
+    for &(_, review) in &reset {
+        let mut review = review.clone();
+        review.reviewed = false;
+        review.recorded_by = None;
+        review.note = None;
+        diesel::update(fcp_review_request::table.find(review.id))
+            .set(&review)
+            .execute(conn)?;
+    }
+
+    // the reviews are reset already, so failing to say so shouldn't fail the webhook
+    ok_or!(refresh_status_comment(&issue, &proposal), why =>
+        warn!("Unable to update the status comment of proposal {}: {:?}", proposal.id, why));
+
+    let reviewers = reset
+        .iter()
+        .map(|&(member, _)| member.clone())
+        .collect::<Vec<_>>();
+    let comment = RfcBotComment::new(
+        &issue,
+        CommentType::ReviewsReset {
+            reviewers: &reviewers,
+        },
+    );
+    ok_or!(comment.post(None), why =>
+        warn!("Unable to announce the reset reviews of proposal {}: {:?}", proposal.id, why));
+
+    Ok(())
+}
+
+/// The completed reviews which a push invalidates. The proposer's own review stands, and so
+/// do waived ones.
+fn reviews_to_reset(
+    initiator_id: i32,
+    reviews: &[(GitHubUser, FcpReviewRequest)],
+) -> Vec<&(GitHubUser, FcpReviewRequest)> {
+    reviews
+        .iter()
+        .filter(|&&(ref member, ref review)| review.reviewed && member.id != initiator_id)
+        .collect()
+}
+
+/// The stored comment with the body GitHub currently has for it.
+fn reconciled_comment(
+    stored: IssueComment,
+    body: String,
+    updated_at: NaiveDateTime,
+) -> IssueComment {
+    IssueComment {
+        body: body.replace(0x00 as char, ""),
+        updated_at,
+        ..stored
+    }
+}
+
+/// Whether comments from `login` should be skipped entirely, e.g. because it's a bot. GitHub
+/// logins aren't case sensitive.
+fn is_ignored_author(login: &str, ignored: &BTreeSet<String>) -> bool {
+    ignored
+        .iter()
+        .any(|ignored| ignored.eq_ignore_ascii_case(login))
+}
+
+/// Whether `login` is the account rfcbot is running as.
+fn is_own_comment(login: &str, bot_login: &str) -> bool {
+    !bot_login.is_empty() && bot_login.eq_ignore_ascii_case(login)
+}
+
+/// The reaction on a status comment that counts as a review when `REVIEW_VIA_REACTION` is set.
+const REVIEW_REACTION: &str = "+1";
+
+/// Mark reviewed the requests of current team members who reacted with `REVIEW_REACTION` to the
+/// proposal's status comment.
+fn review_from_reactions(
+    issue: &Issue,
+    proposal: &FcpProposal,
+    members: &[GitHubUser],
+) -> DashResult<()> {
+    use crate::domain::schema::fcp_review_request::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    let reacted = github().comment_reaction_users(
+        &issue.repository,
+        proposal.fk_bot_tracking_comment,
+        REVIEW_REACTION,
+    )?;
+    let reviews = list_review_requests(proposal.id)?;
+    let newly_reviewed = reaction_reviews(&reviews, &reacted, members);
+    if !newly_reviewed.is_empty() {
+        diesel::update(fcp_review_request.filter(id.eq_any(newly_reviewed)))
+            .set(reviewed.eq(true))
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// The ids of the outstanding review requests whose reviewer reacted and is still on a team
+/// responsible for the issue.
+fn reaction_reviews(
+    reviews: &[(GitHubUser, FcpReviewRequest)],
+    reacted: &[String],
+    members: &[GitHubUser],
+) -> Vec<i32> {
+    reviews
+        .iter()
+        .filter(|(_, request)| !request.reviewed && !request.waived)
+        .filter(|(user, _)| members.iter().any(|m| m.id == user.id))
+        .filter(|(user, _)| reacted.iter().any(|r| r.eq_ignore_ascii_case(&user.login)))
+        .map(|(_, request)| request.id)
+        .collect()
+}
+
+/// Returns how many reviews were newly marked as done.
+fn update_proposal_review_status(proposal_id: i32) -> DashResult<usize> {
     let conn = &*DB_POOL.get()?;
     // this is an updated comment from the bot itself
 
@@ -114,10 +665,12 @@ fn update_proposal_review_status(proposal_id: i32) -> DashResult<()> {
     let proposal: FcpProposal = fcp_proposal::table.find(proposal_id).first(conn)?;
 
     // don't update any statuses if the fcp is running or closed
-    if proposal.fcp_start.is_some() || proposal.fcp_closed {
-        return Ok(());
+    if proposal.state()? != ProposalState::Pending {
+        return Ok(0);
     }
 
+    let mut marked = 0;
+
     let comment: IssueComment = issuecomment::table
         .find(proposal.fk_bot_tracking_comment)
         .first(conn)?;
@@ -134,15 +687,19 @@ fn update_proposal_review_status(proposal_id: i32) -> DashResult<()> {
                 .filter(fk_proposal.eq(proposal.id))
                 .filter(fk_reviewer.eq(user.id))
                 .first(conn)?;
+            if review_request.reviewed {
+                continue;
+            }
 
             review_request.reviewed = true;
             diesel::update(fcp_review_request.find(review_request.id))
                 .set(&review_request)
                 .execute(conn)?;
+            marked += 1;
         }
     }
 
-    Ok(())
+    Ok(marked)
 }
 
 /// Given a poll, parse out each "responded" status, in the poll's ticky boxes,
@@ -195,97 +752,453 @@ fn parse_ticky_boxes<'a>(
     id: i32,
     comment: &'a IssueComment,
 ) -> impl Iterator<Item = &'a str> {
-    comment.body.lines().filter_map(move |line| {
-        if line.starts_with("* [") {
-            let l = line.trim_start_matches("* [");
-            let reviewed = l.starts_with('x');
-            let remaining = l.trim_start_matches("x] @").trim_start_matches(" ] @");
-
-            if let Some(username) = remaining.split_whitespace().next() {
-                trace!(
-                    "reviewer parsed as reviewed? {} (line: \"{}\")",
-                    reviewed,
-                    l
-                );
+    managed_region(&comment.body)
+        .lines()
+        .filter_map(move |line| {
+            if line.starts_with("* [") {
+                let l = line.trim_start_matches("* [");
+                let reviewed = l.starts_with('x');
+                let remaining = l.trim_start_matches("x] ").trim_start_matches(" ] ");
+
+                if let Some(username) = checklist_login(remaining) {
+                    trace!(
+                        "reviewer parsed as reviewed? {} (line: \"{}\")",
+                        reviewed,
+                        l
+                    );
 
-                if reviewed {
-                    Some(username)
+                    if reviewed {
+                        Some(username)
+                    } else {
+                        None
+                    }
                 } else {
+                    warn!(
+                        "An empty usename showed up in comment {} for {} {}",
+                        comment.id, what, id
+                    );
                     None
                 }
             } else {
-                warn!(
-                    "An empty usename showed up in comment {} for {} {}",
-                    comment.id, what, id
-                );
                 None
             }
-        } else {
-            None
-        }
-    })
+        })
+}
+
+/// The login in a checklist entry, which is either a mention (`@login`) or, in status comments,
+/// a link to their profile (`[login](https://github.com/login)`) so that edits don't ping.
+fn checklist_login(entry: &str) -> Option<&str> {
+    if entry.starts_with('[') {
+        entry[1..]
+            .split(']')
+            .next()
+            .filter(|login| !login.is_empty())
+    } else {
+        entry.trim_start_matches('@').split_whitespace().next()
+    }
 }
 
 fn evaluate_nags() {
+    ok_or!(flush_coalesced_edits(), why =>
+        report_error("Unable to post held back status comment updates", &why));
+
+    ok_or!(retry_failed_posts(), why =>
+        report_error("Unable to retry failed status comment updates", &why));
+
     ok_or!(evaluate_pendings(), why =>
-        error!("Unable to evaluate outstanding proposals: {:?}", why));
+        report_error("Unable to evaluate outstanding proposals", &why));
+
+    ok_or!(evaluate_running(), why =>
+        report_error("Unable to evaluate running fcps", &why));
 
     ok_or!(evaluate_ffcps(), why =>
-        error!("Unable to evaluate outstanding ffcps: {:?}", why));
+        report_error("Unable to evaluate outstanding ffcps", &why));
+
+    ok_or!(update_mirrors(), why =>
+        report_error("Unable to update mirrored proposals", &why));
 
     ok_or!(evaluate_polls(), why =>
-        error!("Unable to evaluate outstanding polls: {:?}", why));
+        report_error("Unable to evaluate outstanding polls", &why));
+
+    ok_or!(update_fcp_digest(), why =>
+        report_error("Unable to update the FCP digest", &why));
 }
 
-fn evaluate_polls() -> DashResult<()> {
-    use crate::domain::schema::issuecomment::dsl::id as issuecomment_id;
-    use crate::domain::schema::issuecomment::dsl::*;
-    use crate::domain::schema::poll::dsl::*;
-    let conn = &*DB_POOL.get()?;
+/// What's sent to `ERROR_REPORT_URL` when something fails.
+#[derive(Debug, Serialize)]
+struct ErrorReport<'a> {
+    context: &'a str,
+    error: String,
+    version: &'a str,
+}
 
-    // first process all "pending" polls (unresponded)
-    let pending = poll.filter(poll_closed.eq(false)).load::<Poll>(conn);
-    let pending = ok_or!(pending, why => {
-        error!("Unable to retrieve list of pending polls: {:?}", why);
-        throw!(why)
+/// Log an error which we carry on past, and send it to the configured error tracker, if any.
+/// Reporting is best-effort and happens in the background, so a failure to report is only
+/// logged.
+fn report_error(context: &str, why: &DashError) {
+    error!("{}: {:?}", context, why);
+
+    let url = CONFIG.error_report_url.as_deref();
+    send_error_report(url, context, why, |url, report| {
+        post_in_background(url, report)
     });
+}
 
-    for mut survey in pending {
-        let initiator = githubuser::table
-            .find(survey.fk_initiator)
-            .first::<GitHubUser>(conn);
-        let initiator = ok_or_continue!(initiator, why =>
-            error!("Unable to retrieve poll initiator for poll id {}: {:?}",
-                    survey.id, why));
+fn send_error_report<F>(url: Option<&str>, context: &str, why: &DashError, send: F)
+where
+    F: FnOnce(&str, &ErrorReport<'_>) -> DashResult<()>,
+{
+    let url = match url {
+        Some(url) => url,
+        None => return,
+    };
 
-        let issue = issue::table.find(survey.fk_issue).first::<Issue>(conn);
-        let issue = ok_or_continue!(issue, why =>
-            error!("Unable to retrieve issue for poll {}: {:?}",
-                    survey.id, why));
+    let report = ErrorReport {
+        context,
+        error: format!("{:?}", why),
+        version: VERSION,
+    };
+    ok_or!(send(url, &report), why =>
+        warn!("Unable to report an error to {}: {:?}", url, why));
+}
 
-        // check to see if any checkboxes were modified before we end up replacing the comment
-        ok_or_continue!(update_poll_response_status(survey.id), why =>
-            error!("Unable to update response status for poll {}: {:?}",
-                    survey.id, why));
+/// How many times a failed status comment update is tried before it's left for a human to look at.
+const MAX_COMMENT_POST_ATTEMPTS: i32 = 8;
 
-        // get associated responses
-        let responses = ok_or_continue!(list_poll_response_requests(survey.id), why =>
-            error!("Unable to retrieve response requests for survey {}: {:?}",
-                    survey.id, why));
+/// Re-attempt the status comment updates which failed on earlier passes, once they're due.
+fn retry_failed_posts() -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
 
-        // If everyone has answered the poll, close it:
-        if responses.iter().all(|(_, response)| response.responded) {
-            survey.poll_closed = true;
-            let update = diesel::update(poll.find(survey.id))
-                .set(&survey)
-                .execute(conn);
-            ok_or_continue!(update, why =>
-                error!("Unable to close poll {}: {:?}", survey.id, why));
-        }
+    retry_due_posts(conn, Utc::now().naive_utc(), |pending| {
+        repost_comment(conn, pending.fk_target_comment, &pending.body)
+    })
+}
 
-        // update existing status comment with responses & concerns
-        let status_comment = RfcBotComment::new(
-            &issue,
+/// Replaces the body of one of our comments with one that was put aside earlier, adding the
+/// repository's footer. Like `RfcBotComment::post`, this fails if comment posts are disabled.
+fn repost_comment(conn: &PgConnection, comment_id: i32, body: &str) -> DashResult<()> {
+    if !posting_enabled() {
+        info!(
+            "Skipping update to comment {}, comment posts are disabled.",
+            comment_id
+        );
+        throw!(DashError::Misc(None));
+    }
+
+    let target = issuecomment::table
+        .find(comment_id)
+        .first::<IssueComment>(conn)?;
+    let issue = issue::table.find(target.fk_issue).first::<Issue>(conn)?;
+    if !may_comment_on(&issue) {
+        // fresh updates are posted if it's reopened or unmuted
+        return Ok(());
+    }
+
+    let body = with_footer(body, &CONFIG.repo_comment_footers, &issue.repository);
+    let existing = Some((target.id, target.body.as_str()));
+    let posted = github().post_comment(&issue.repository, issue.number, existing, &body)?;
+    if let Some(posted) = posted.comment() {
+        let posted = posted.with_repo(&issue.repository)?;
+        diesel::update(issuecomment::table.find(posted.id))
+            .set(&posted)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// The shortest time between two edits of the same status comment. A burst of webhooks
+/// evaluates the same proposals over and over, and editing a comment several times in a few
+/// seconds trips GitHub's secondary rate limits.
+const STATUS_EDIT_INTERVAL_SECS: i64 = 10;
+
+/// Tracks when each status comment was last edited, holding back the newest body for any edit
+/// which comes too soon after the previous one.
+struct EditCoalescer {
+    interval: Duration,
+    edits: BTreeMap<i32, (NaiveDateTime, Option<String>)>,
+}
+
+impl EditCoalescer {
+    fn new(interval: Duration) -> Self {
+        EditCoalescer {
+            interval,
+            edits: BTreeMap::new(),
+        }
+    }
+
+    /// Can comment `comment_id` be edited to `body` right now? If not, the body is kept until
+    /// `take_due` hands it back, replacing anything held back before it. The edit only counts
+    /// once it's been made, which the caller reports with `edited`.
+    fn offer(&mut self, comment_id: i32, body: &str, now: NaiveDateTime) -> bool {
+        let interval = self.interval;
+        match self.edits.get_mut(&comment_id) {
+            Some((last_edit, held)) if now - *last_edit < interval => {
+                *held = Some(body.to_string());
+                false
+            }
+            Some((_, held)) => {
+                // superseded by the body about to be posted
+                *held = None;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Comment `comment_id` was edited at `now`.
+    fn edited(&mut self, comment_id: i32, now: NaiveDateTime) {
+        self.edits.insert(comment_id, (now, None));
+    }
+
+    /// The held back bodies which can now be posted, which count as edits made at `now`.
+    fn take_due(&mut self, now: NaiveDateTime) -> Vec<(i32, String)> {
+        let interval = self.interval;
+        let mut due = vec![];
+        for (&comment_id, (last_edit, held)) in &mut self.edits {
+            if now - *last_edit >= interval {
+                if let Some(body) = held.take() {
+                    *last_edit = now;
+                    due.push((comment_id, body));
+                }
+            }
+        }
+        // nothing more to learn from edits which are long gone
+        self.edits
+            .retain(|_, (last_edit, held)| held.is_some() || now - *last_edit < interval);
+        due
+    }
+}
+
+/// Posts the status comment updates which were held back by `STATUS_EDITS`, once they're due.
+fn flush_coalesced_edits() -> DashResult<()> {
+    let due = STATUS_EDITS
+        .lock()
+        .unwrap()
+        .take_due(Utc::now().naive_utc());
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &*DB_POOL.get()?;
+    for (comment_id, body) in due {
+        ok_or!(repost_comment(conn, comment_id, &body), why =>
+            error!("Unable to post held back update to comment {}: {:?}", comment_id, why));
+    }
+    Ok(())
+}
+
+/// Posts held back status comment updates as they fall due, so that they go out even if no more
+/// webhooks arrive to trigger a nag pass.
+pub fn start_edit_flusher() {
+    let interval = std::time::Duration::from_secs(STATUS_EDIT_INTERVAL_SECS as u64);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        let _in_progress_marker = lock_nags();
+        ok_or!(flush_coalesced_edits(), why =>
+            report_error("Unable to post held back status comment updates", &why));
+    });
+}
+
+/// Edits a proposal's status comment, unless it was edited moments ago. Then the update is held
+/// back for `flush_coalesced_edits`, and reported as unchanged.
+fn post_status_comment(
+    status_comment: &RfcBotComment<'_>,
+    previous: &IssueComment,
+) -> DashResult<PostOutcome> {
+    let issue = status_comment.issue;
+    if posting_enabled() && may_comment_on(issue) {
+        let body = status_comment.templated(&CONFIG.comment_templates);
+        let changed =
+            with_footer(&body, &CONFIG.repo_comment_footers, &issue.repository) != previous.body;
+        let now = Utc::now().naive_utc();
+        if changed && !STATUS_EDITS.lock().unwrap().offer(previous.id, &body, now) {
+            debug!(
+                "Holding back an update to comment {}, it was edited moments ago",
+                previous.id
+            );
+            return Ok(PostOutcome::Unchanged);
+        }
+    }
+
+    let posted = status_comment.post(Some(previous))?;
+    if let PostOutcome::Created(comment) | PostOutcome::Edited(comment) = &posted {
+        STATUS_EDITS
+            .lock()
+            .unwrap()
+            .edited(comment.id, Utc::now().naive_utc());
+    }
+    Ok(posted)
+}
+
+fn retry_due_posts<F>(conn: &PgConnection, now: NaiveDateTime, mut post: F) -> DashResult<()>
+where
+    F: FnMut(&PendingCommentPost) -> DashResult<()>,
+{
+    let due = pending_comment_posts::table
+        .filter(pending_comment_posts::retry_after.le(now))
+        .filter(pending_comment_posts::attempts.lt(MAX_COMMENT_POST_ATTEMPTS))
+        .load::<PendingCommentPost>(conn)?;
+
+    for mut pending in due {
+        match post(&pending) {
+            Ok(()) => {
+                diesel::delete(pending_comment_posts::table.find(pending.id)).execute(conn)?;
+            }
+            Err(why) => {
+                pending.attempts += 1;
+                pending.retry_after = now + comment_post_backoff(pending.attempts);
+                if pending.attempts >= MAX_COMMENT_POST_ATTEMPTS {
+                    error!(
+                        "Giving up on updating comment {} for proposal {}: {:?}",
+                        pending.fk_target_comment, pending.fk_proposal, why
+                    );
+                } else {
+                    warn!(
+                        "Retry {} of comment {} for proposal {} failed: {:?}",
+                        pending.attempts, pending.fk_target_comment, pending.fk_proposal, why
+                    );
+                }
+                diesel::update(pending_comment_posts::table.find(pending.id))
+                    .set(&pending)
+                    .execute(conn)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Remembers a status comment update which couldn't be posted, so it's retried on a later pass.
+/// Only the latest body for a comment is kept.
+fn record_failed_post(proposal_id: i32, target: &IssueComment, body: &str) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+    let attempts = 1;
+    let pending = NewPendingCommentPost {
+        fk_proposal: proposal_id,
+        fk_target_comment: target.id,
+        body,
+        attempts,
+        retry_after: Utc::now().naive_utc() + comment_post_backoff(attempts),
+    };
+
+    diesel::insert_into(pending_comment_posts::table)
+        .values(&pending)
+        .on_conflict(pending_comment_posts::fk_target_comment)
+        .do_update()
+        .set(pending_comment_posts::body.eq(body))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Doubles the wait after each failed attempt, starting from two minutes.
+fn comment_post_backoff(attempts: i32) -> Duration {
+    Duration::minutes(1 << attempts.max(0).min(MAX_COMMENT_POST_ATTEMPTS))
+}
+
+/// Keep the configured digest issue's comment listing all running FCPs up to date.
+fn update_fcp_digest() -> DashResult<()> {
+    let (digest_repo, digest_number) = match CONFIG.fcp_digest_issue {
+        Some((ref repo, number)) => (repo.as_str(), number),
+        None => return Ok(()),
+    };
+
+    let conn = &*DB_POOL.get()?;
+
+    let digest_issue = issue::table
+        .filter(issue::repository.eq(digest_repo))
+        .filter(issue::number.eq(digest_number))
+        .first::<Issue>(conn)
+        .optional()?;
+    let digest_issue = match digest_issue {
+        Some(i) => i,
+        None => {
+            warn!(
+                "FCP digest issue {}#{} hasn't been scraped yet",
+                digest_repo, digest_number
+            );
+            return Ok(());
+        }
+    };
+
+    let running = fcp_proposal::table
+        .inner_join(issue::table)
+        .filter(fcp_proposal::state.eq(ProposalState::InFcp.repr()))
+        .order((issue::repository, issue::number))
+        .load::<(FcpProposal, Issue)>(conn)?
+        .into_iter()
+        .map(|(proposal, i)| (i, proposal))
+        .collect::<Vec<_>>();
+
+    let digest = RfcBotComment::new(&digest_issue, CommentType::FcpDigest(&running));
+
+    let existing = issuecomment::table
+        .filter(issuecomment::fk_issue.eq(digest_issue.id))
+        .filter(issuecomment::body.like(format!("{}%", FCP_DIGEST_MARKER)))
+        .order(issuecomment::created_at.desc())
+        .first::<IssueComment>(conn)
+        .optional()?;
+
+    match existing {
+        Some(previous) => {
+            digest.post(Some(&previous))?;
+        }
+        None => {
+            post_insert_comment(&digest_issue, CommentType::FcpDigest(&running))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn evaluate_polls() -> DashResult<()> {
+    use crate::domain::schema::issuecomment::dsl::id as issuecomment_id;
+    use crate::domain::schema::issuecomment::dsl::*;
+    use crate::domain::schema::poll::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    // first process all "pending" polls (unresponded)
+    let pending = poll.filter(poll_closed.eq(false)).load::<Poll>(conn);
+    let pending = ok_or!(pending, why => {
+        error!("Unable to retrieve list of pending polls: {:?}", why);
+        throw!(why)
+    });
+
+    for mut survey in pending {
+        let initiator = githubuser::table
+            .find(survey.fk_initiator)
+            .first::<GitHubUser>(conn);
+        let initiator = ok_or_continue!(initiator, why =>
+            error!("Unable to retrieve poll initiator for poll id {}: {:?}",
+                    survey.id, why));
+
+        let issue = issue::table.find(survey.fk_issue).first::<Issue>(conn);
+        let issue = ok_or_continue!(issue, why =>
+            error!("Unable to retrieve issue for poll {}: {:?}",
+                    survey.id, why));
+
+        // check to see if any checkboxes were modified before we end up replacing the comment
+        ok_or_continue!(update_poll_response_status(survey.id), why =>
+            error!("Unable to update response status for poll {}: {:?}",
+                    survey.id, why));
+
+        // get associated responses
+        let responses = ok_or_continue!(list_poll_response_requests(survey.id), why =>
+            error!("Unable to retrieve response requests for survey {}: {:?}",
+                    survey.id, why));
+
+        // If everyone has answered the poll, close it:
+        if responses.iter().all(|(_, response)| response.responded) {
+            survey.poll_closed = true;
+            let update = diesel::update(poll.find(survey.id))
+                .set(&survey)
+                .execute(conn);
+            ok_or_continue!(update, why =>
+                error!("Unable to close poll {}: {:?}", survey.id, why));
+        }
+
+        // update existing status comment with responses & concerns
+        let status_comment = RfcBotComment::new(
+            &issue,
             CommentType::QuestionAsked {
                 initiator: &initiator,
                 respondents: &responses,
@@ -297,16 +1210,15 @@ fn evaluate_polls() -> DashResult<()> {
         let previous_comment: IssueComment = issuecomment
             .filter(issuecomment_id.eq(survey.fk_bot_tracking_comment))
             .first(conn)?;
-
-        if previous_comment.body != status_comment.body {
-            // if the comment body in the database equals the new one we generated, then no change
-            // is needed from github (this assumes our DB accurately reflects GH's, which should
-            // be true in most cases by the time this is called)
-            let post = status_comment.post(Some(survey.fk_bot_tracking_comment));
-            ok_or_continue!(post, why =>
-                error!("Unable to update status comment for poll {}: {:?}",
-                        survey.id, why));
-        }
+        let status_comment = status_comment.preserving_edits_to(&previous_comment.body);
+
+        // if the comment body in the database equals the new one we generated, then no change
+        // is needed from github (this assumes our DB accurately reflects GH's, which should
+        // be true in most cases by the time this is called)
+        let post = status_comment.post(Some(&previous_comment));
+        ok_or_continue!(post, why =>
+            error!("Unable to update status comment for poll {}: {:?}",
+                    survey.id, why));
     }
 
     Ok(())
@@ -321,14 +1233,21 @@ fn evaluate_pendings() -> DashResult<()> {
 
     // first process all "pending" proposals (unreviewed or remaining concerns)
     let pending = fcp_proposal
-        .filter(fcp_start.is_null())
-        .load::<FcpProposal>(conn);
-    let pending_proposals = ok_or!(pending, why => {
+        .inner_join(issue::table)
+        .filter(state.eq(ProposalState::Pending.repr()))
+        .load::<(FcpProposal, Issue)>(conn);
+    let pending = ok_or!(pending, why => {
         error!("Unable to retrieve list of pending proposals: {:?}", why);
         throw!(why)
     });
 
-    for mut proposal in pending_proposals {
+    // if the issue has been closed before an FCP starts,
+    // then we just need to cancel the FCP entirely
+    let (pending_proposals, closed) = split_closed(pending);
+    ok_or!(cancel_closed(&closed), why =>
+        error!("Unable to cancel FCPs of closed issues: {:?}", why));
+
+    for (mut proposal, issue) in pending_proposals {
         let initiator = githubuser::table
             .find(proposal.fk_initiator)
             .first::<GitHubUser>(conn);
@@ -336,22 +1255,22 @@ fn evaluate_pendings() -> DashResult<()> {
             error!("Unable to retrieve proposal initiator for proposal id {}: {:?}",
                     proposal.id, why));
 
-        let issue = issue::table.find(proposal.fk_issue).first::<Issue>(conn);
-        let issue = ok_or_continue!(issue, why =>
-            error!("Unable to retrieve issue for proposal {}: {:?}",
+        // check to see if any checkboxes were modified before we end up replacing the comment
+        ok_or_continue!(update_proposal_review_status(proposal.id), why =>
+            error!("Unable to update review status for proposal {}: {:?}",
                     proposal.id, why));
 
-        // if the issue has been closed before an FCP starts,
-        // then we just need to cancel the FCP entirely
-        if !issue.open {
-            ok_or_continue!(cancel_fcp(&initiator, &issue, &proposal), why =>
-                error!("Unable to cancel FCP for proposal {}: {:?}",
+        if CONFIG.review_via_reaction {
+            let reviewed = subteam_members(&issue)
+                .and_then(|members| review_from_reactions(&issue, &proposal, &members));
+            ok_or!(reviewed, why =>
+                warn!("Unable to count reactions as reviews for proposal {}: {:?}",
                         proposal.id, why));
         }
 
-        // check to see if any checkboxes were modified before we end up replacing the comment
-        ok_or_continue!(update_proposal_review_status(proposal.id), why =>
-            error!("Unable to update review status for proposal {}: {:?}",
+        // teams tagged after the proposal was made need to review it too
+        ok_or_continue!(reconcile_review_requests(&issue, &proposal), why =>
+            error!("Unable to add reviewers from new teams to proposal {}: {:?}",
                     proposal.id, why));
 
         // get associated concerns and reviews
@@ -363,200 +1282,817 @@ fn evaluate_pendings() -> DashResult<()> {
             why => error!("Unable to retrieve concerns for proposal {}: {:?}",
                     proposal.id, why));
 
-        let num_outstanding_reviews = reviews.iter().filter(|&&(_, ref r)| !r.reviewed).count();
-        let num_complete_reviews = reviews.len() - num_outstanding_reviews;
-        let num_active_concerns = concerns
-            .iter()
-            .filter(|&&(_, ref c)| c.fk_resolved_comment.is_none())
-            .count();
-
         // update existing status comment with reviews & concerns
+        let teams = team_pings(&SETUP.read().unwrap(), &issue);
         let status_comment = RfcBotComment::new(
             &issue,
-            CommentType::FcpProposed(
-                &initiator,
-                FcpDisposition::from_str(&proposal.disposition)?,
-                &reviews,
-                &concerns,
-            ),
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::from_str(&proposal.disposition)?,
+                reviewers: &reviews,
+                concerns: &concerns,
+                teams: &teams,
+                min_distinct_reviewers: CONFIG.min_distinct_reviewers,
+                fcp_end: None,
+                state: proposal.state().ok().and_then(shown_state),
+            },
         );
 
         let previous_comment: IssueComment = issuecomment
             .filter(issuecomment_id.eq(proposal.fk_bot_tracking_comment))
             .first(conn)?;
+        let status_comment = status_comment.preserving_edits_to(&previous_comment.body);
+
+        // if the comment body in the database equals the new one we generated, then no change
+        // is needed from github (this assumes our DB accurately reflects GH's, which should
+        // be true in most cases by the time this is called)
+        let post = post_status_comment(&status_comment, &previous_comment);
+        ok_or_continue!(post, why => {
+            error!("Unable to update status comment for proposal {}: {:?}",
+                    proposal.id, why);
+            if posting_enabled() {
+                let body = status_comment.templated(&CONFIG.comment_templates);
+                ok_or!(record_failed_post(proposal.id, &previous_comment, &body),
+                    why => error!("Unable to record failed update for proposal {}: {:?}",
+                                  proposal.id, why));
+            }
+        });
 
-        if previous_comment.body != status_comment.body {
-            // if the comment body in the database equals the new one we generated, then no change
-            // is needed from github (this assumes our DB accurately reflects GH's, which should
-            // be true in most cases by the time this is called)
-            let post = status_comment.post(Some(proposal.fk_bot_tracking_comment));
-            ok_or_continue!(post, why =>
-                error!("Unable to update status comment for proposal {}: {:?}",
-                        proposal.id, why));
-        }
-
-        let majority_complete = num_outstanding_reviews < num_complete_reviews;
-
-        if num_active_concerns == 0 && majority_complete && num_outstanding_reviews < 3 {
-            // TODO only record the fcp as started if we know that we successfully commented
-            // i.e. either the comment claims to have posted, or we get a comment back to reconcile
+        // a fresh update supersedes anything still waiting to be retried
+        let superseded = pending_comment_posts::table
+            .filter(pending_comment_posts::fk_target_comment.eq(previous_comment.id));
+        ok_or!(diesel::delete(superseded).execute(conn), why =>
+            error!("Unable to clear retries for proposal {}: {:?}", proposal.id, why));
+
+        if fcp_can_start(
+            &initiator,
+            &reviews,
+            &concerns,
+            CONFIG.min_distinct_reviewers,
+            CONFIG.fcp_requires_all_reviews,
+        ) {
+            start_fcp(conn, &mut proposal, &issue, &initiator);
+        } else if let Some(blocker) = last_blocker(
+            &proposal,
+            &initiator,
+            &reviews,
+            &concerns,
+            CONFIG.min_distinct_reviewers,
+            CONFIG.fcp_requires_all_reviews,
+        ) {
+            let heads_up = RfcBotComment::new(
+                &issue,
+                CommentType::FcpAlmostReady {
+                    blocker,
+                    status_comment_id: proposal.fk_bot_tracking_comment,
+                },
+            );
+            ok_or_continue!(heads_up.post(None), why =>
+                error!("Unable to post heads-up for proposal {}: {:?}", proposal.id, why));
 
-            // FCP can start now -- update the database
-            proposal.fcp_start = Some(Utc::now().naive_utc());
+            proposal.almost_ready_notified = true;
             let update = diesel::update(fcp_proposal.find(proposal.id))
                 .set(&proposal)
                 .execute(conn);
             ok_or_continue!(update, why =>
-                error!("Unable to mark FCP {} as started: {:?}",
-                       proposal.id, why));
-
-            // attempt to add the final-comment-period label
-            // TODO only add label if FCP > 1 day
-            use crate::config::CONFIG;
-            if CONFIG.post_comments {
-                let label_res = issue.add_label(Label::FCP);
-                issue.remove_label(Label::PFCP);
-                let added_label = match label_res {
-                    Ok(()) => true,
-                    Err(why) => {
-                        warn!(
-                            "Unable to add FCP label to {}#{}: {:?}",
-                            &issue.repository, issue.number, why
-                        );
-                        false
-                    }
-                };
-
-                let comment_type = CommentType::FcpAllReviewedNoConcerns {
-                    added_label,
-                    author: &initiator,
-                    status_comment_id: proposal.fk_bot_tracking_comment,
-                };
-
-                // leave a comment for FCP start
-                let fcp_start_comment = RfcBotComment::new(&issue, comment_type);
-                ok_or_continue!(fcp_start_comment.post(None), why =>
-                    error!("Unable to post comment for FCP {}'s start: {:?}",
-                            proposal.id, why));
-            }
+                error!("Unable to mark proposal {} as notified: {:?}", proposal.id, why));
         }
     }
 
     Ok(())
 }
 
-fn evaluate_ffcps() -> DashResult<()> {
-    use crate::domain::schema::fcp_proposal::dsl::*;
-    use diesel::prelude::*;
-    let conn = &*DB_POOL.get()?;
-
-    // look for any FCP proposals that entered FCP a week or more ago but aren't marked as closed
-    let one_business_week_ago = Utc::now().naive_utc() - Duration::days(10);
-    let ffcps = fcp_proposal
-        .filter(fcp_start.le(one_business_week_ago))
-        .filter(fcp_closed.eq(false))
-        .load::<FcpProposal>(conn);
-    let finished_fcps = ok_or!(ffcps, why => {
-        error!("Unable to retrieve FCPs that need to be marked as finished: {:?}",
-               why);
-        throw!(why);
-    });
-
-    for mut proposal in finished_fcps {
-        let initiator = githubuser::table
-            .find(proposal.fk_initiator)
-            .first::<GitHubUser>(conn);
-        let initiator = ok_or_continue!(initiator, why =>
-            error!("Unable to retrieve proposal initiator for proposal id {}: {:?}",
-                    proposal.id,
-                    why));
-
-        let issue = issue::table.find(proposal.fk_issue).first::<Issue>(conn);
-        let issue = ok_or_continue!(issue, why =>
-            error!("Unable to find issue to match proposal {}: {:?}",
-                   proposal.id, why));
-
-        // TODO only update the db if the comment posts, but reconcile if we find out it worked
-
-        // update the fcp
-        proposal.fcp_closed = true;
-        let update_fcp = diesel::update(fcp_proposal.find(proposal.id))
-            .set(&proposal)
-            .execute(conn);
-        ok_or_continue!(update_fcp, why =>
-            error!("Unable to update FCP {}: {:?}", proposal.id, why));
-
-        // parse the disposition:
-        let disp = FcpDisposition::from_str(&proposal.disposition)?;
-
-        // Add FFCP label and remove FCP label.
-        let label_res = issue.add_label(Label::FFCP);
-        issue.remove_label(Label::FCP);
+/// Announces that the proposal's final comment period has started and records the start, unless
+/// the announcement couldn't be posted.
+fn start_fcp(
+    conn: &PgConnection,
+    proposal: &mut FcpProposal,
+    issue: &Issue,
+    initiator: &GitHubUser,
+) {
+    // attempt to add the final-comment-period label
+    // TODO only add label if FCP > 1 day
+    if posting_enabled() && may_comment_on(issue) {
+        let label_res = issue.add_label(Label::FCP);
+        issue.remove_label(Label::PFCP);
         let added_label = match label_res {
-            Ok(_) => {
-                if let Err(why) = issue.add_label(Label::ToAnnounce) {
-                    warn!(
-                        "Unable to add to-announce label to {}#{}: {:?}",
-                        &issue.repository, issue.number, why
-                    );
-                }
-                true
-            }
+            Ok(()) => true,
             Err(why) => {
                 warn!(
-                    "Unable to add Finished-FCP label to {}#{}: {:?}",
-                    &issue.repository, issue.number, why
+                    "Unable to add FCP label to {}#{}: {:?}",
+                    issue.repository, issue.number, why
                 );
                 false
             }
         };
 
-        // Build the comment:
-        let comment_type = CommentType::FcpWeekPassed {
+        let length_days = FcpDisposition::from_str(&proposal.disposition)
+            .map(|disp| fcp_length_days(CONFIG.fcp_durations_for(&issue.repository), disp));
+        let length_days = ok_or!(length_days, why => {
+            error!("Unable to parse disposition of FCP {}: {:?}", proposal.id, why);
+            return;
+        });
+
+        let comment_type = CommentType::FcpAllReviewedNoConcerns {
             added_label,
-            author: &initiator,
+            author: initiator,
             status_comment_id: proposal.fk_bot_tracking_comment,
-            disposition: disp,
+            length_days,
         };
-        let fcp_close_comment = RfcBotComment::new(&issue, comment_type);
 
-        // Post it!
-        ok_or_continue!(fcp_close_comment.post(None), why =>
-            error!("Unable to post FCP-ending comment for proposal {}: {:?}",
-                    proposal.id, why));
+        // if recording the start failed last time, the comment is already up
+        let comments = github().comments_of_issue(&issue.repository, issue.number);
+        let comments = ok_or!(comments, why => {
+            error!("Unable to check for an existing start comment for FCP {}: {:?}",
+                    proposal.id, why);
+            return;
+        });
+        let announced = comments
+            .iter()
+            .any(|c| announces_fcp_start(issue, proposal.fk_bot_tracking_comment, &c.body));
 
-        execute_ffcp_actions(&issue, disp);
+        // leave a comment for FCP start, the fcp is only recorded as started once it's
+        // up so that a failed post is retried on the next pass
+        if announced {
+            info!("FCP {}'s start was already announced", proposal.id);
+        } else {
+            let fcp_start_comment = RfcBotComment::new(issue, comment_type);
+            let posted = ok_or!(fcp_start_comment.post(None), why => {
+                error!("Unable to post comment for FCP {}'s start: {:?}",
+                        proposal.id, why);
+                return;
+            });
+            if posted.comment().is_none() {
+                return;
+            }
+        }
     }
 
-    Ok(())
+    // FCP can start now -- update the database
+    ok_or!(proposal.transition(ProposalState::InFcp), why => {
+        error!("Unable to start FCP {}: {:?}", proposal.id, why);
+        return;
+    });
+    proposal.fcp_start = Some(Utc::now().naive_utc());
+    let update = diesel::update(fcp_proposal::table.find(proposal.id))
+        .set(&*proposal)
+        .execute(conn);
+    ok_or!(update, why => {
+        error!("Unable to mark FCP {} as started: {:?}", proposal.id, why);
+        return;
+    });
+
+    if CONFIG.status_emoji {
+        ok_or!(refresh_status_comment(issue, proposal), why =>
+            warn!("Unable to show FCP {} has started: {:?}", proposal.id, why));
+    }
+    notify_transition(issue, proposal);
 }
 
-fn can_ffcp_close(issue: &Issue) -> bool {
-    SETUP
-        .read()
-        .unwrap()
-        .should_ffcp_auto_close(&issue.repository)
+/// Is `body` the comment announcing the start of the FCP tracked by `status_comment_id`?
+fn announces_fcp_start(issue: &Issue, status_comment_id: i32, body: &str) -> bool {
+    let mut announcement = String::from(FCP_START_ANNOUNCEMENT);
+    announcement.push_str("as per the [review above](");
+    RfcBotComment::add_comment_url(issue, &mut announcement, status_comment_id);
+    body.contains(&announcement)
 }
 
-fn can_ffcp_postpone(issue: &Issue) -> bool {
-    SETUP
-        .read()
-        .unwrap()
-        .should_ffcp_auto_postpone(&issue.repository)
+/// Separates pending proposals on open issues from those whose issue has been closed.
+fn split_closed(
+    pending: Vec<(FcpProposal, Issue)>,
+) -> (Vec<(FcpProposal, Issue)>, Vec<(FcpProposal, Issue)>) {
+    pending.into_iter().partition(|(_, issue)| issue.open)
 }
 
-fn execute_ffcp_actions(issue: &Issue, disposition: FcpDisposition) {
-    match disposition {
-        FcpDisposition::Merge => {
-            // TODO: This one will require a lot of work to
-            // auto-merge RFCs and create the tracking issue.
-        }
-        FcpDisposition::Close if can_ffcp_close(issue) => {
-            let _ = issue.add_label(Label::Closed);
-            issue.remove_label(Label::DispositionClose);
-            issue.close();
-        }
-        FcpDisposition::Postpone if can_ffcp_postpone(issue) => {
+/// Cancels the proposals of issues closed before their FCP started, in a single query.
+fn cancel_closed(closed: &[(FcpProposal, Issue)]) -> DashResult<()> {
+    use crate::domain::schema::fcp_proposal::dsl::*;
+
+    if closed.is_empty() {
+        return Ok(());
+    }
+    let conn = &*DB_POOL.get()?;
+
+    let mut cancelled = Vec::new();
+    for (proposal, issue) in closed {
+        let mut proposal = proposal.clone();
+        ok_or_continue!(proposal.transition(ProposalState::Cancelled), why =>
+            error!("Unable to cancel FCP for proposal {}: {:?}", proposal.id, why));
+        cancelled.push((proposal, issue));
+    }
+
+    info!("cancelling {} proposals of closed issues", cancelled.len());
+    for (proposal, _) in &cancelled {
+        ok_or!(archive_proposal(conn, proposal), why =>
+            error!("Unable to archive proposal {}: {:?}", proposal.id, why));
+    }
+    let ids = cancelled.iter().map(|(p, _)| p.id).collect::<Vec<_>>();
+    diesel::delete(fcp_proposal.filter(id.eq_any(ids))).execute(conn)?;
+
+    for (proposal, issue) in cancelled {
+        let initiator = githubuser::table
+            .find(proposal.fk_initiator)
+            .first::<GitHubUser>(conn);
+        let initiator = ok_or_continue!(initiator, why =>
+            error!("Unable to retrieve proposal initiator for proposal id {}: {:?}",
+                    proposal.id, why));
+        announce_cancellation(&initiator, issue, &proposal);
+    }
+
+    Ok(())
+}
+
+/// Keeps enough of a proposal to restore it if its issue is reopened. Only the issue's most
+/// recently cancelled proposal is kept.
+fn archive_proposal(conn: &PgConnection, proposal: &FcpProposal) -> DashResult<()> {
+    let archived = NewArchivedProposal {
+        fk_issue: proposal.fk_issue,
+        fk_initiator: proposal.fk_initiator,
+        fk_initiating_comment: proposal.fk_initiating_comment,
+        disposition: &proposal.disposition,
+        fk_bot_tracking_comment: proposal.fk_bot_tracking_comment,
+        archived_at: Utc::now().naive_utc(),
+        tracking_label: proposal.tracking_label.as_deref(),
+    };
+
+    diesel::insert_into(archived_proposals::table)
+        .values(&archived)
+        .on_conflict(archived_proposals::fk_issue)
+        .do_update()
+        .set((
+            archived_proposals::fk_initiator.eq(archived.fk_initiator),
+            archived_proposals::fk_initiating_comment.eq(archived.fk_initiating_comment),
+            archived_proposals::disposition.eq(archived.disposition),
+            archived_proposals::fk_bot_tracking_comment.eq(archived.fk_bot_tracking_comment),
+            archived_proposals::archived_at.eq(archived.archived_at),
+            archived_proposals::tracking_label.eq(archived.tracking_label),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Requests reviews from any members of the issue's teams who haven't been asked yet, which
+/// happens when a team's label is added after the proposal was made.
+fn reconcile_review_requests(issue: &Issue, proposal: &FcpProposal) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+
+    let disposition = FcpDisposition::from_str(&proposal.disposition)?;
+    let members = disposition_reviewers(
+        &SETUP.read().unwrap(),
+        disposition,
+        &subteam_members(issue)?,
+    );
+    let reviews = list_review_requests(proposal.id)?;
+    let missing = missing_reviewers(&members, &reviews);
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    // record who tagged the new teams, it's not otherwise obvious where the reviewers came from
+    let proposed_at = issuecomment::table
+        .find(proposal.fk_initiating_comment)
+        .first::<IssueComment>(conn)?
+        .created_at;
+    match github().issue_events(&issue.repository, issue.number) {
+        Ok(events) => {
+            for (label, actor) in labels_added_since(&events, DateTime::from_utc(proposed_at, Utc))
+            {
+                info!(
+                    "{}#{} was labelled {} by {} after proposal {} was made",
+                    issue.repository,
+                    issue.number,
+                    label,
+                    actor.unwrap_or("unknown"),
+                    proposal.id
+                );
+            }
+        }
+        Err(why) => warn!(
+            "Unable to fetch label events for {}#{}: {:?}",
+            issue.repository, issue.number, why
+        ),
+    }
+
+    for &member in &missing {
+        info!(
+            "requesting review of proposal {} from {}",
+            proposal.id, member.login
+        );
+        diesel::insert_into(fcp_review_request::table)
+            .values(&NewFcpReviewRequest {
+                fk_proposal: proposal.id,
+                fk_reviewer: member.id,
+                reviewed: false,
+                waived: false,
+            })
+            .execute(conn)?;
+    }
+
+    let missing = missing.into_iter().cloned().collect::<Vec<_>>();
+    request_reviews(issue, proposal, &missing)
+}
+
+/// Ping reviewers in a comment of their own, the status comment only links to them.
+fn request_reviews(
+    issue: &Issue,
+    proposal: &FcpProposal,
+    reviewers: &[GitHubUser],
+) -> DashResult<()> {
+    if reviewers.is_empty() {
+        return Ok(());
+    }
+
+    let request = CommentType::ReviewRequested {
+        status_comment_id: proposal.fk_bot_tracking_comment,
+        disposition: FcpDisposition::from_str(&proposal.disposition)?,
+        reviewers,
+    };
+    RfcBotComment::new(issue, request).post(None)?;
+    Ok(())
+}
+
+/// The labels added after `since` which are still on the issue, and who added them, given
+/// its label events in chronological order.
+fn labels_added_since(events: &[LabelEvent], since: DateTime<Utc>) -> BTreeMap<&str, Option<&str>> {
+    let mut added = BTreeMap::new();
+    for event in events {
+        if !event.added {
+            added.remove(&*event.label);
+        } else if event.created_at > since {
+            added.insert(&*event.label, event.actor.as_ref().map(String::as_str));
+        }
+    }
+    added
+}
+
+/// The members who haven't been asked to review a proposal yet.
+fn missing_reviewers<'m>(
+    members: &'m [GitHubUser],
+    reviews: &[(GitHubUser, FcpReviewRequest)],
+) -> Vec<&'m GitHubUser> {
+    members
+        .iter()
+        .filter(|&m| reviews.iter().all(|&(ref r, _)| r.id != m.id))
+        .collect()
+}
+
+/// Commands which only admins may run, whether or not they're in `PRIVILEGED_COMMANDS`.
+const ALWAYS_PRIVILEGED_COMMANDS: &[&str] = &["fcp-end-now", "reviewed-for"];
+
+/// Whether `login` may run the named command, given which commands are privileged and who
+/// the admins are.
+fn command_allowed(
+    command: &str,
+    login: &str,
+    privileged_commands: &BTreeSet<String>,
+    admins: &BTreeSet<String>,
+) -> bool {
+    let privileged =
+        privileged_commands.contains(command) || ALWAYS_PRIVILEGED_COMMANDS.contains(&command);
+    !privileged || admins.contains(login)
+}
+
+/// Has a proposal been reviewed enough, without outstanding concerns, to enter FCP? Unless
+/// `requires_reviews`, only its concerns and the minimum of distinct reviewers hold it back.
+fn fcp_can_start(
+    initiator: &GitHubUser,
+    reviews: &[(GitHubUser, FcpReviewRequest)],
+    concerns: &[(GitHubUser, FcpConcern)],
+    min_distinct_reviewers: usize,
+    requires_reviews: bool,
+) -> bool {
+    // waived reviews count as complete
+    let num_outstanding_reviews = reviews
+        .iter()
+        .filter(|&&(_, ref r)| !r.reviewed && !r.waived)
+        .count();
+    let num_complete_reviews = reviews.len() - num_outstanding_reviews;
+    let num_active_concerns = concerns
+        .iter()
+        .filter(|&&(_, ref c)| c.fk_resolved_comment.is_none())
+        .count();
+
+    let majority_complete = num_outstanding_reviews < num_complete_reviews;
+    let reviews_complete = majority_complete && num_outstanding_reviews < 3;
+
+    // without anyone to review it, the proposal would start unreviewed
+    !reviews.is_empty()
+        && num_active_concerns == 0
+        && (reviews_complete || !requires_reviews)
+        && missing_distinct_reviews(initiator, reviews, min_distinct_reviewers) == 0
+}
+
+/// The one outstanding review or concern holding a proposal back from its final comment period.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LastBlocker<'a> {
+    Review(&'a GitHubUser),
+    Concern {
+        author: &'a GitHubUser,
+        name: &'a str,
+    },
+}
+
+/// Who to give a heads-up to, if the proposal hasn't had one and would start its final comment
+/// period once a single review is done or a single concern is resolved.
+fn last_blocker<'a>(
+    proposal: &FcpProposal,
+    initiator: &GitHubUser,
+    reviews: &'a [(GitHubUser, FcpReviewRequest)],
+    concerns: &'a [(GitHubUser, FcpConcern)],
+    min_distinct_reviewers: usize,
+    requires_reviews: bool,
+) -> Option<LastBlocker<'a>> {
+    if proposal.almost_ready_notified
+        || fcp_can_start(
+            initiator,
+            reviews,
+            concerns,
+            min_distinct_reviewers,
+            requires_reviews,
+        )
+    {
+        return None;
+    }
+
+    let outstanding_reviews = reviews
+        .iter()
+        .filter(|&&(_, ref r)| !r.reviewed && !r.waived)
+        .collect::<Vec<_>>();
+    let active_concerns = concerns
+        .iter()
+        .filter(|&&(_, ref c)| c.fk_resolved_comment.is_none())
+        .collect::<Vec<_>>();
+
+    match (outstanding_reviews.len(), active_concerns.len()) {
+        // outstanding reviews don't matter when only concerns block the proposal
+        (outstanding, 1)
+            if (outstanding == 0 || !requires_reviews)
+                && fcp_can_start(
+                    initiator,
+                    reviews,
+                    &[],
+                    min_distinct_reviewers,
+                    requires_reviews,
+                ) =>
+        {
+            let (author, concern) = active_concerns[0];
+            Some(LastBlocker::Concern {
+                author,
+                name: &concern.name,
+            })
+        }
+        (1, 0) => {
+            let (reviewer, _) = outstanding_reviews[0];
+            let reviewed = reviews
+                .iter()
+                .cloned()
+                .map(|(member, mut review)| {
+                    review.reviewed |= member.id == reviewer.id;
+                    (member, review)
+                })
+                .collect::<Vec<_>>();
+            if fcp_can_start(
+                initiator,
+                &reviewed,
+                &[],
+                min_distinct_reviewers,
+                requires_reviews,
+            ) {
+                Some(LastBlocker::Review(reviewer))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// How many more reviews are needed from people other than the proposer, whose own review
+/// is assumed when the proposal is made.
+fn missing_distinct_reviews(
+    initiator: &GitHubUser,
+    reviews: &[(GitHubUser, FcpReviewRequest)],
+    min_distinct_reviewers: usize,
+) -> usize {
+    let distinct_reviews = reviews
+        .iter()
+        .filter(|&&(ref m, ref r)| (r.reviewed || r.waived) && m.id != initiator.id)
+        .count();
+    min_distinct_reviewers.saturating_sub(distinct_reviews)
+}
+
+/// How long a final comment period with this disposition lasts once it has started.
+fn fcp_length_days(durations: &FcpDurations, disposition: FcpDisposition) -> i64 {
+    match disposition {
+        FcpDisposition::Merge => durations.merge,
+        FcpDisposition::Close => durations.close,
+        FcpDisposition::Postpone => durations.postpone,
+    }
+}
+
+/// When a proposal's final comment period ends: at its override if one was set, and otherwise
+/// after the usual length for its disposition. Proposals which haven't entered FCP have no end.
+pub fn fcp_end(proposal: &FcpProposal, durations: &FcpDurations) -> Option<NaiveDateTime> {
+    let length_days = FcpDisposition::from_str(&proposal.disposition)
+        .map(|disp| fcp_length_days(durations, disp))
+        .unwrap_or(durations.merge);
+    proposal
+        .fcp_end_override
+        .or_else(|| proposal.fcp_start.map(|s| s + Duration::days(length_days)))
+}
+
+/// Has a final comment period lasting `length_days`, which started at `start`, finished?
+fn fcp_finished(start: NaiveDateTime, length_days: i64, now: NaiveDateTime) -> bool {
+    start + Duration::days(length_days) <= now
+}
+
+/// Has this proposal's final comment period finished, given the length it would usually have?
+/// An end date set with `fcp-end` takes precedence.
+fn proposal_finished(proposal: &FcpProposal, length_days: i64, now: NaiveDateTime) -> bool {
+    match (proposal.fcp_end_override, proposal.fcp_start) {
+        (Some(end), _) => end <= now,
+        (None, Some(start)) => fcp_finished(start, length_days, now),
+        (None, None) => false,
+    }
+}
+
+/// The fewest days any final comment period can last, across every repository.
+fn shortest_fcp_length_days(config: &Config) -> i64 {
+    let shortest = |d: &FcpDurations| d.merge.min(d.close).min(d.postpone);
+    config
+        .repo_fcp_duration_days
+        .values()
+        .map(shortest)
+        .fold(shortest(&config.fcp_duration_days), i64::min)
+}
+
+/// Whether a proposal's final comment period is over, going by the length of FCPs in its
+/// repository.
+fn repo_proposal_finished(
+    config: &Config,
+    issue: &Issue,
+    proposal: &FcpProposal,
+    disp: FcpDisposition,
+    now: NaiveDateTime,
+) -> bool {
+    let durations = config.fcp_durations_for(&issue.repository);
+    proposal_finished(proposal, fcp_length_days(durations, disp), now)
+}
+
+/// Keep the countdown in the status comments of running FCPs current.
+fn evaluate_running() -> DashResult<()> {
+    use crate::domain::schema::fcp_proposal::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    let running = fcp_proposal
+        .filter(state.eq(ProposalState::InFcp.repr()))
+        .load::<FcpProposal>(conn)?;
+
+    for proposal in running {
+        let issue = issue::table.find(proposal.fk_issue).first::<Issue>(conn);
+        let issue = ok_or_continue!(issue, why =>
+            error!("Unable to find issue to match proposal {}: {:?}",
+                   proposal.id, why));
+
+        ok_or_continue!(refresh_status_comment(&issue, &proposal), why =>
+            error!("Unable to update status comment for proposal {}: {:?}",
+                   proposal.id, why));
+    }
+
+    Ok(())
+}
+
+fn evaluate_ffcps() -> DashResult<()> {
+    use crate::domain::schema::fcp_proposal::dsl::*;
+    use diesel::prelude::*;
+    let conn = &*DB_POOL.get()?;
+
+    // look for any FCP proposals that entered FCP long enough ago to have finished with the
+    // shortest duration in any repository but aren't marked as closed, the real duration is
+    // checked below
+    let now = Utc::now().naive_utc();
+    let shortest = shortest_fcp_length_days(&CONFIG);
+    let ffcps = fcp_proposal
+        .filter(state.eq(ProposalState::InFcp.repr()))
+        .filter(
+            fcp_start
+                .le(now - Duration::days(shortest))
+                .or(fcp_end_override.le(now)),
+        )
+        .load::<FcpProposal>(conn);
+    let finished_fcps = ok_or!(ffcps, why => {
+        error!("Unable to retrieve FCPs that need to be marked as finished: {:?}",
+               why);
+        throw!(why);
+    });
+
+    for mut proposal in finished_fcps {
+        // parse the disposition:
+        let disp = FcpDisposition::from_str(&proposal.disposition)?;
+
+        let issue = issue::table.find(proposal.fk_issue).first::<Issue>(conn);
+        let issue = ok_or_continue!(issue, why =>
+            error!("Unable to find issue to match proposal {}: {:?}",
+                   proposal.id, why));
+
+        if !repo_proposal_finished(&CONFIG, &issue, &proposal, disp, now) {
+            continue;
+        }
+
+        let initiator = githubuser::table
+            .find(proposal.fk_initiator)
+            .first::<GitHubUser>(conn);
+        let initiator = ok_or_continue!(initiator, why =>
+            error!("Unable to retrieve proposal initiator for proposal id {}: {:?}",
+                    proposal.id,
+                    why));
+
+        finish_fcp(&CONFIG, conn, &mut proposal, &issue, &initiator, disp);
+    }
+
+    Ok(())
+}
+
+/// Mark a proposal's final comment period as finished, announcing it and carrying out its
+/// disposition where the deployment allows.
+fn finish_fcp(
+    config: &Config,
+    conn: &PgConnection,
+    proposal: &mut FcpProposal,
+    issue: &Issue,
+    initiator: &GitHubUser,
+    disp: FcpDisposition,
+) {
+    // Add FFCP label and remove FCP label.
+    let label_res = issue.add_label(Label::FFCP);
+    issue.remove_label(Label::FCP);
+    let added_label = match label_res {
+        Ok(_) => {
+            if let Err(why) = issue.add_label(Label::ToAnnounce) {
+                warn!(
+                    "Unable to add to-announce label to {}#{}: {:?}",
+                    issue.repository, issue.number, why
+                );
+            }
+            true
+        }
+        Err(why) => {
+            warn!(
+                "Unable to add Finished-FCP label to {}#{}: {:?}",
+                issue.repository, issue.number, why
+            );
+            false
+        }
+    };
+
+    // Build the comment:
+    let comment_type = CommentType::FcpWeekPassed {
+        added_label,
+        author: initiator,
+        status_comment_id: proposal.fk_bot_tracking_comment,
+        disposition: disp,
+    };
+    let fcp_close_comment = RfcBotComment::new(issue, comment_type);
+
+    // Post it! The fcp is only closed in the database once the comment is up, so that a
+    // failed post is retried on the next pass
+    if posting_enabled() && may_comment_on(issue) {
+        let posted = ok_or!(fcp_close_comment.post(None), why => {
+            error!("Unable to post FCP-ending comment for proposal {}: {:?}",
+                    proposal.id, why);
+            return;
+        });
+        if posted.comment().is_none() {
+            return;
+        }
+    }
+
+    // update the fcp
+    ok_or!(proposal.transition(ProposalState::Closed), why => {
+        error!("Unable to close FCP {}: {:?}", proposal.id, why);
+        return;
+    });
+    let update_fcp = diesel::update(fcp_proposal::table.find(proposal.id))
+        .set(&*proposal)
+        .execute(conn);
+    ok_or!(update_fcp, why => {
+        error!("Unable to update FCP {}: {:?}", proposal.id, why);
+        return;
+    });
+
+    if config.status_emoji {
+        ok_or!(refresh_status_comment(issue, proposal), why =>
+            warn!("Unable to show FCP {} has finished: {:?}", proposal.id, why));
+    }
+    notify_transition(issue, proposal);
+    issue.remove_tracking_label(proposal);
+    execute_ffcp_actions(config, issue, disp);
+}
+
+/// What's sent to `NOTIFY_WEBHOOK_URL` when a proposal changes state.
+#[derive(Debug, Serialize)]
+struct ProposalNotification<'a> {
+    proposal_id: i32,
+    repository: &'a str,
+    number: i32,
+    disposition: &'a str,
+    state: &'a str,
+}
+
+/// How long a webhook or the error tracker gets to answer before we give up on it.
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+lazy_static! {
+    static ref WEBHOOK_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .expect("Unable to build the webhook client");
+}
+
+/// Post `payload` to `url` from another thread, so that a slow receiver can't hold up the nag
+/// pass (and `NAG_LOCK` with it). Failures to deliver it are only logged.
+fn post_in_background<T: serde::Serialize>(url: &str, payload: &T) -> DashResult<()> {
+    let url = url.to_string();
+    let payload = serde_json::to_value(payload)?;
+    std::thread::spawn(move || {
+        let sent = WEBHOOK_CLIENT
+            .post(&url)
+            .json(&payload)
+            .send()
+            .and_then(|res| res.error_for_status());
+        ok_or!(sent, why => warn!("Unable to post to {}: {:?}", url, why));
+    });
+    Ok(())
+}
+
+/// Tell the configured webhook, if any, that a proposal has entered or left its final comment
+/// period. This is best-effort, so failures are only logged.
+fn notify_transition(issue: &Issue, proposal: &FcpProposal) {
+    let url = CONFIG.notify_webhook_url.as_deref();
+    send_notification(url, issue, proposal, |url, payload| {
+        post_in_background(url, payload)
+    });
+}
+
+fn send_notification<F>(url: Option<&str>, issue: &Issue, proposal: &FcpProposal, send: F)
+where
+    F: FnOnce(&str, &ProposalNotification<'_>) -> DashResult<()>,
+{
+    let url = match url {
+        Some(url) => url,
+        None => return,
+    };
+
+    let payload = ProposalNotification {
+        proposal_id: proposal.id,
+        repository: &issue.repository,
+        number: issue.number,
+        disposition: &proposal.disposition,
+        state: &proposal.state,
+    };
+    ok_or!(send(url, &payload), why =>
+        warn!("Unable to notify {} about proposal {}: {:?}", url, proposal.id, why));
+}
+
+fn can_ffcp_close(issue: &Issue) -> bool {
+    SETUP
+        .read()
+        .unwrap()
+        .should_ffcp_auto_close(&issue.repository)
+}
+
+fn can_ffcp_postpone(issue: &Issue) -> bool {
+    SETUP
+        .read()
+        .unwrap()
+        .should_ffcp_auto_postpone(&issue.repository)
+}
+
+fn execute_ffcp_actions(config: &Config, issue: &Issue, disposition: FcpDisposition) {
+    match disposition {
+        FcpDisposition::Merge if should_auto_merge(issue, config.auto_merge_on_fcp) => {
+            let merged = github().merge_pr(&issue.repository, issue.number, "merge");
+            let merged = merged.unwrap_or_else(|why| {
+                warn!(
+                    "Unable to merge {}#{}: {:?}",
+                    issue.repository, issue.number, why
+                );
+                false
+            });
+            if !merged {
+                let _ = RfcBotComment::new(issue, CommentType::MergeFailed).post(None);
+            }
+        }
+        FcpDisposition::Merge => {
+            // TODO: This one will require a lot of work to
+            // auto-merge RFCs and create the tracking issue.
+        }
+        FcpDisposition::Close if can_ffcp_close(issue) => {
+            let _ = issue.add_label(Label::Closed);
+            issue.remove_label(Label::DispositionClose);
+            issue.close();
+        }
+        FcpDisposition::Postpone if can_ffcp_postpone(issue) => {
             let _ = issue.add_label(Label::Postponed);
             issue.remove_label(Label::DispositionPostpone);
             issue.close();
@@ -565,6 +2101,11 @@ fn execute_ffcp_actions(issue: &Issue, disposition: FcpDisposition) {
     }
 }
 
+/// Only pull requests can be merged, and then only if the deployment has asked for it.
+fn should_auto_merge(issue: &Issue, auto_merge_on_fcp: bool) -> bool {
+    auto_merge_on_fcp && issue.is_pull_request && issue.open
+}
+
 fn list_review_requests(proposal_id: i32) -> DashResult<Vec<(GitHubUser, FcpReviewRequest)>> {
     let conn = &*DB_POOL.get()?;
 
@@ -614,7 +2155,7 @@ fn list_concerns_with_authors(proposal_id: i32) -> DashResult<Vec<(GitHubUser, F
 
     let concerns = fcp_concern::table
         .filter(fcp_concern::fk_proposal.eq(proposal_id))
-        .order(fcp_concern::name)
+        .order(fcp_concern::id)
         .load::<FcpConcern>(conn)?;
 
     let mut w_authors = Vec::with_capacity(concerns.len());
@@ -630,19 +2171,58 @@ fn list_concerns_with_authors(proposal_id: i32) -> DashResult<Vec<(GitHubUser, F
     Ok(w_authors)
 }
 
-fn resolve_applicable_feedback_requests(
-    author: &GitHubUser,
-    issue: &Issue,
-    comment: &IssueComment,
-) -> DashResult<()> {
-    use crate::domain::schema::rfc_feedback_request::dsl::*;
+/// The feedback requested on an issue, with who asked for it and who was asked, sorted by the
+/// requested user.
+fn list_feedback_requests(
+    issue_id: i32,
+) -> DashResult<Vec<(GitHubUser, GitHubUser, FeedbackRequest)>> {
+    use crate::domain::schema::rfc_feedback_request;
     let conn = &*DB_POOL.get()?;
 
-    // check for an open feedback request, close since no longer applicable
-    let existing_request = rfc_feedback_request
-        .filter(fk_requested.eq(author.id))
-        .filter(fk_issue.eq(issue.id))
-        .first::<FeedbackRequest>(conn)
+    let requests = rfc_feedback_request::table
+        .filter(rfc_feedback_request::fk_issue.eq(issue_id))
+        .load::<FeedbackRequest>(conn)?;
+
+    let mut w_users = Vec::with_capacity(requests.len());
+
+    for request in requests {
+        let initiator = githubuser::table
+            .filter(githubuser::id.eq(request.fk_initiator))
+            .first::<GitHubUser>(conn)?;
+        let requested = githubuser::table
+            .filter(githubuser::id.eq(request.fk_requested))
+            .first::<GitHubUser>(conn)?;
+
+        w_users.push((initiator, requested, request));
+    }
+
+    w_users.sort_by(|a, b| a.1.login.cmp(&b.1.login));
+
+    Ok(w_users)
+}
+
+/// Outstanding concerns come first so they're what readers see, then the resolved ones, each in
+/// the order they were raised. A concern appearing twice is only listed once.
+fn ordered_concerns(concerns: &[(GitHubUser, FcpConcern)]) -> Vec<&(GitHubUser, FcpConcern)> {
+    let mut ordered = concerns.iter().collect::<Vec<_>>();
+    ordered.sort_by_key(|(_, concern)| (concern.fk_resolved_comment.is_some(), concern.id));
+    ordered.dedup_by_key(|(_, concern)| concern.id);
+    ordered
+}
+
+fn resolve_applicable_feedback_requests(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+) -> DashResult<()> {
+    use crate::domain::schema::rfc_feedback_request::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    // check for an open feedback request, close since no longer applicable
+    let existing_request = rfc_feedback_request
+        .filter(fk_requested.eq(author.id))
+        .filter(fk_issue.eq(issue.id))
+        .first::<FeedbackRequest>(conn)
         .optional()?;
 
     if let Some(mut request) = existing_request {
@@ -674,25 +2254,53 @@ fn specific_subteam_members<F>(included: F) -> DashResult<Vec<GitHubUser>>
 where
     F: Fn(&String) -> bool,
 {
-    let setup = SETUP.read().unwrap();
-    let teams = setup.teams();
-    let members = teams
+    let members = team_member_logins(&SETUP.read().unwrap(), included);
+    resolve_logins_to_users(&members)
+}
+
+/// The logins of the members of every team whose label is `included`.
+fn team_member_logins<F>(setup: &RfcbotConfig, included: F) -> Vec<String>
+where
+    F: Fn(&String) -> bool,
+{
+    setup
+        .teams()
         .filter(|&(label, _)| included(&label.0))
         .flat_map(|(_, team)| team.member_logins().map(std::string::ToString::to_string))
         .collect::<BTreeSet<_>>()
         .into_iter() // diesel won't work with btreeset, and dedup has weird lifetime errors
-        .collect::<Vec<_>>();
-    resolve_logins_to_users(&members)
+        .collect::<Vec<_>>()
 }
 
 /// Return a list of all known team members.
 fn all_team_members() -> DashResult<Vec<GitHubUser>> { specific_subteam_members(|_| true) }
 
 /// Check if an issue comment is written by a member of one of the subteams
-/// labelled on the issue.
+/// responsible for the issue.
 fn subteam_members(issue: &Issue) -> DashResult<Vec<GitHubUser>> {
     // retrieve all of the teams tagged on this issue
-    specific_subteam_members(|label| issue.labels.contains(&label))
+    let members = {
+        let setup = SETUP.read().unwrap();
+        team_member_logins(&setup, |label| team_responsible(&setup, issue, label))
+    };
+    resolve_logins_to_users(&members)
+}
+
+/// The pings of the teams responsible for `issue`, e.g. `rust-lang/lang`.
+fn team_pings(setup: &RfcbotConfig, issue: &Issue) -> Vec<String> {
+    setup
+        .teams()
+        .filter(|&(label, _)| team_responsible(setup, issue, &label.0))
+        .map(|(_, team)| team.ping().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Is the team with this label responsible for `issue`? Either the issue is labelled for the
+/// team, or the team looks after everything in the issue's repository.
+fn team_responsible(setup: &RfcbotConfig, issue: &Issue, label: &str) -> bool {
+    issue.labels.iter().any(|l| l == label) || setup.repo_team(&issue.repository) == Some(label)
 }
 
 fn cancel_fcp(author: &GitHubUser, issue: &Issue, existing: &FcpProposal) -> DashResult<()> {
@@ -700,9 +2308,27 @@ fn cancel_fcp(author: &GitHubUser, issue: &Issue, existing: &FcpProposal) -> Das
 
     let conn = &*DB_POOL.get()?;
 
+    let mut cancelled = existing.clone();
+    cancelled.transition(ProposalState::Cancelled)?;
+
+    // the reviews and concerns are about to go, so the status comment is updated first
+    if CONFIG.status_emoji {
+        ok_or!(refresh_status_comment(issue, &cancelled), why =>
+            warn!("Unable to show proposal {} was cancelled: {:?}", cancelled.id, why));
+    }
+
     // if exists delete FCP with associated concerns, reviews, feedback requests
     // db schema has ON DELETE CASCADE
     diesel::delete(fcp_proposal.filter(id.eq(existing.id))).execute(conn)?;
+    announce_cancellation(author, issue, &cancelled);
+
+    Ok(())
+}
+
+/// Lets everyone know that a proposal which has been deleted was cancelled.
+fn announce_cancellation(author: &GitHubUser, issue: &Issue, cancelled: &FcpProposal) {
+    notify_transition(issue, cancelled);
+    issue.remove_tracking_label(cancelled);
 
     // leave github comment stating that FCP proposal cancelled
     let comment = RfcBotComment::new(issue, CommentType::FcpProposalCancelled(author));
@@ -716,8 +2342,6 @@ fn cancel_fcp(author: &GitHubUser, issue: &Issue, existing: &FcpProposal) -> Das
     ]
     .iter()
     .for_each(|&lab| issue.remove_label(lab));
-
-    Ok(())
 }
 
 fn existing_proposal(issue: &Issue) -> DashResult<Option<FcpProposal>> {
@@ -733,7 +2357,7 @@ fn post_insert_comment(issue: &Issue, comment: CommentType<'_>) -> DashResult<Is
     let conn = &*DB_POOL.get()?;
 
     let comment = RfcBotComment::new(issue, comment);
-    let comment = comment.post(None)?;
+    let comment = comment.post(None)?.comment().ok_or(DashError::Misc(None))?;
     info!("Posted base comment to github, no reviewers listed yet");
 
     // at this point our new comment doesn't yet exist in the database, so
@@ -765,12 +2389,47 @@ impl<'a> RfcBotCommand<'a> {
             StartPoll { teams, question } => process_poll(author, issue, comment, question, teams),
             FcpPropose(disp) => process_fcp_propose(author, issue, comment, team_members, disp),
             FcpCancel => process_fcp_cancel(author, issue),
-            Reviewed => process_reviewed(author, issue),
-            NewConcern(concern_name) => process_new_concern(author, issue, comment, concern_name),
-            ResolveConcern(concern_name) => {
-                process_resolve_concern(author, issue, comment, concern_name)
-            }
+            Confirm => process_confirm(author, issue, team_members),
+            Reviewed(note) => process_reviewed(author, issue, note),
+            Unreviewed => process_unreviewed(author, issue),
+            Waive(username) => process_waive(author, issue, username),
+            ReviewedFor(username) => process_reviewed_for(author, issue, username),
+            Snooze(days) => process_snooze(issue, days),
+            SetFcpEnd(date) => process_set_fcp_end(issue, date),
+            EndFcpNow => process_end_fcp_now(issue),
+            Version => process_version(issue),
+            ProposalId => process_proposal_id(author, issue),
+            History => process_history(author, issue),
+            NextSteps => process_next_steps(author, issue),
+            Mute => set_muted(issue, true),
+            Unmute => set_muted(issue, false),
+            Restore => process_restore(issue, team_members),
+            Hold => process_hold(issue, true),
+            Unhold => process_hold(issue, false),
+            Track { label, milestone } => process_track(issue, label, milestone),
+            Mirror(number) => process_mirror(issue, number),
+            NewConcern(concern_name, directed_at, concern_description) => process_new_concern(
+                author,
+                issue,
+                comment,
+                concern_name,
+                directed_at,
+                concern_description,
+            ),
+            ResolveConcern(concern_name) => process_resolve_concern(
+                author,
+                issue,
+                comment,
+                concern_name,
+                CONFIG.admins.contains(&author.login),
+            ),
+            ResolveAll => process_resolve_all(author, issue, comment),
+            ListConcerns => process_list_concerns(author, issue),
+            ListReviewed => process_list_reviewed(author, issue),
             FeedbackRequest(username) => process_feedback_request(author, issue, username),
+            FeedbackDone(username) => process_feedback_done(issue, comment, username),
+            ListFeedback => process_list_feedback(author, issue),
+            Echo { text, parsed } => process_echo(author, issue, text, parsed.as_deref()),
         }
     }
 }
@@ -790,7 +2449,7 @@ fn process_poll(
         let setup = SETUP.read().unwrap();
         tmp_teams = setup
             .teams()
-            .filter(|&(label, _)| issue.labels.contains(&label.0))
+            .filter(|&(label, _)| team_responsible(&setup, issue, &label.0))
             .map(|(label, _)| label.0.clone())
             .collect::<BTreeSet<_>>();
         tmp_teams.iter().map(std::string::String::as_str).collect()
@@ -862,7 +2521,7 @@ fn process_poll(
             respondents: &*response_requests,
         },
     );
-    new_gh_comment.post(Some(gh_comment.id))?;
+    new_gh_comment.post(Some(&gh_comment))?;
 
     debug!("github comment updated with poll respondents");
 
@@ -877,16 +2536,80 @@ fn process_fcp_propose(
     disp: FcpDisposition,
 ) -> DashResult<()> {
     debug!("processing fcp proposal: {:?}", disp);
+    let (reviewers, auto_review) = {
+        let setup = SETUP.read().unwrap();
+        (
+            disposition_reviewers(&setup, disp, team_members),
+            setup.initiator_auto_review(|label| team_responsible(&setup, issue, label)),
+        )
+    };
+    propose_fcp(
+        author,
+        issue,
+        comment,
+        &reviewers,
+        disp,
+        auto_review,
+        CONFIG.require_label_for_fcp.as_deref(),
+    )
+}
+
+/// The members of the issue's teams who review a proposal with this disposition.
+fn disposition_reviewers(
+    setup: &RfcbotConfig,
+    disp: FcpDisposition,
+    team_members: &[GitHubUser],
+) -> Vec<GitHubUser> {
+    team_members
+        .iter()
+        .filter(|member| setup.reviews_disposition(disp.repr(), &member.login))
+        .cloned()
+        .collect()
+}
+
+/// Starts a proposal on the issue unless it already has one, with the initiator's review done
+/// if `auto_review` is set. Issues without the `required_label` get an explanation instead.
+fn propose_fcp(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+    team_members: &[GitHubUser],
+    disp: FcpDisposition,
+    auto_review: bool,
+    required_label: Option<&str>,
+) -> DashResult<()> {
     use crate::domain::schema::fcp_proposal::dsl::*;
 
     if existing_proposal(issue)?.is_none() {
+        if let Some(label) = required_label.filter(|&l| !issue.labels.iter().any(|i| i == l)) {
+            info!(
+                "refusing a proposal on {}#{}, it isn't labelled {}",
+                issue.repository, issue.number, label
+            );
+            let refusal = CommentType::FcpLabelRequired { author, label };
+            RfcBotComment::new(issue, refusal).post(None)?;
+            return Ok(());
+        }
+
         let conn = &*DB_POOL.get()?;
         // if not exists, create new FCP proposal
         info!("proposal is a new FCP, creating...");
 
         // leave github comment stating that FCP is proposed, ping reviewers
-        let gh_comment =
-            post_insert_comment(issue, CommentType::FcpProposed(author, disp, &[], &[]))?;
+        let teams = team_pings(&SETUP.read().unwrap(), issue);
+        let gh_comment = post_insert_comment(
+            issue,
+            CommentType::FcpProposed {
+                initiator: author,
+                disposition: disp,
+                reviewers: &[],
+                concerns: &[],
+                teams: &teams,
+                min_distinct_reviewers: CONFIG.min_distinct_reviewers,
+                fcp_end: None,
+                state: shown_state(ProposalState::Pending),
+            },
+        )?;
 
         let proposal = NewFcpProposal {
             fk_issue: issue.id,
@@ -895,25 +2618,24 @@ fn process_fcp_propose(
             fk_bot_tracking_comment: gh_comment.id,
             disposition: disp.repr(),
             fcp_start: None,
-            fcp_closed: false,
+            snooze_until: None,
+            state: ProposalState::Pending.repr(),
+            almost_ready_notified: false,
+            fcp_end_override: None,
+            tracking_label: CONFIG.tracking_label.as_deref(),
         };
         let proposal = diesel::insert_into(fcp_proposal)
             .values(&proposal)
             .get_result::<FcpProposal>(conn)?;
 
         debug!("proposal inserted into the database");
+        ok_or!(issue.add_tracking_label(&proposal), why =>
+            warn!("Unable to add the tracking label to {}#{}: {:?}",
+                  issue.repository, issue.number, why));
 
         // generate review requests for all relevant subteam members
-
-        let review_requests = team_members
-            .iter()
-            .map(|member| NewFcpReviewRequest {
-                fk_proposal: proposal.id,
-                fk_reviewer: member.id,
-                // let's assume the initiator has reviewed it
-                reviewed: member.id == author.id,
-            })
-            .collect::<Vec<_>>();
+        let review_requests =
+            initial_review_requests(proposal.id, author, team_members, auto_review);
 
         diesel::insert_into(fcp_review_request::table)
             .values(&review_requests)
@@ -929,408 +2651,5771 @@ fn process_fcp_propose(
 
         let new_gh_comment = RfcBotComment::new(
             issue,
-            CommentType::FcpProposed(author, disp, &review_requests, &[]),
+            CommentType::FcpProposed {
+                initiator: author,
+                disposition: disp,
+                reviewers: &review_requests,
+                concerns: &[],
+                teams: &teams,
+                min_distinct_reviewers: CONFIG.min_distinct_reviewers,
+                fcp_end: None,
+                state: shown_state(ProposalState::Pending),
+            },
         );
-        new_gh_comment.post(Some(gh_comment.id))?;
+        new_gh_comment.post(Some(&gh_comment))?;
         debug!("github comment updated with reviewers");
-    }
-
-    Ok(())
-}
-
-fn process_fcp_cancel(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
-    if let Some(existing) = existing_proposal(issue)? {
-        cancel_fcp(author, issue, &existing)?;
-    }
-    Ok(())
-}
-
-fn process_reviewed(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
-    // set a reviewed entry for the comment author on this issue
-    if let Some(proposal) = existing_proposal(issue)? {
-        use crate::domain::schema::fcp_review_request::dsl::*;
-        let conn = &*DB_POOL.get()?;
 
-        let review_request = fcp_review_request
-            .filter(fk_proposal.eq(proposal.id))
-            .filter(fk_reviewer.eq(author.id))
-            .first::<FcpReviewRequest>(conn)
-            .optional()?;
+        let unreviewed = review_requests
+            .iter()
+            .filter(|(_, request)| !request.reviewed)
+            .map(|(member, _)| member.clone())
+            .collect::<Vec<_>>();
+        request_reviews(issue, &proposal, &unreviewed)?;
 
-        if let Some(mut review_request) = review_request {
-            // store an FK to the comment marking for review (not null fk here means
-            // reviewed)
-            review_request.reviewed = true;
-            diesel::update(fcp_review_request.find(review_request.id))
-                .set(&review_request)
-                .execute(conn)?;
+        if review_requests.is_empty() {
+            warn!("no reviewers for proposal {}", proposal.id);
+            let comment = RfcBotComment::new(issue, CommentType::NoReviewers(author));
+            ok_or!(comment.post(None), why =>
+                warn!("Unable to say proposal {} has no reviewers: {:?}", proposal.id, why));
         }
+
+        ok_or!(check_linked_proposal(author, issue, disp), why =>
+            warn!("Unable to check {}#{}'s linked issue for proposals: {:?}",
+                  issue.repository, issue.number, why));
     }
 
     Ok(())
 }
 
-fn process_new_concern(
-    author: &GitHubUser,
-    issue: &Issue,
-    comment: &IssueComment,
-    concern_name: &str,
-) -> DashResult<()> {
-    if let Some(mut proposal) = existing_proposal(issue)? {
-        // check for existing concern
-        use crate::domain::schema::fcp_concern::dsl::*;
-        use crate::domain::schema::fcp_proposal::dsl::*;
-        let conn = &*DB_POOL.get()?;
-
-        let existing_concern = fcp_concern
-            .filter(fk_proposal.eq(proposal.id))
-            .filter(name.eq(concern_name))
-            .first::<FcpConcern>(conn)
-            .optional()?;
+/// Swaps the tracking label of the issue's proposal for `label`, and puts the issue in the
+/// milestone if one is given.
+fn process_track(issue: &Issue, label: &str, milestone: Option<i32>) -> DashResult<()> {
+    let mut proposal = match existing_proposal(issue)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    let conn = &*DB_POOL.get()?;
 
-        if existing_concern.is_none() {
-            // if not exists, create new concern with this author as creator
-            let new_concern = NewFcpConcern {
-                fk_proposal: proposal.id,
-                fk_initiator: author.id,
-                fk_resolved_comment: None,
-                name: concern_name,
-                fk_initiating_comment: comment.id,
-            };
-            diesel::insert_into(fcp_concern)
-                .values(&new_concern)
-                .execute(conn)?;
+    let previous = proposal.tracking_label.replace(label.to_string());
+    diesel::update(fcp_proposal::table.find(proposal.id))
+        .set(&proposal)
+        .execute(conn)?;
 
-            // Take us out of FCP and back into PFCP if need be:
-            if proposal.fcp_start.is_some() {
-                // Update DB: FCP is not started anymore.
-                proposal.fcp_start = None;
-                let update = diesel::update(fcp_proposal.find(proposal.id))
-                    .set(&proposal)
-                    .execute(conn);
-                ok_or!(update, why => {
-                    error!("Unable to mark FCP {} as unstarted: {:?}", proposal.id, why);
-                    return Ok(());
-                });
+    if let Some(previous) = previous.filter(|previous| previous != label) {
+        issue.remove_label_named(&previous);
+    }
+    issue.add_tracking_label(&proposal)?;
 
-                // Update labels:
-                let _ = issue.add_label(Label::PFCP);
-                issue.remove_label(Label::FCP);
-            }
-        }
+    if let Some(milestone) = milestone {
+        github().set_milestone(&issue.repository, issue.number, milestone)?;
     }
 
     Ok(())
 }
 
-fn process_resolve_concern(
-    author: &GitHubUser,
-    issue: &Issue,
-    comment: &IssueComment,
-    concern_name: &str,
-) -> DashResult<()> {
-    debug!("Command is to resolve a concern ({}).", concern_name);
-
-    if let Some(proposal) = existing_proposal(issue)? {
-        // check for existing concern
-        use crate::domain::schema::fcp_concern::dsl::*;
-        let conn = &*DB_POOL.get()?;
-
-        let existing_concern = fcp_concern
-            .filter(fk_proposal.eq(proposal.id))
-            .filter(fk_initiator.eq(author.id))
-            .filter(name.eq(concern_name))
-            .first::<FcpConcern>(conn)
-            .optional()?;
+/// Starts keeping a read-only copy of the proposal's status on another issue in the repository.
+fn process_mirror(issue: &Issue, target_number: i32) -> DashResult<()> {
+    let proposal = match existing_proposal(issue)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    if target_number == issue.number {
+        return Ok(());
+    }
+    let conn = &*DB_POOL.get()?;
 
-        if let Some(mut concern) = existing_concern {
-            // mark concern as resolved by adding resolved_comment
-            debug!("Found a matching concern ({})", concern_name);
-            concern.fk_resolved_comment = Some(comment.id);
-            diesel::update(fcp_concern.find(concern.id))
-                .set(&concern)
-                .execute(conn)?;
+    let target = issue::table
+        .filter(issue::repository.eq(issue.repository.as_str()))
+        .filter(issue::number.eq(target_number))
+        .first::<Issue>(conn)
+        .optional()?;
+    let target = match target {
+        Some(target) => target,
+        None => {
+            warn!(
+                "Can't mirror proposal {} to {}#{}, it hasn't been scraped yet",
+                proposal.id, issue.repository, target_number
+            );
+            return Ok(());
         }
+    };
+
+    let existing = fcp_mirrors::table
+        .filter(fcp_mirrors::fk_proposal.eq(proposal.id))
+        .filter(fcp_mirrors::fk_issue.eq(target.id))
+        .first::<FcpMirror>(conn)
+        .optional()?;
+    if existing.is_some() {
+        return Ok(());
     }
 
+    let reviews = list_review_requests(proposal.id)?;
+    let concerns = list_concerns_with_authors(proposal.id)?;
+    let comment = post_insert_comment(
+        &target,
+        CommentType::FcpMirror {
+            source: issue,
+            proposal: &proposal,
+            reviewers: &reviews,
+            concerns: &concerns,
+        },
+    )?;
+
+    diesel::insert_into(fcp_mirrors::table)
+        .values(&NewFcpMirror {
+            fk_proposal: proposal.id,
+            fk_issue: target.id,
+            fk_mirror_comment: comment.id,
+        })
+        .execute(conn)?;
+    info!(
+        "mirroring proposal {} to {}#{}",
+        proposal.id, target.repository, target.number
+    );
     Ok(())
 }
 
-fn process_feedback_request(author: &GitHubUser, issue: &Issue, username: &str) -> DashResult<()> {
-    use crate::domain::schema::rfc_feedback_request::dsl::*;
+/// Brings the mirrors of every proposal up to date with its status.
+fn update_mirrors() -> DashResult<()> {
     let conn = &*DB_POOL.get()?;
 
-    // we'll just assume that this user exists...it's very unlikely that someone
-    // will request feedback from a user who's *never* commented or committed
-    // on/to a rust-lang* repo
-    let requested_user = githubuser::table
-        .filter(githubuser::login.eq(username))
-        .first::<GitHubUser>(conn)?;
+    let mirrors = fcp_mirrors::table
+        .inner_join(issue::table)
+        .load::<(FcpMirror, Issue)>(conn)?;
+    for (mirror, target) in mirrors {
+        ok_or!(update_mirror(conn, &mirror, &target), why =>
+            error!("Unable to update the mirror of proposal {} on {}#{}: {:?}",
+                   mirror.fk_proposal, target.repository, target.number, why));
+    }
+    Ok(())
+}
 
-    // check for existing feedback request
-    let existing_request = rfc_feedback_request
-        .filter(fk_requested.eq(requested_user.id))
-        .filter(fk_issue.eq(issue.id))
-        .first::<FeedbackRequest>(conn)
-        .optional()?;
+fn update_mirror(conn: &PgConnection, mirror: &FcpMirror, target: &Issue) -> DashResult<()> {
+    if !posting_enabled() || !may_comment_on(target) {
+        return Ok(());
+    }
 
-    if existing_request.is_none() {
-        // create feedback request
-        let new_request = NewFeedbackRequest {
-            fk_initiator: author.id,
-            fk_requested: requested_user.id,
-            fk_issue: issue.id,
-            fk_feedback_comment: None,
-        };
-        diesel::insert_into(rfc_feedback_request)
-            .values(&new_request)
+    let (proposal, source) = fcp_proposal::table
+        .inner_join(issue::table)
+        .filter(fcp_proposal::id.eq(mirror.fk_proposal))
+        .first::<(FcpProposal, Issue)>(conn)?;
+    let reviews = list_review_requests(proposal.id)?;
+    let concerns = list_concerns_with_authors(proposal.id)?;
+    let previous = issuecomment::table
+        .find(mirror.fk_mirror_comment)
+        .first::<IssueComment>(conn)?;
+
+    let comment = RfcBotComment::new(
+        target,
+        CommentType::FcpMirror {
+            source: &source,
+            proposal: &proposal,
+            reviewers: &reviews,
+            concerns: &concerns,
+        },
+    );
+    if let Some(posted) = comment.post(Some(&previous))?.comment() {
+        let posted = posted.with_repo(&target.repository)?;
+        diesel::update(issuecomment::table.find(posted.id))
+            .set(&posted)
             .execute(conn)?;
     }
-
     Ok(())
 }
 
-struct RfcBotComment<'a> {
-    issue: &'a Issue,
-    body: String,
-    comment_type: CommentType<'a>,
+/// Puts the proposal on hold or takes it back off, stopping its FCP if it was running.
+fn process_hold(issue: &Issue, hold: bool) -> DashResult<()> {
+    use crate::domain::schema::fcp_proposal::dsl::*;
+
+    let mut proposal = match existing_proposal(issue)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    let was_in_fcp = proposal.state()? == ProposalState::InFcp;
+    if !hold && proposal.state()? != ProposalState::OnHold {
+        throw!(DashError::Misc(Some(format!(
+            "proposal {} isn't on hold",
+            proposal.id
+        ))));
+    }
+    proposal.transition(if hold {
+        ProposalState::OnHold
+    } else {
+        ProposalState::Pending
+    })?;
+    if was_in_fcp {
+        proposal.fcp_start = None;
+        proposal.fcp_end_override = None;
+    }
+
+    let conn = &*DB_POOL.get()?;
+    diesel::update(fcp_proposal.find(proposal.id))
+        .set(&proposal)
+        .execute(conn)?;
+
+    if was_in_fcp {
+        notify_transition(issue, &proposal);
+        let _ = issue.add_label(Label::PFCP);
+        issue.remove_label(Label::FCP);
+    }
+    refresh_status_comment(issue, &proposal)
 }
 
-#[derive(Clone)]
-enum CommentType<'a> {
-    FcpProposed(
-        &'a GitHubUser,
-        FcpDisposition,
-        &'a [(GitHubUser, FcpReviewRequest)],
-        &'a [(GitHubUser, FcpConcern)],
-    ),
-    FcpProposalCancelled(&'a GitHubUser),
-    FcpAllReviewedNoConcerns {
-        author: &'a GitHubUser,
-        status_comment_id: i32,
-        added_label: bool,
-    },
-    FcpWeekPassed {
-        author: &'a GitHubUser,
-        status_comment_id: i32,
-        added_label: bool,
-        disposition: FcpDisposition,
-    },
-    QuestionAsked {
-        initiator: &'a GitHubUser,
-        respondents: &'a [(GitHubUser, PollResponseRequest)],
-        question: &'a str,
-        teams: BTreeSet<&'a str>,
-    },
+/// Brings back the proposal which was cancelled when the issue was closed, now that it's open
+/// again. Its reviews start over and its concerns are gone.
+fn process_restore(issue: &Issue, team_members: &[GitHubUser]) -> DashResult<()> {
+    if !issue.open {
+        info!(
+            "not restoring a proposal on {}#{}, it's still closed",
+            issue.repository, issue.number
+        );
+        return Ok(());
+    }
+
+    let auto_review = {
+        let setup = SETUP.read().unwrap();
+        setup.initiator_auto_review(|label| team_responsible(&setup, issue, label))
+    };
+    let conn = &*DB_POOL.get()?;
+    let proposal = match restore_archived(conn, issue, team_members, auto_review)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    info!(
+        "restored proposal {} on {}#{}",
+        proposal.id, issue.repository, issue.number
+    );
+
+    let disposition = FcpDisposition::from_str(&proposal.disposition)?;
+    let _ = issue.add_label(Label::PFCP);
+    let _ = issue.add_label(disposition.label());
+    ok_or!(issue.add_tracking_label(&proposal), why =>
+        warn!("Unable to add the tracking label to {}#{}: {:?}",
+              issue.repository, issue.number, why));
+    refresh_status_comment(issue, &proposal)
+}
+
+/// Turns the issue's archived proposal back into a pending one with new review requests,
+/// unless it doesn't have one or already has another proposal.
+fn restore_archived(
+    conn: &PgConnection,
+    issue: &Issue,
+    team_members: &[GitHubUser],
+    auto_review: bool,
+) -> DashResult<Option<FcpProposal>> {
+    if existing_proposal(issue)?.is_some() {
+        return Ok(None);
+    }
+
+    let archived = archived_proposals::table
+        .filter(archived_proposals::fk_issue.eq(issue.id))
+        .first::<ArchivedProposal>(conn)
+        .optional()?;
+    let archived = match archived {
+        Some(archived) => archived,
+        None => return Ok(None),
+    };
+    let initiator = githubuser::table
+        .find(archived.fk_initiator)
+        .first::<GitHubUser>(conn)?;
+
+    let proposal = conn.transaction::<_, diesel::result::Error, _>(|| {
+        let proposal = diesel::insert_into(fcp_proposal::table)
+            .values(&NewFcpProposal {
+                fk_issue: archived.fk_issue,
+                fk_initiator: archived.fk_initiator,
+                fk_initiating_comment: archived.fk_initiating_comment,
+                disposition: &archived.disposition,
+                fk_bot_tracking_comment: archived.fk_bot_tracking_comment,
+                fcp_start: None,
+                snooze_until: None,
+                state: ProposalState::Pending.repr(),
+                almost_ready_notified: false,
+                fcp_end_override: None,
+                tracking_label: archived.tracking_label.as_deref(),
+            })
+            .get_result::<FcpProposal>(conn)?;
+
+        let review_requests =
+            initial_review_requests(proposal.id, &initiator, team_members, auto_review);
+        diesel::insert_into(fcp_review_request::table)
+            .values(&review_requests)
+            .execute(conn)?;
+
+        diesel::delete(archived_proposals::table.find(archived.id)).execute(conn)?;
+        Ok(proposal)
+    })?;
+
+    Ok(Some(proposal))
+}
+
+/// The review requests a new proposal starts with, one per team member. Unless the teams want
+/// proposers to review explicitly, the initiator is assumed to have reviewed it.
+fn initial_review_requests(
+    proposal_id: i32,
+    author: &GitHubUser,
+    team_members: &[GitHubUser],
+    auto_review: bool,
+) -> Vec<NewFcpReviewRequest> {
+    team_members
+        .iter()
+        .map(|member| NewFcpReviewRequest {
+            fk_proposal: proposal_id,
+            fk_reviewer: member.id,
+            reviewed: auto_review && member.id == author.id,
+            waived: false,
+        })
+        .collect()
+}
+
+/// Warns when the tracking issue linked from a PR or issue's description already has an
+/// active proposal with a different disposition, so the two don't end up contradicting each
+/// other.
+fn check_linked_proposal(
+    author: &GitHubUser,
+    issue: &Issue,
+    disp: FcpDisposition,
+) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+
+    let linked_number = match linked_issue_number(&issue.body) {
+        Some(number) if number != issue.number => number,
+        _ => return Ok(()),
+    };
+
+    let linked = issue::table
+        .filter(issue::repository.eq(issue.repository.as_str()))
+        .filter(issue::number.eq(linked_number))
+        .first::<Issue>(conn)
+        .optional()?;
+    let linked_proposal = match linked {
+        Some(linked) => existing_proposal(&linked)?,
+        None => None,
+    };
+
+    if let Some(linked_proposal) = linked_proposal {
+        if let Some(linked_disposition) = conflicting_disposition(&linked_proposal, disp)? {
+            let comment_type = CommentType::ConflictingLinkedProposal {
+                author,
+                linked_issue: linked_number,
+                disposition: disp,
+                linked_disposition,
+            };
+            RfcBotComment::new(issue, comment_type).post(None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The issue number referenced by a line like `Tracking issue: #123`.
+fn linked_issue_number(body: &str) -> Option<i32> {
+    body.lines().find_map(|line| {
+        let line = line.trim().to_lowercase();
+        let prefix = ["tracking issue:", "tracking:"]
+            .iter()
+            .find(|prefix| line.starts_with(*prefix))?;
+
+        let number = line[prefix.len()..].trim().trim_start_matches('#');
+        let digits = number
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        digits.parse().ok()
+    })
+}
+
+/// The disposition of the linked proposal, if it's still active and differs from `disp`.
+fn conflicting_disposition(
+    linked: &FcpProposal,
+    disp: FcpDisposition,
+) -> DashResult<Option<FcpDisposition>> {
+    let active = matches!(
+        linked.state()?,
+        ProposalState::Pending | ProposalState::InFcp
+    );
+    let linked_disp = FcpDisposition::from_str(&linked.disposition)?;
+
+    Ok(if active && linked_disp != disp {
+        Some(linked_disp)
+    } else {
+        None
+    })
+}
+
+/// Record an FCP proposal from someone who isn't on any of the tagged teams, so that a
+/// member can confirm it later.
+fn queue_fcp_proposal(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+    disp: FcpDisposition,
+) -> DashResult<()> {
+    if existing_proposal(issue)?.is_some() || queued_proposal(issue)?.is_some() {
+        return Ok(());
+    }
+
+    let conn = &*DB_POOL.get()?;
+    info!("queueing FCP proposal from non-member {}", author.login);
+
+    let queued = NewProposedCommand {
+        fk_issue: issue.id,
+        fk_initiator: author.id,
+        fk_initiating_comment: comment.id,
+        disposition: disp.repr(),
+    };
+    diesel::insert_into(proposed_command::table)
+        .values(&queued)
+        .execute(conn)?;
+
+    let comment = RfcBotComment::new(
+        issue,
+        CommentType::FcpProposalQueued {
+            author,
+            disposition: disp,
+        },
+    );
+    ok_or!(comment.post(None), why =>
+        warn!("Unable to ask for confirmation of the proposal on {}#{}: {:?}",
+              issue.repository, issue.number, why));
+
+    Ok(())
+}
+
+fn queued_proposal(issue: &Issue) -> DashResult<Option<ProposedCommand>> {
+    let conn = &*DB_POOL.get()?;
+    Ok(proposed_command::table
+        .filter(proposed_command::fk_issue.eq(issue.id))
+        .first::<ProposedCommand>(conn)
+        .optional()?)
+}
+
+fn process_confirm(
+    author: &GitHubUser,
+    issue: &Issue,
+    team_members: &[GitHubUser],
+) -> DashResult<()> {
+    if let Some(queued) = queued_proposal(issue)? {
+        let conn = &*DB_POOL.get()?;
+        diesel::delete(proposed_command::table.find(queued.id)).execute(conn)?;
+
+        info!(
+            "{} confirmed queued FCP proposal from user id {}",
+            author.login, queued.fk_initiator
+        );
+
+        // the proposal is still the queued request's, started from its comment
+        let initiator = githubuser::table
+            .find(queued.fk_initiator)
+            .first::<GitHubUser>(conn)?;
+        let initiating_comment = issuecomment::table
+            .find(queued.fk_initiating_comment)
+            .first::<IssueComment>(conn)?;
+        let disp = FcpDisposition::from_str(&queued.disposition)?;
+        process_fcp_propose(&initiator, issue, &initiating_comment, team_members, disp)?;
+    }
+
+    Ok(())
+}
+
+fn process_fcp_cancel(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    if let Some(existing) = existing_proposal(issue)? {
+        cancel_fcp(author, issue, &existing)?;
+    }
+    Ok(())
+}
+
+fn process_reviewed(
+    author: &GitHubUser,
+    issue: &Issue,
+    review_note: Option<&str>,
+) -> DashResult<()> {
+    // set a reviewed entry for the comment author on this issue
+    if let Some(proposal) = existing_proposal(issue)? {
+        use crate::domain::schema::fcp_review_request::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        let review_request = fcp_review_request
+            .filter(fk_proposal.eq(proposal.id))
+            .filter(fk_reviewer.eq(author.id))
+            .first::<FcpReviewRequest>(conn)
+            .optional()?;
+
+        if let Some(mut review_request) = review_request {
+            // store an FK to the comment marking for review (not null fk here means
+            // reviewed)
+            review_request.reviewed = true;
+            review_request.recorded_by = None;
+            review_request.note = review_note.map(str::to_string);
+            diesel::update(fcp_review_request.find(review_request.id))
+                .set(&review_request)
+                .execute(conn)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn process_unreviewed(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    // clear the reviewed entry for the comment author on this issue, but only
+    // while the proposal is still pending
+    if let Some(proposal) = existing_proposal(issue)? {
+        if proposal.state()? != ProposalState::Pending {
+            info!(
+                "ignoring unreview from {}, FCP {} has already started",
+                author.login, proposal.id
+            );
+            return Ok(());
+        }
+
+        use crate::domain::schema::fcp_review_request::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        let review_request = fcp_review_request
+            .filter(fk_proposal.eq(proposal.id))
+            .filter(fk_reviewer.eq(author.id))
+            .first::<FcpReviewRequest>(conn)
+            .optional()?;
+
+        if let Some(mut review_request) = review_request {
+            if review_request.reviewed {
+                review_request.reviewed = false;
+                review_request.recorded_by = None;
+                review_request.note = None;
+                diesel::update(fcp_review_request.find(review_request.id))
+                    .set(&review_request)
+                    .execute(conn)?;
+
+                // the status comment still has a checked box for this reviewer, which
+                // would mark them as reviewed again the next time we evaluate nags
+                refresh_status_comment(issue, &proposal)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn process_list_concerns(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let concerns = match existing_proposal(issue)? {
+        Some(proposal) => list_concerns_with_authors(proposal.id)?,
+        None => Vec::new(),
+    };
+
+    let comment_type = CommentType::ConcernList {
+        author,
+        concerns: &concerns,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+/// Replies with the proposal's timeline, dated by the comments which moved it along. Reviews
+/// aren't on it, since nothing records when they were made, so they follow it as a roster.
+fn process_history(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let proposal = match existing_proposal(issue)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    let conn = &*DB_POOL.get()?;
+
+    let initiator = githubuser::table
+        .find(proposal.fk_initiator)
+        .first::<GitHubUser>(conn)?;
+    let concerns = list_concerns_with_authors(proposal.id)?;
+    let reviews = list_review_requests(proposal.id)?;
+
+    let comment_ids = concerns
+        .iter()
+        .flat_map(|(_, c)| {
+            Some(c.fk_initiating_comment)
+                .into_iter()
+                .chain(c.fk_resolved_comment)
+        })
+        .chain(Some(proposal.fk_initiating_comment))
+        .collect::<Vec<_>>();
+    let dates = issuecomment::table
+        .filter(issuecomment::id.eq_any(comment_ids))
+        .select((issuecomment::id, issuecomment::created_at))
+        .load::<(i32, NaiveDateTime)>(conn)?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    let date_of = |id| {
+        // proposals made in the description don't have a comment of their own
+        dates
+            .get(&id)
+            .cloned()
+            .or_else(|| Some(issue.created_at).filter(|_| id == body_comment(issue).id))
+    };
+
+    let length_days = fcp_length_days(
+        CONFIG.fcp_durations_for(&issue.repository),
+        FcpDisposition::from_str(&proposal.disposition)?,
+    );
+    let entries = proposal_history(&proposal, &initiator, &concerns, date_of, length_days);
+
+    let comment_type = CommentType::ProposalHistory {
+        author,
+        entries: &entries,
+        reviewers: &reviews,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+/// Replies with what the proposal is still waiting on, for proposers who aren't sure what
+/// happens next.
+fn process_next_steps(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let proposal = existing_proposal(issue)?;
+    let (reviewers, concerns) = match proposal {
+        Some(ref proposal) => (
+            list_review_requests(proposal.id)?,
+            list_concerns_with_authors(proposal.id)?,
+        ),
+        None => (vec![], vec![]),
+    };
+    let fcp_end = proposal
+        .as_ref()
+        .and_then(|proposal| fcp_end(proposal, CONFIG.fcp_durations_for(&issue.repository)));
+
+    let comment_type = CommentType::NextSteps {
+        author,
+        proposal: proposal.as_ref(),
+        reviewers: &reviewers,
+        concerns: &concerns,
+        fcp_end,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+/// Something which happened to a proposal, for its history.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct HistoryEntry {
+    at: NaiveDateTime,
+    event: String,
+}
+
+/// The dated events in a proposal's life so far, oldest first. `date_of` gives when a comment
+/// was made, events whose comment can't be found are left out.
+fn proposal_history<F>(
+    proposal: &FcpProposal,
+    initiator: &GitHubUser,
+    concerns: &[(GitHubUser, FcpConcern)],
+    date_of: F,
+    length_days: i64,
+) -> Vec<HistoryEntry>
+where
+    F: Fn(i32) -> Option<NaiveDateTime>,
+{
+    let mut entries = vec![];
+    let mut push = |at: Option<NaiveDateTime>, event: String| {
+        if let Some(at) = at {
+            entries.push(HistoryEntry { at, event });
+        }
+    };
+
+    push(
+        date_of(proposal.fk_initiating_comment),
+        format!(
+            "@{} proposed to {} this",
+            initiator.login, proposal.disposition
+        ),
+    );
+    for (author, concern) in concerns {
+        push(
+            date_of(concern.fk_initiating_comment),
+            format!("@{} raised the concern `{}`", author.login, concern.name),
+        );
+        push(
+            concern.fk_resolved_comment.and_then(&date_of),
+            format!(
+                "the concern `{}` was resolved{}",
+                concern.name,
+                if concern.fk_overridden_by.is_some() {
+                    " by an administrator"
+                } else {
+                    ""
+                }
+            ),
+        );
+    }
+    if let Some(start) = proposal.fcp_start {
+        push(Some(start), "the final comment period started".to_string());
+        if proposal.state().ok() == Some(ProposalState::Closed) {
+            let end = proposal
+                .fcp_end_override
+                .unwrap_or_else(|| start + Duration::days(length_days));
+            push(Some(end), "the final comment period ended".to_string());
+        }
+    }
+
+    // ties keep the order above, so a proposal comes before concerns raised alongside it
+    entries.sort_by_key(|entry| entry.at);
+    entries
+}
+
+fn process_list_reviewed(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let reviewers = match existing_proposal(issue)? {
+        Some(proposal) => list_review_requests(proposal.id)?,
+        None => Vec::new(),
+    };
+
+    let comment_type = CommentType::ReviewedRoster {
+        author,
+        reviewers: &reviewers,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+fn process_list_feedback(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let requests = list_feedback_requests(issue.id)?;
+
+    let comment_type = CommentType::FeedbackList {
+        author,
+        requests: &requests,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+fn process_echo(
+    author: &GitHubUser,
+    issue: &Issue,
+    text: &str,
+    parsed: Option<&RfcBotCommand<'_>>,
+) -> DashResult<()> {
+    let comment_type = CommentType::ParseEcho {
+        author,
+        text,
+        parsed,
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+fn process_version(issue: &Issue) -> DashResult<()> {
+    let comment_type = CommentType::Status {
+        version: VERSION,
+        commit: COMMIT.unwrap_or("unknown"),
+        post_comments: CONFIG.post_comments,
+        durations: CONFIG.fcp_durations_for(&issue.repository),
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
+}
+
+fn process_proposal_id(author: &GitHubUser, issue: &Issue) -> DashResult<()> {
+    let proposal = existing_proposal(issue)?;
+    let comment_type = CommentType::ProposalId {
+        author,
+        proposal: proposal.as_ref(),
+    };
+    RfcBotComment::new(issue, comment_type).post(None)?;
+    Ok(())
 }
 
-impl<'a> RfcBotComment<'a> {
-    fn new(issue: &'a Issue, comment_type: CommentType<'a>) -> RfcBotComment<'a> {
-        let body = Self::format(issue, &comment_type);
+fn process_set_fcp_end(issue: &Issue, date: NaiveDate) -> DashResult<()> {
+    use crate::domain::schema::fcp_proposal::dsl::*;
+
+    if let Some(mut proposal) = existing_proposal(issue)? {
+        let end = date.and_hms(0, 0, 0);
+        check_fcp_end(end, proposal.fcp_start, Utc::now().naive_utc())?;
+
+        let conn = &*DB_POOL.get()?;
+        proposal.fcp_end_override = Some(end);
+        diesel::update(fcp_proposal.find(proposal.id))
+            .set(&proposal)
+            .execute(conn)?;
+        refresh_status_comment(issue, &proposal)?;
+    }
+
+    Ok(())
+}
+
+/// Finish the issue's final comment period without waiting for the rest of it to pass.
+fn process_end_fcp_now(issue: &Issue) -> DashResult<()> {
+    let mut proposal = match existing_proposal(issue)? {
+        Some(proposal) => proposal,
+        None => return Ok(()),
+    };
+    if proposal.state()? != ProposalState::InFcp {
+        throw!(DashError::Misc(Some(format!(
+            "proposal {} isn't in its final comment period",
+            proposal.id
+        ))));
+    }
+
+    let conn = &*DB_POOL.get()?;
+    let initiator = githubuser::table
+        .find(proposal.fk_initiator)
+        .first::<GitHubUser>(conn)?;
+    let disp = FcpDisposition::from_str(&proposal.disposition)?;
+    finish_fcp(&CONFIG, conn, &mut proposal, issue, &initiator, disp);
+    Ok(())
+}
+
+/// A final comment period can't be made to end before it started, or in the past.
+fn check_fcp_end(
+    end: NaiveDateTime,
+    start: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> DashResult<()> {
+    if end <= now || start.map_or(false, |start| end <= start) {
+        throw!(DashError::Misc(Some(format!(
+            "the end of an FCP must be in the future and after it started, not {}",
+            end
+        ))));
+    }
+    Ok(())
+}
+
+fn set_muted(issue: &Issue, muted: bool) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+    diesel::update(issue::table.find(issue.id))
+        .set(issue::muted.eq(muted))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn process_snooze(issue: &Issue, days: i64) -> DashResult<()> {
+    if let Some(mut proposal) = existing_proposal(issue)? {
+        use crate::domain::schema::fcp_proposal::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        // only the reminders are paused, the FCP itself keeps going
+        proposal.snooze_until = Some(Utc::now().naive_utc() + Duration::days(days));
+        diesel::update(fcp_proposal.find(proposal.id))
+            .set(&proposal)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn process_waive(author: &GitHubUser, issue: &Issue, username: &str) -> DashResult<()> {
+    if let Some(proposal) = existing_proposal(issue)? {
+        if proposal.state()? != ProposalState::Pending {
+            return Ok(());
+        }
+
+        let conn = &*DB_POOL.get()?;
+
+        let review_request = fcp_review_request::table
+            .inner_join(githubuser::table)
+            .filter(fcp_review_request::fk_proposal.eq(proposal.id))
+            .filter(githubuser::login.eq(username))
+            .select(fcp_review_request::all_columns)
+            .first::<FcpReviewRequest>(conn)
+            .optional()?;
+
+        if let Some(mut review_request) = review_request {
+            info!(
+                "{} waived the review from {} on proposal {}",
+                author.login, username, proposal.id
+            );
+            review_request.waived = true;
+            diesel::update(fcp_review_request::table.find(review_request.id))
+                .set(&review_request)
+                .execute(conn)?;
+
+            refresh_status_comment(issue, &proposal)?;
+        } else {
+            info!(
+                "{} tried to waive a review from {}, who isn't a reviewer on proposal {}",
+                author.login, username, proposal.id
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Mark `username`'s review as done on their behalf, noting who recorded it.
+fn process_reviewed_for(author: &GitHubUser, issue: &Issue, username: &str) -> DashResult<()> {
+    if let Some(proposal) = existing_proposal(issue)? {
+        let conn = &*DB_POOL.get()?;
+
+        let review_request = fcp_review_request::table
+            .inner_join(githubuser::table)
+            .filter(fcp_review_request::fk_proposal.eq(proposal.id))
+            .filter(githubuser::login.eq(username))
+            .select(fcp_review_request::all_columns)
+            .first::<FcpReviewRequest>(conn)
+            .optional()?;
+
+        if let Some(mut review_request) = review_request {
+            if review_request.reviewed {
+                return Ok(());
+            }
+
+            info!(
+                "{} recorded a review from {} on proposal {}",
+                author.login, username, proposal.id
+            );
+            review_request.reviewed = true;
+            review_request.recorded_by = Some(author.login.clone());
+            diesel::update(fcp_review_request::table.find(review_request.id))
+                .set(&review_request)
+                .execute(conn)?;
+
+            refresh_status_comment(issue, &proposal)?;
+        } else {
+            info!(
+                "{} tried to record a review from {}, who isn't a reviewer on proposal {}",
+                author.login, username, proposal.id
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Regenerate a proposal's status comment and store the posted body in the database.
+fn refresh_status_comment(issue: &Issue, proposal: &FcpProposal) -> DashResult<()> {
+    let conn = &*DB_POOL.get()?;
+
+    let initiator = githubuser::table
+        .find(proposal.fk_initiator)
+        .first::<GitHubUser>(conn)?;
+    let reviews = list_review_requests(proposal.id)?;
+    let concerns = list_concerns_with_authors(proposal.id)?;
+    let disposition = FcpDisposition::from_str(&proposal.disposition)?;
+    let fcp_end = fcp_end(proposal, CONFIG.fcp_durations_for(&issue.repository));
+
+    let teams = team_pings(&SETUP.read().unwrap(), issue);
+    let status_comment = RfcBotComment::new(
+        issue,
+        CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition,
+            reviewers: &reviews,
+            concerns: &concerns,
+            teams: &teams,
+            min_distinct_reviewers: CONFIG.min_distinct_reviewers,
+            fcp_end,
+            state: proposal.state().ok().and_then(shown_state),
+        },
+    );
+
+    let previous_comment = issuecomment::table
+        .find(proposal.fk_bot_tracking_comment)
+        .first::<IssueComment>(conn)?;
+    let status_comment = status_comment.preserving_edits_to(&previous_comment.body);
+
+    if let Some(posted) = post_status_comment(&status_comment, &previous_comment)?.comment() {
+        let posted = posted.with_repo(&issue.repository)?;
+        diesel::update(issuecomment::table.find(posted.id))
+            .set(&posted)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn process_new_concern(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+    concern_name: &str,
+    directed_at: Option<&str>,
+    concern_description: Option<&str>,
+) -> DashResult<()> {
+    raise_concern(
+        author,
+        issue,
+        comment,
+        concern_name,
+        directed_at,
+        concern_description,
+        CONFIG.max_concerns,
+    )
+}
+
+/// Add a concern to the issue's proposal, unless it already has `max_concerns` unresolved ones.
+fn raise_concern(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+    concern_name: &str,
+    directed_at: Option<&str>,
+    concern_description: Option<&str>,
+    max_concerns: Option<usize>,
+) -> DashResult<()> {
+    let concern_name = &*normalize_concern_name(concern_name)?;
+
+    if let Some(mut proposal) = existing_proposal(issue)? {
+        let directed_at = match directed_at {
+            Some(login) => directed_reviewer(&proposal, login)?,
+            None => None,
+        };
+
+        // check for an existing concern, one which was resolved can be raised again
+        use crate::domain::schema::fcp_concern::dsl::*;
+        use crate::domain::schema::fcp_proposal::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        let existing_concern = fcp_concern
+            .filter(fk_proposal.eq(proposal.id))
+            .filter(lower(name).eq(concern_name.to_lowercase()))
+            .filter(fk_resolved_comment.is_null())
+            .first::<FcpConcern>(conn)
+            .optional()?;
+
+        if existing_concern.is_none() {
+            if let Some(max) = max_concerns {
+                let unresolved = fcp_concern
+                    .filter(fk_proposal.eq(proposal.id))
+                    .filter(fk_resolved_comment.is_null())
+                    .count()
+                    .get_result::<i64>(conn)?;
+                if unresolved as usize >= max {
+                    info!(
+                        "proposal {} already has {} unresolved concerns, refusing another",
+                        proposal.id, unresolved
+                    );
+                    let refusal = CommentType::ConcernLimitReached { author, max };
+                    RfcBotComment::new(issue, refusal).post(None)?;
+                    return Ok(());
+                }
+            }
+
+            // if not exists, create new concern with this author as creator
+            let new_concern = NewFcpConcern {
+                fk_proposal: proposal.id,
+                fk_initiator: author.id,
+                fk_resolved_comment: None,
+                name: concern_name,
+                fk_initiating_comment: comment.id,
+                fk_directed_at: directed_at,
+                description: concern_description,
+                fk_overridden_by: None,
+            };
+            diesel::insert_into(fcp_concern)
+                .values(&new_concern)
+                .execute(conn)?;
+
+            // Take us out of FCP and back into PFCP if need be:
+            if proposal.state()? == ProposalState::InFcp {
+                // Update DB: FCP is not started anymore.
+                proposal.transition(ProposalState::Pending)?;
+                proposal.fcp_start = None;
+                proposal.fcp_end_override = None;
+                let update = diesel::update(fcp_proposal.find(proposal.id))
+                    .set(&proposal)
+                    .execute(conn);
+                ok_or!(update, why => {
+                    error!("Unable to mark FCP {} as unstarted: {:?}", proposal.id, why);
+                    return Ok(());
+                });
+                notify_transition(issue, &proposal);
+
+                // Update labels:
+                let _ = issue.add_label(Label::PFCP);
+                issue.remove_label(Label::FCP);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the id of the reviewer a concern is directed at, so long as they've been asked to
+/// review the proposal.
+fn directed_reviewer(proposal: &FcpProposal, login: &str) -> DashResult<Option<i32>> {
+    let conn = &*DB_POOL.get()?;
+
+    let reviewer = fcp_review_request::table
+        .inner_join(githubuser::table)
+        .filter(fcp_review_request::fk_proposal.eq(proposal.id))
+        .filter(githubuser::login.eq(login))
+        .select(githubuser::id)
+        .first::<i32>(conn)
+        .optional()?;
+
+    if reviewer.is_none() {
+        warn!(
+            "concern directed at {}, who isn't a reviewer on proposal {}",
+            login, proposal.id
+        );
+    }
+
+    Ok(reviewer)
+}
+
+/// Collapse runs of whitespace in a concern name so that names which only differ in spacing
+/// match. Concern names are also compared case-insensitively.
+fn normalize_concern_name(concern_name: &str) -> DashResult<String> {
+    let normalized = concern_name.split_whitespace().join(" ");
+    if normalized.is_empty() {
+        throw!(DashError::Misc(Some("empty concern name".to_string())));
+    }
+    Ok(normalized)
+}
+
+/// Resolves one of the author's concerns. Admins (`RFCBOT_ADMINS`) may resolve anyone's concern,
+/// e.g. when its author is away, which is recorded on the concern.
+fn process_resolve_concern(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+    concern_name: &str,
+    is_admin: bool,
+) -> DashResult<()> {
+    debug!("Command is to resolve a concern ({}).", concern_name);
+    let concern_name = &*normalize_concern_name(concern_name)?;
+
+    if let Some(proposal) = existing_proposal(issue)? {
+        // check for existing concern
+        use crate::domain::schema::fcp_concern::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        let existing_concern = fcp_concern
+            .filter(fk_proposal.eq(proposal.id))
+            .filter(lower(name).eq(concern_name.to_lowercase()))
+            .filter(fk_resolved_comment.is_null())
+            .first::<FcpConcern>(conn)
+            .optional()?
+            .filter(|concern| concern.fk_initiator == author.id || is_admin);
+
+        if let Some(mut concern) = existing_concern {
+            // mark concern as resolved by adding resolved_comment
+            debug!("Found a matching concern ({})", concern_name);
+            concern.fk_resolved_comment = Some(comment.id);
+            if concern.fk_initiator != author.id {
+                info!(
+                    "{} is resolving concern {} on behalf of its author",
+                    author.login, concern.id
+                );
+                concern.fk_overridden_by = Some(author.id);
+            }
+            diesel::update(fcp_concern.find(concern.id))
+                .set(&concern)
+                .execute(conn)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn process_resolve_all(
+    author: &GitHubUser,
+    issue: &Issue,
+    comment: &IssueComment,
+) -> DashResult<()> {
+    debug!("Command is to resolve all of {}'s concerns.", author.login);
+
+    if let Some(proposal) = existing_proposal(issue)? {
+        use crate::domain::schema::fcp_concern::dsl::*;
+        let conn = &*DB_POOL.get()?;
+
+        diesel::update(
+            fcp_concern
+                .filter(fk_proposal.eq(proposal.id))
+                .filter(fk_initiator.eq(author.id))
+                .filter(fk_resolved_comment.is_null()),
+        )
+        .set(fk_resolved_comment.eq(comment.id))
+        .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn process_feedback_request(author: &GitHubUser, issue: &Issue, username: &str) -> DashResult<()> {
+    use crate::domain::schema::rfc_feedback_request::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    // we'll just assume that this user exists...it's very unlikely that someone
+    // will request feedback from a user who's *never* commented or committed
+    // on/to a rust-lang* repo
+    let requested_user = githubuser::table
+        .filter(githubuser::login.eq(username))
+        .first::<GitHubUser>(conn)?;
+
+    // check for existing feedback request
+    let existing_request = rfc_feedback_request
+        .filter(fk_requested.eq(requested_user.id))
+        .filter(fk_issue.eq(issue.id))
+        .first::<FeedbackRequest>(conn)
+        .optional()?;
+
+    if existing_request.is_none() {
+        // create feedback request
+        let new_request = NewFeedbackRequest {
+            fk_initiator: author.id,
+            fk_requested: requested_user.id,
+            fk_issue: issue.id,
+            fk_feedback_comment: None,
+        };
+        diesel::insert_into(rfc_feedback_request)
+            .values(&new_request)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// A command parsed from a comment, and the comment rfcbot would post in response to it (if
+/// it posts one immediately).
+#[derive(Debug, Serialize)]
+pub struct CommandPreview {
+    pub command: String,
+    pub comment: Option<String>,
+}
+
+/// Works out what rfcbot would do with a comment on `issue`, without touching the database or
+/// GitHub. Reviewers are taken straight from the team configuration.
+pub fn preview_command(
+    setup: &RfcbotConfig,
+    config: &Config,
+    issue: &Issue,
+    author: &GitHubUser,
+    body: &str,
+) -> Vec<CommandPreview> {
+    let members = team_member_logins(setup, |label| team_responsible(setup, issue, label))
+        .into_iter()
+        .enumerate()
+        .map(|(id, login)| GitHubUser {
+            // made up ids, except that the author should be recognisable
+            id: if login == author.login {
+                author.id
+            } else {
+                -(id as i32) - 1
+            },
+            login,
+        })
+        .collect::<Vec<_>>();
+    let auto_review = setup.initiator_auto_review(|label| team_responsible(setup, issue, label));
+    let teams = team_pings(setup, issue);
+    let reviewers = members
+        .into_iter()
+        .map(|member| {
+            let request = FcpReviewRequest {
+                id: 0,
+                fk_proposal: 0,
+                fk_reviewer: member.id,
+                reviewed: auto_review && member.login == author.login,
+                waived: false,
+                recorded_by: None,
+                note: None,
+            };
+            (member, request)
+        })
+        .collect::<Vec<_>>();
+
+    let body = normalize_command_text(body);
+    RfcBotCommand::from_str_all(setup, &body)
+        .map(|command| {
+            let comment_type = match command {
+                RfcBotCommand::FcpPropose(disposition) => Some(CommentType::FcpProposed {
+                    initiator: author,
+                    disposition,
+                    reviewers: &reviewers,
+                    concerns: &[],
+                    teams: &teams,
+                    min_distinct_reviewers: config.min_distinct_reviewers,
+                    fcp_end: None,
+                    state: Some(ProposalState::Pending).filter(|_| config.status_emoji),
+                }),
+                RfcBotCommand::FcpCancel => Some(CommentType::FcpProposalCancelled(author)),
+                RfcBotCommand::StartPoll {
+                    ref teams,
+                    question,
+                } => Some(CommentType::QuestionAsked {
+                    initiator: author,
+                    teams: teams.clone(),
+                    respondents: &[],
+                    question,
+                }),
+                RfcBotCommand::Version => Some(CommentType::Status {
+                    version: VERSION,
+                    commit: COMMIT.unwrap_or("unknown"),
+                    post_comments: config.post_comments,
+                    durations: config.fcp_durations_for(&issue.repository),
+                }),
+                RfcBotCommand::Echo { text, ref parsed } => Some(CommentType::ParseEcho {
+                    author,
+                    text,
+                    parsed: parsed.as_deref(),
+                }),
+                // the rest only update the existing status comment
+                _ => None,
+            };
+
+            CommandPreview {
+                command: format!("{:?}", command),
+                comment: comment_type.map(|c| {
+                    let body = RfcBotComment::new(issue, c).body;
+                    with_footer(&body, &config.repo_comment_footers, &issue.repository)
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Marks a pending feedback request as resolved on behalf of the requested user, who might
+/// have given their feedback somewhere other than the issue.
+fn process_feedback_done(issue: &Issue, comment: &IssueComment, username: &str) -> DashResult<()> {
+    use crate::domain::schema::rfc_feedback_request::dsl::*;
+    let conn = &*DB_POOL.get()?;
+
+    let requested_user = githubuser::table
+        .filter(githubuser::login.eq(username))
+        .first::<GitHubUser>(conn)?;
+
+    let pending_request = rfc_feedback_request
+        .filter(fk_requested.eq(requested_user.id))
+        .filter(fk_issue.eq(issue.id))
+        .filter(fk_feedback_comment.is_null())
+        .first::<FeedbackRequest>(conn)
+        .optional()?;
+
+    if let Some(mut request) = pending_request {
+        request.fk_feedback_comment = Some(comment.id);
+        diesel::update(rfc_feedback_request.find(request.id))
+            .set(&request)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+struct RfcBotComment<'a> {
+    issue: &'a Issue,
+    body: String,
+    comment_type: CommentType<'a>,
+}
+
+#[derive(Clone)]
+enum CommentType<'a> {
+    FcpProposed {
+        initiator: &'a GitHubUser,
+        disposition: FcpDisposition,
+        reviewers: &'a [(GitHubUser, FcpReviewRequest)],
+        concerns: &'a [(GitHubUser, FcpConcern)],
+        /// The pings of the teams reviewing it, named in the comment if there are any.
+        teams: &'a [String],
+        min_distinct_reviewers: usize,
+        fcp_end: Option<NaiveDateTime>,
+        /// Shown as an emoji at the top of the comment, if `STATUS_EMOJI` is set.
+        state: Option<ProposalState>,
+    },
+    FcpProposalCancelled(&'a GitHubUser),
+    CommandRefused {
+        author: &'a GitHubUser,
+        command: &'a str,
+    },
+    FcpLabelRequired {
+        author: &'a GitHubUser,
+        label: &'a str,
+    },
+    ConcernLimitReached {
+        author: &'a GitHubUser,
+        max: usize,
+    },
+    /// Pings reviewers once, as they're only linked from the status comment.
+    ReviewRequested {
+        status_comment_id: i32,
+        disposition: FcpDisposition,
+        reviewers: &'a [GitHubUser],
+    },
+    FcpProposalQueued {
+        author: &'a GitHubUser,
+        disposition: FcpDisposition,
+    },
+    FcpAllReviewedNoConcerns {
+        author: &'a GitHubUser,
+        status_comment_id: i32,
+        added_label: bool,
+        length_days: i64,
+    },
+    FcpAlmostReady {
+        blocker: LastBlocker<'a>,
+        status_comment_id: i32,
+    },
+    FcpWeekPassed {
+        author: &'a GitHubUser,
+        status_comment_id: i32,
+        added_label: bool,
+        disposition: FcpDisposition,
+    },
+    FcpDigest(&'a [(Issue, FcpProposal)]),
+    Status {
+        version: &'a str,
+        commit: &'a str,
+        post_comments: bool,
+        durations: &'a FcpDurations,
+    },
+    QuestionAsked {
+        initiator: &'a GitHubUser,
+        respondents: &'a [(GitHubUser, PollResponseRequest)],
+        question: &'a str,
+        teams: BTreeSet<&'a str>,
+    },
+    ConcernList {
+        author: &'a GitHubUser,
+        concerns: &'a [(GitHubUser, FcpConcern)],
+    },
+    ReviewedRoster {
+        author: &'a GitHubUser,
+        reviewers: &'a [(GitHubUser, FcpReviewRequest)],
+    },
+    ProposalHistory {
+        author: &'a GitHubUser,
+        entries: &'a [HistoryEntry],
+        reviewers: &'a [(GitHubUser, FcpReviewRequest)],
+    },
+    ReviewsReset {
+        reviewers: &'a [GitHubUser],
+    },
+    FeedbackList {
+        author: &'a GitHubUser,
+        requests: &'a [(GitHubUser, GitHubUser, FeedbackRequest)],
+    },
+    ProposalId {
+        author: &'a GitHubUser,
+        proposal: Option<&'a FcpProposal>,
+    },
+    NextSteps {
+        author: &'a GitHubUser,
+        proposal: Option<&'a FcpProposal>,
+        reviewers: &'a [(GitHubUser, FcpReviewRequest)],
+        concerns: &'a [(GitHubUser, FcpConcern)],
+        fcp_end: Option<NaiveDateTime>,
+    },
+    ConflictingLinkedProposal {
+        author: &'a GitHubUser,
+        linked_issue: i32,
+        disposition: FcpDisposition,
+        linked_disposition: FcpDisposition,
+    },
+    ParseEcho {
+        author: &'a GitHubUser,
+        text: &'a str,
+        parsed: Option<&'a RfcBotCommand<'a>>,
+    },
+    NoReviewers(&'a GitHubUser),
+    MergeFailed,
+    FcpForceClosed,
+    /// The status of a proposal on the `source` issue, shown on another issue.
+    FcpMirror {
+        source: &'a Issue,
+        proposal: &'a FcpProposal,
+        reviewers: &'a [(GitHubUser, FcpReviewRequest)],
+        concerns: &'a [(GitHubUser, FcpConcern)],
+    },
+}
+
+impl<'a> CommentType<'a> {
+    /// The key for this kind of comment in `COMMENT_TEMPLATES_PATH`.
+    fn template_name(&self) -> &'static str {
+        match self {
+            CommentType::FcpProposed { .. } => "fcp_proposed",
+            CommentType::FcpProposalCancelled(_) => "fcp_proposal_cancelled",
+            CommentType::CommandRefused { .. } => "command_refused",
+            CommentType::FcpLabelRequired { .. } => "fcp_label_required",
+            CommentType::ConcernLimitReached { .. } => "concern_limit_reached",
+            CommentType::ReviewRequested { .. } => "review_requested",
+            CommentType::FcpProposalQueued { .. } => "fcp_proposal_queued",
+            CommentType::FcpAllReviewedNoConcerns { .. } => "fcp_all_reviewed_no_concerns",
+            CommentType::FcpAlmostReady { .. } => "fcp_almost_ready",
+            CommentType::FcpWeekPassed { .. } => "fcp_week_passed",
+            CommentType::FcpDigest(_) => "fcp_digest",
+            CommentType::Status { .. } => "status",
+            CommentType::QuestionAsked { .. } => "question_asked",
+            CommentType::ConcernList { .. } => "concern_list",
+            CommentType::ReviewedRoster { .. } => "reviewed_roster",
+            CommentType::ProposalHistory { .. } => "proposal_history",
+            CommentType::ReviewsReset { .. } => "reviews_reset",
+            CommentType::FeedbackList { .. } => "feedback_list",
+            CommentType::ProposalId { .. } => "proposal_id",
+            CommentType::NextSteps { .. } => "next_steps",
+            CommentType::ConflictingLinkedProposal { .. } => "conflicting_linked_proposal",
+            CommentType::ParseEcho { .. } => "parse_echo",
+            CommentType::NoReviewers(_) => "no_reviewers",
+            CommentType::MergeFailed => "merge_failed",
+            CommentType::FcpForceClosed => "fcp_force_closed",
+            CommentType::FcpMirror { .. } => "fcp_mirror",
+        }
+    }
+
+    /// The placeholders a template for this kind of comment can use, besides `{default}`.
+    fn template_fields(&self) -> BTreeMap<&'static str, String> {
+        let mut fields = BTreeMap::new();
+        match *self {
+            CommentType::FcpProposed {
+                initiator,
+                disposition,
+                reviewers,
+                ..
+            } => {
+                let mut boxes = String::new();
+                format_review_boxes(&mut boxes, reviewers);
+                fields.insert("initiator", initiator.login.clone());
+                fields.insert("disposition", disposition.repr().to_string());
+                fields.insert("reviewers", boxes);
+            }
+            CommentType::FcpProposalCancelled(author)
+            | CommentType::NoReviewers(author)
+            | CommentType::CommandRefused { author, .. }
+            | CommentType::FcpLabelRequired { author, .. }
+            | CommentType::ConcernLimitReached { author, .. }
+            | CommentType::FcpProposalQueued { author, .. }
+            | CommentType::FcpAllReviewedNoConcerns { author, .. }
+            | CommentType::FcpWeekPassed { author, .. }
+            | CommentType::ConcernList { author, .. }
+            | CommentType::ReviewedRoster { author, .. }
+            | CommentType::ProposalHistory { author, .. }
+            | CommentType::FeedbackList { author, .. }
+            | CommentType::ProposalId { author, .. }
+            | CommentType::NextSteps { author, .. }
+            | CommentType::ConflictingLinkedProposal { author, .. }
+            | CommentType::ParseEcho { author, .. } => {
+                fields.insert("author", author.login.clone());
+            }
+            _ => {}
+        }
+        fields
+    }
+}
+
+/// Fills each `{name}` in `template` with its field, leaving unknown placeholders alone.
+fn fill_template(template: &str, fields: &BTreeMap<&str, String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let value = rest
+            .find('}')
+            .and_then(|close| fields.get(&rest[1..close]).map(|v| (close, v)));
+        match value {
+            Some((close, value)) => {
+                filled.push_str(value);
+                rest = &rest[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+impl<'a> RfcBotComment<'a> {
+    fn new(issue: &'a Issue, comment_type: CommentType<'a>) -> RfcBotComment<'a> {
+        let mut body = Self::format(issue, &comment_type);
+
+        // status comments are regenerated as things change, so mark the part we own
+        match comment_type {
+            CommentType::FcpProposed { .. } | CommentType::QuestionAsked { .. } => {
+                body = format!("{}\n{}\n{}", MANAGED_START, body, MANAGED_END);
+            }
+            _ => {}
+        }
+
+        RfcBotComment {
+            issue,
+            body,
+            comment_type,
+        }
+    }
+
+    /// Keep any text that was added to the previous version of this comment outside of the
+    /// region managed by rfcbot.
+    fn preserving_edits_to(mut self, previous: &str) -> Self {
+        self.body = splice_managed_region(previous, &self.body);
+        self
+    }
+
+    fn couldnt_add_label<'b>(msg: &mut String, author: &'b GitHubUser, label: Label) {
+        msg.push_str("\n\n*psst @");
+        msg.push_str(&author.login);
+        msg.push_str(", I wasn't able to add the `");
+        msg.push_str(label.as_str());
+        msg.push_str("` label, please do so.*");
+    }
+
+    fn format(issue: &Issue, comment_type: &CommentType<'_>) -> String {
+        match *comment_type {
+            CommentType::QuestionAsked {
+                initiator,
+                respondents,
+                question,
+                ref teams,
+            } => {
+                let mut msg = String::from("Team member @");
+                msg.push_str(&initiator.login);
+                msg.push_str(" has asked teams: ");
+                msg.extend(teams.iter().cloned().intersperse(", "));
+                msg.push_str(", for consensus on: \n > ");
+                msg.push_str(question);
+                msg.push_str("\n\n");
+                format_ticky_boxes(
+                    &mut msg,
+                    respondents.iter().map(|(m, rr)| (m, rr.responded)),
+                );
+                msg
+            }
+
+            CommentType::FcpProposed {
+                initiator,
+                disposition,
+                reviewers,
+                concerns,
+                teams,
+                min_distinct_reviewers,
+                fcp_end,
+                state,
+            } => {
+                let mut msg = String::new();
+                if let Some(state) = state {
+                    msg.push_str(state_emoji(state));
+                    msg.push(' ');
+                }
+                msg.push_str("Team member ");
+                msg.push_str(&profile_link(&initiator.login));
+                msg.push_str(" has proposed to ");
+                msg.push_str(disposition.repr());
+                if teams.is_empty() {
+                    msg.push_str(" this. The next step is review by the rest of the tagged ");
+                    msg.push_str("team members:\n\n");
+                } else {
+                    msg.push_str(" this. The next step is review by the rest of the team ");
+                    msg.push_str("members of ");
+                    let links = teams.iter().map(|ping| team_link(ping));
+                    msg.push_str(&links.collect::<Vec<_>>().join(", "));
+                    msg.push_str(":\n\n");
+                }
+
+                format_review_boxes(&mut msg, reviewers);
+
+                let missing =
+                    missing_distinct_reviews(initiator, reviewers, min_distinct_reviewers);
+                if missing > 0 {
+                    msg.push_str("\nWaiting on ");
+                    msg.push_str(&missing.to_string());
+                    msg.push_str(if missing == 1 {
+                        " more review"
+                    } else {
+                        " more reviews"
+                    });
+                    msg.push_str(" from someone other than the proposer.\n");
+                }
+
+                if concerns.is_empty() {
+                    msg.push_str("\nNo concerns currently listed.\n");
+                } else {
+                    msg.push_str("\nConcerns:\n\n");
+                }
+
+                for &(_, ref concern) in ordered_concerns(concerns) {
+                    if let Some(resolved_comment_id) = concern.fk_resolved_comment {
+                        msg.push_str("* ~~");
+                        msg.push_str(&concern.name);
+                        msg.push_str("~~ resolved by ");
+                        Self::add_comment_url(issue, &mut msg, resolved_comment_id);
+                        msg.push_str("\n");
+                    } else {
+                        msg.push_str("* ");
+                        msg.push_str(&concern.name);
+                        msg.push_str(" (");
+                        Self::add_comment_url(issue, &mut msg, concern.fk_initiating_comment);
+                        msg.push_str(")");
+
+                        let directed_at = reviewers
+                            .iter()
+                            .find(|&&(ref m, _)| Some(m.id) == concern.fk_directed_at);
+                        if let Some(&(ref reviewer, _)) = directed_at {
+                            msg.push_str(" (for ");
+                            msg.push_str(&profile_link(&reviewer.login));
+                            msg.push_str(")");
+                        }
+
+                        msg.push_str("\n");
+                        if let Some(ref description) = concern.description {
+                            push_concern_description(&mut msg, description);
+                        }
+                    }
+                }
+
+                if let Some(end) = fcp_end {
+                    let days = business_days_until(end, Utc::now().naive_utc());
+                    msg.push_str(&format!(
+                        "\n:bell: The final comment period ends in {} business day{} (on {}).\n",
+                        days,
+                        if days == 1 { "" } else { "s" },
+                        end.format("%Y-%m-%d")
+                    ));
+                }
+
+                msg.push_str("\nOnce a majority of reviewers approve (and at most 2 approvals are outstanding), ");
+                msg.push_str("this will enter its final comment period. ");
+                msg.push_str("If you spot a major issue that hasn't been raised ");
+                msg.push_str("at any point in this process, please speak up!\n");
+
+                msg.push_str("\nSee [this document](");
+                msg.push_str("https://github.com/rust-lang/rfcbot-rs/blob/master/README.md");
+                msg.push_str(") for info about what commands tagged team members can give me.");
+
+                msg
+            }
+
+            CommentType::FcpDigest(running) => {
+                format_fcp_digest(running, &CONFIG, Utc::now().naive_utc())
+            }
+
+            CommentType::FcpProposalCancelled(initiator) => {
+                format!("@{} proposal cancelled.", initiator.login)
+            }
+
+            CommentType::CommandRefused { author, command } => format!(
+                "Sorry @{}, only rfcbot's administrators can use the `{}` command here.",
+                author.login, command
+            ),
+
+            CommentType::FcpLabelRequired { author, label } => format!(
+                "Sorry @{}, final comment periods can only be proposed here on issues labelled \
+                 `{}`. Please add the label and propose again.",
+                author.login, label
+            ),
+
+            CommentType::ReviewRequested {
+                status_comment_id,
+                disposition,
+                reviewers,
+            } => {
+                let mut msg = String::new();
+                let pings = reviewers.iter().map(|r| format!("@{}", r.login));
+                msg.push_str(&pings.collect::<Vec<_>>().join(", "));
+                msg.push_str(": your review is requested on this proposal to ");
+                msg.push_str(disposition.repr());
+                msg.push_str(". Please check your box in the [status comment](");
+                Self::add_comment_url(issue, &mut msg, status_comment_id);
+                msg.push_str(") or use `@rfcbot reviewed` once you have.");
+                msg
+            }
+
+            CommentType::ConcernLimitReached { author, max } => format!(
+                "Sorry @{}, this proposal already has {} unresolved concerns, which is as many as \
+                 rfcbot tracks. Please resolve some of them before raising another.",
+                author.login, max
+            ),
+
+            CommentType::ConcernList { author, concerns } => {
+                let mut msg = format!("@{}, ", author.login);
+
+                if concerns.is_empty() {
+                    msg.push_str("no concerns have been raised on this proposal.");
+                    return msg;
+                }
+
+                msg.push_str("these are the concerns on this proposal:\n\n");
+                for (initiator, concern) in ordered_concerns(concerns) {
+                    if let Some(resolved_comment_id) = concern.fk_resolved_comment {
+                        msg.push_str("* ~~");
+                        msg.push_str(&concern.name);
+                        msg.push_str("~~ raised by @");
+                        msg.push_str(&initiator.login);
+                        msg.push_str(", resolved by ");
+                        Self::add_comment_url(issue, &mut msg, resolved_comment_id);
+                    } else {
+                        msg.push_str("* ");
+                        msg.push_str(&concern.name);
+                        msg.push_str(" raised by @");
+                        msg.push_str(&initiator.login);
+                        msg.push_str(" (");
+                        Self::add_comment_url(issue, &mut msg, concern.fk_initiating_comment);
+                        msg.push_str(")");
+                    }
+                    msg.push('\n');
+                }
+
+                msg
+            }
+
+            CommentType::FeedbackList { author, requests } => {
+                let mut msg = format!("@{}, ", author.login);
+
+                if requests.is_empty() {
+                    msg.push_str("nobody has been asked for feedback on this issue.");
+                    return msg;
+                }
+
+                msg.push_str("this is the feedback requested on this issue:\n\n");
+                for (initiator, requested, request) in requests {
+                    if let Some(feedback_comment_id) = request.fk_feedback_comment {
+                        msg.push_str("* ~~@");
+                        msg.push_str(&requested.login);
+                        msg.push_str("~~ asked by @");
+                        msg.push_str(&initiator.login);
+                        msg.push_str(", resolved by ");
+                        Self::add_comment_url(issue, &mut msg, feedback_comment_id);
+                    } else {
+                        msg.push_str("* @");
+                        msg.push_str(&requested.login);
+                        msg.push_str(" asked by @");
+                        msg.push_str(&initiator.login);
+                        msg.push_str(", still waiting");
+                    }
+                    msg.push('\n');
+                }
+
+                msg
+            }
+
+            CommentType::ReviewedRoster { author, reviewers } => {
+                let mut msg = format!("@{}, ", author.login);
+
+                let reviewed = reviewers
+                    .iter()
+                    .filter(|&&(_, ref review)| review.reviewed)
+                    .collect::<Vec<_>>();
+                if reviewed.is_empty() {
+                    msg.push_str("nobody has reviewed this proposal yet.");
+                    return msg;
+                }
+
+                msg.push_str("this proposal has been reviewed by:\n\n");
+                for (member, _) in reviewed {
+                    msg.push_str("* @");
+                    msg.push_str(&member.login);
+                    msg.push('\n');
+                }
+
+                msg
+            }
+
+            CommentType::ProposalHistory {
+                author,
+                entries,
+                reviewers,
+            } => {
+                let mut msg = format!(
+                    "@{}, here's how this proposal has gone so far:\n\n",
+                    author.login
+                );
+                for entry in entries {
+                    msg.push_str(&format!(
+                        "* {}: {}\n",
+                        entry.at.format("%Y-%m-%d"),
+                        entry.event
+                    ));
+                }
+
+                // there's no record of when a box was checked, only whether it is
+                let reviewed = reviewers
+                    .iter()
+                    .filter(|&&(_, ref review)| review.reviewed)
+                    .collect::<Vec<_>>();
+                if reviewed.is_empty() {
+                    msg.push_str("\nNobody has reviewed it yet.");
+                } else {
+                    msg.push_str("\nReviewed by (review times aren't tracked):\n\n");
+                    for (member, review) in reviewed {
+                        msg.push_str(&format!("* @{}", member.login));
+                        if let Some(recorder) = &review.recorded_by {
+                            msg.push_str(&format!(" (recorded by @{})", recorder));
+                        }
+                        msg.push('\n');
+                    }
+                }
+                msg
+            }
+
+            CommentType::ReviewsReset { reviewers } => {
+                let mut msg = String::new();
+                for (i, reviewer) in reviewers.iter().enumerate() {
+                    if i > 0 {
+                        msg.push_str(", ");
+                    }
+                    msg.push('@');
+                    msg.push_str(&reviewer.login);
+                }
+                msg.push_str(
+                    ": new commits have been pushed, so your reviews have been reset. \
+                     Please check your box again once you've looked at the changes.",
+                );
+                msg
+            }
+
+            CommentType::ProposalId {
+                author,
+                proposal: Some(proposal),
+            } => {
+                let mut msg = format!(
+                    "@{}, this issue's proposal has id {}. Its tracking comment is ",
+                    author.login, proposal.id
+                );
+                Self::add_comment_url(issue, &mut msg, proposal.fk_bot_tracking_comment);
+                msg.push('.');
+                msg
+            }
+
+            CommentType::ProposalId {
+                author,
+                proposal: None,
+            } => format!("@{}, there's no proposal on this issue.", author.login),
+
+            CommentType::NextSteps {
+                author,
+                proposal: None,
+                ..
+            } => format!("@{}, there's no proposal on this issue.", author.login),
+
+            CommentType::NextSteps {
+                author,
+                proposal: Some(proposal),
+                reviewers,
+                concerns,
+                fcp_end,
+            } => {
+                let mut msg = format!("@{}, ", author.login);
+                let state = proposal.state();
+                match state {
+                    Ok(ProposalState::Pending) | Ok(ProposalState::OnHold) => {
+                        if let Ok(ProposalState::OnHold) = state {
+                            msg.push_str(
+                                "this proposal is on hold, so its final comment period won't \
+                                 start until it's taken off hold. ",
+                            );
+                        }
+                        format_pending_steps(&mut msg, reviewers, concerns);
+                    }
+                    Ok(ProposalState::InFcp) => {
+                        msg.push_str("this proposal is in its final comment period");
+                        if let Some(end) = fcp_end {
+                            msg.push_str(&format!(", which ends on {}", end.format("%Y-%m-%d")));
+                        }
+                        msg.push_str(". If you have concerns, please speak up before it ends!");
+                    }
+                    Ok(ProposalState::Closed) => msg.push_str(
+                        "this proposal's final comment period is complete, so there's nothing \
+                         left to do here.",
+                    ),
+                    Ok(ProposalState::Cancelled) | Err(_) => msg.push_str(
+                        "this proposal was cancelled, so there's nothing left to do here.",
+                    ),
+                }
+                msg
+            }
+
+            CommentType::MergeFailed => String::from(
+                "I tried to merge this now that the final comment period is over, but GitHub \
+                 wouldn't let me. It may have conflicts or failing checks, so it'll need merging \
+                 by hand.",
+            ),
+
+            CommentType::FcpForceClosed => String::from(
+                "An administrator has closed this proposal by hand, so I'll stop tracking it.",
+            ),
+
+            CommentType::FcpMirror {
+                source,
+                proposal,
+                reviewers,
+                concerns,
+            } => {
+                let mut msg = format!(
+                    "This mirrors the proposal to {} {}#{}. Its status is kept up to date here, \
+                     but reviews, concerns and other commands belong on [its status comment](",
+                    proposal.disposition, source.repository, source.number
+                );
+                Self::add_comment_url(source, &mut msg, proposal.fk_bot_tracking_comment);
+                msg.push_str(").\n\n* Status: ");
+                msg.push_str(proposal.state().map(state_description).unwrap_or("unknown"));
+
+                let done = reviewers
+                    .iter()
+                    .filter(|(_, r)| r.reviewed || r.waived)
+                    .count();
+                msg.push_str(&format!(
+                    "\n* Reviews: {} of {} done\n* Unresolved concerns: ",
+                    done,
+                    reviewers.len()
+                ));
+
+                let unresolved = ordered_concerns(concerns)
+                    .into_iter()
+                    .filter(|(_, c)| c.fk_resolved_comment.is_none())
+                    .map(|(_, c)| format!("`{}`", c.name))
+                    .collect::<Vec<_>>();
+                if unresolved.is_empty() {
+                    msg.push_str("none");
+                } else {
+                    msg.push_str(&unresolved.join(", "));
+                }
+                msg.push('\n');
+                msg
+            }
+
+            CommentType::NoReviewers(author) => format!(
+                "@{}, no reviewers could be found for this proposal, so its final comment period \
+                 can't start. Please check that the right team labels are applied and that the \
+                 teams have members.",
+                author.login
+            ),
+
+            CommentType::ConflictingLinkedProposal {
+                author,
+                linked_issue,
+                disposition,
+                linked_disposition,
+            } => format!(
+                "@{}, heads up: the tracking issue #{} already has a proposal to {}, \
+                 which conflicts with this proposal to {}.",
+                author.login,
+                linked_issue,
+                linked_disposition.repr(),
+                disposition.repr()
+            ),
+
+            CommentType::ParseEcho {
+                author,
+                text,
+                parsed: Some(command),
+            } => format!(
+                "@{}, I parsed `{}` as:\n\n```\n{:?}\n```",
+                author.login, text, command
+            ),
+
+            CommentType::ParseEcho {
+                author,
+                text,
+                parsed: None,
+            } => format!(
+                "@{}, I could not parse `{}` as a command.",
+                author.login, text
+            ),
+
+            CommentType::Status {
+                version,
+                commit,
+                post_comments,
+                durations,
+            } => format!(
+                "rfcbot version {} (commit {})\n\n\
+                 * posting comments: {}\n\
+                 * FCP durations: merge {} days, close {} days, postpone {} days",
+                version,
+                commit,
+                if post_comments { "on" } else { "off" },
+                durations.merge,
+                durations.close,
+                durations.postpone
+            ),
+
+            CommentType::FcpProposalQueued {
+                author,
+                disposition,
+            } => format!(
+                "@{} has proposed to {} this, but isn't a member of the tagged teams. \
+                 A team member must confirm the proposal with `@rfcbot confirm`.",
+                author.login,
+                disposition.repr()
+            ),
+
+            CommentType::FcpAllReviewedNoConcerns {
+                author,
+                status_comment_id,
+                added_label,
+                length_days,
+            } => {
+                let mut msg = String::new();
+
+                msg.push_str(FCP_START_ANNOUNCEMENT);
+                msg.push_str("as per the [review above](");
+                Self::add_comment_url(issue, &mut msg, status_comment_id);
+                msg.push_str("). :bell:");
+
+                msg.push_str("\n\nThe final comment period will last for **");
+                msg.push_str(&length_days.to_string());
+                msg.push_str(" days**.");
+
+                if !added_label {
+                    Self::couldnt_add_label(&mut msg, author, Label::FCP);
+                }
+
+                msg
+            }
+
+            CommentType::FcpAlmostReady {
+                blocker,
+                status_comment_id,
+            } => {
+                let mut msg = String::new();
+
+                match blocker {
+                    LastBlocker::Review(reviewer) => {
+                        msg.push_str("@");
+                        msg.push_str(&reviewer.login);
+                        msg.push_str(", yours is the last review needed");
+                    }
+                    LastBlocker::Concern { author, name } => {
+                        msg.push_str("@");
+                        msg.push_str(&author.login);
+                        msg.push_str(", your concern `");
+                        msg.push_str(name);
+                        msg.push_str("` is the last one outstanding");
+                    }
+                }
+
+                msg.push_str(" before this enters its final comment period, as per the ");
+                msg.push_str("[review above](");
+                Self::add_comment_url(issue, &mut msg, status_comment_id);
+                msg.push_str(").");
+
+                msg
+            }
+
+            CommentType::FcpWeekPassed {
+                author,
+                added_label,
+                status_comment_id,
+                disposition,
+            } => {
+                let mut msg = String::new();
+                msg.push_str("The final comment period, with a disposition to **");
+                msg.push_str(disposition.repr());
+                msg.push_str("**, as per the [review above](");
+                Self::add_comment_url(issue, &mut msg, status_comment_id);
+                msg.push_str("), is now **complete**.");
+
+                msg.push_str(
+                    "\n\nAs the automated representative of the governance process, \
+                     I would like to thank the author for their work and everyone else \
+                     who contributed.",
+                );
+
+                match disposition {
+                    FcpDisposition::Merge => {
+                        msg.push_str("\n\nThis will be merged soon.");
+                    }
+                    FcpDisposition::Close if can_ffcp_close(issue) => {
+                        msg.push_str("\n\nThis is now closed.");
+                    }
+                    FcpDisposition::Postpone if can_ffcp_postpone(issue) => {
+                        msg.push_str("\n\nThis is now postponed.");
+                    }
+                    _ => {}
+                }
+
+                if !added_label {
+                    Self::couldnt_add_label(&mut msg, author, Label::FFCP);
+                }
+
+                msg
+            }
+        }
+    }
+
+    fn add_comment_url(issue: &Issue, msg: &mut String, comment_id: i32) {
+        if comment_id == body_comment(issue).id {
+            // there's no anchor for the body itself
+            msg.push_str(&format!(
+                "https://github.com/{repo}/{typ}/{number}",
+                repo = issue.repository,
+                typ = if issue.is_pull_request {
+                    "pull"
+                } else {
+                    "issues"
+                },
+                number = issue.number,
+            ));
+            return;
+        }
+
+        let url = format!(
+            "https://github.com/{repo}/{typ}/{number}#issuecomment-{id}",
+            repo = issue.repository,
+            typ = if issue.is_pull_request {
+                "pull"
+            } else {
+                "issues"
+            },
+            number = issue.number,
+            id = comment_id,
+        );
+        msg.push_str(&url);
+    }
+
+    fn maybe_add_pfcp_label(&self) {
+        if let CommentType::FcpProposed { disposition, .. } = self.comment_type {
+            let _ = self.issue.add_label(Label::PFCP);
+            let _ = self.issue.add_label(disposition.label());
+        }
+    }
+
+    /// The body with the deployment's template for this kind of comment, if it has one, in place
+    /// of the built-in wording.
+    fn templated(&self, templates: &BTreeMap<String, String>) -> String {
+        let template = match templates.get(self.comment_type.template_name()) {
+            Some(template) => template,
+            None => return self.body.clone(),
+        };
+
+        let mut fields = self.comment_type.template_fields();
+        fields.insert("default", Self::format(self.issue, &self.comment_type));
+        let filled = fill_template(template, &fields);
+
+        // text added outside of the managed region of a status comment is kept
+        if managed_bounds(&self.body).is_some() {
+            let managed = format!("{}\n{}\n{}", MANAGED_START, filled, MANAGED_END);
+            splice_managed_region(&self.body, &managed)
+        } else {
+            filled
+        }
+    }
+
+    fn post(&self, existing_comment: Option<&IssueComment>) -> DashResult<PostOutcome> {
+        if posting_enabled() {
+            if may_comment_on(self.issue) {
+                let body = with_footer(
+                    &self.templated(&CONFIG.comment_templates),
+                    &CONFIG.repo_comment_footers,
+                    &self.issue.repository,
+                );
+                if existing_comment.map_or(false, |c| c.body != body) {
+                    self.maybe_add_pfcp_label();
+                }
+                github().post_comment(
+                    &self.issue.repository,
+                    self.issue.number,
+                    existing_comment.map(|c| (c.id, c.body.as_str())),
+                    &body,
+                )
+            } else {
+                info!(
+                    "Skipping comment to {}#{}, the issue is closed or muted",
+                    self.issue.repository, self.issue.number
+                );
+
+                throw!(DashError::Misc(None))
+            }
+        } else {
+            info!(
+                "Skipping comment to {}#{}, comment posts are disabled.",
+                self.issue.repository, self.issue.number
+            );
+            throw!(DashError::Misc(None))
+        }
+    }
+}
+
+/// Nests a concern's description under its entry in the list, without the block's own
+/// indentation. Every line is indented, so none of it can pass for a review checkbox.
+fn push_concern_description(msg: &mut String, description: &str) {
+    let margin = description
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    for line in description.lines() {
+        let line = line.get(margin..).unwrap_or_else(|| line.trim_start());
+        let line = line.trim_end().replace("<!--", "&lt;!--");
+        if !line.is_empty() {
+            msg.push_str("  ");
+            msg.push_str(&line);
+        }
+        msg.push('\n');
+    }
+}
+
+/// The emoji at the top of a status comment for a proposal in `state`.
+fn state_emoji(state: ProposalState) -> &'static str {
+    match state {
+        ProposalState::Pending => "\u{23f3}",
+        ProposalState::InFcp => "\u{1f514}",
+        ProposalState::Closed => "\u{2705}",
+        ProposalState::Cancelled => "\u{274c}",
+        ProposalState::OnHold => "\u{23f8}\u{fe0f}",
+    }
+}
+
+/// How a mirror describes where its proposal is.
+fn state_description(state: ProposalState) -> &'static str {
+    match state {
+        ProposalState::Pending => "waiting on reviews or concerns",
+        ProposalState::InFcp => "in its final comment period",
+        ProposalState::Closed => "its final comment period is over",
+        ProposalState::Cancelled => "cancelled",
+        ProposalState::OnHold => "on hold",
+    }
+}
+
+/// The state to show in a status comment, if the deployment shows them.
+fn shown_state(state: ProposalState) -> Option<ProposalState> {
+    Some(state).filter(|_| CONFIG.status_emoji)
+}
+
+/// Closed issues are left alone, as are issues where rfcbot has been muted.
+fn may_comment_on(issue: &Issue) -> bool { issue.open && !issue.muted }
+
+const FCP_DIGEST_MARKER: &str = "<!-- rfcbot-fcp-digest -->";
+
+/// Render a markdown table of the running FCPs.
+fn format_fcp_digest(
+    running: &[(Issue, FcpProposal)],
+    config: &Config,
+    now: NaiveDateTime,
+) -> String {
+    let mut msg = String::from(FCP_DIGEST_MARKER);
+    msg.push_str("\n");
+
+    if running.is_empty() {
+        msg.push_str("No final comment periods are currently running.\n");
+        return msg;
+    }
+
+    msg.push_str("These are currently in their final comment period:\n\n");
+    msg.push_str("| Repository | Issue | Disposition | Days remaining |\n");
+    msg.push_str("|---|---|---|---|\n");
+
+    for (issue, proposal) in running {
+        let durations = config.fcp_durations_for(&issue.repository);
+        let length_days = FcpDisposition::from_str(&proposal.disposition)
+            .map(|disp| fcp_length_days(durations, disp))
+            .unwrap_or(durations.merge);
+        let days_remaining =
+            fcp_end(proposal, durations).map_or(length_days, |end| days_until(end, now));
+
+        msg.push_str(&format!(
+            "| {repo} | [#{number}](https://github.com/{repo}/{typ}/{number}) | {disp} | {days} |\n",
+            repo = issue.repository,
+            typ = if issue.is_pull_request { "pull" } else { "issues" },
+            number = issue.number,
+            disp = proposal.disposition,
+            days = days_remaining,
+        ));
+    }
+
+    msg
+}
+
+/// Whole days left until `end`, where partial days are rounded up since an FCP with hours
+/// left isn't done yet.
+fn days_until(end: NaiveDateTime, now: NaiveDateTime) -> i64 {
+    ((end - now).num_hours() + 23).div_euclid(24).max(0)
+}
+
+/// How many of the days counted by `days_until` are business days, i.e. end on a weekday.
+fn business_days_until(end: NaiveDateTime, now: NaiveDateTime) -> i64 {
+    (0..days_until(end, now))
+        .filter(|&day| (end - Duration::days(day)).weekday().number_from_monday() <= 5)
+        .count() as i64
+}
+
+const FOOTER_MARKER: &str = "<!-- rfcbot-footer -->";
+
+/// Replace any footer at the end of `body` with the one configured for `repo`, or with the
+/// default one if the repository doesn't have its own.
+fn with_footer(body: &str, footers: &BTreeMap<String, String>, repo: &str) -> String {
+    let body = match body.find(FOOTER_MARKER) {
+        Some(start) => body[..start].trim_end(),
+        None => body,
+    };
+
+    match footers
+        .get(repo)
+        .or_else(|| footers.get(DEFAULT_COMMENT_FOOTER))
+    {
+        Some(footer) => format!("{}\n\n{}\n{}", body, FOOTER_MARKER, footer),
+        None => body.to_string(),
+    }
+}
+
+const FCP_START_ANNOUNCEMENT: &str = ":bell: **This is now entering its final comment period**, ";
+
+const MANAGED_START: &str = "<!-- rfcbot-managed-start -->";
+const MANAGED_END: &str = "<!-- rfcbot-managed-end -->";
+
+/// Find the bounds of the rfcbot-managed region of a comment, including the markers.
+fn managed_bounds(body: &str) -> Option<(usize, usize)> {
+    let start = body.find(MANAGED_START)?;
+    let end = body[start..].find(MANAGED_END)? + start + MANAGED_END.len();
+    Some((start, end))
+}
+
+/// The part of a comment that rfcbot manages. Comments posted before the markers were
+/// introduced are managed in their entirety.
+fn managed_region(body: &str) -> &str {
+    match managed_bounds(body) {
+        Some((start, end)) => &body[start..end],
+        None => body,
+    }
+}
+
+/// Replace the managed region of `previous` with the (marked) `regenerated` text, leaving
+/// anything outside of the markers untouched.
+fn splice_managed_region(previous: &str, regenerated: &str) -> String {
+    match managed_bounds(previous) {
+        Some((start, end)) => format!("{}{}{}", &previous[..start], regenerated, &previous[end..]),
+        None => regenerated.to_string(),
+    }
+}
+
+/// The review checklist of a status comment. Reviewers are linked rather than mentioned, as the
+/// comment is edited (and occasionally reposted) so often; `ReviewRequested` pings them instead.
+fn format_review_boxes(msg: &mut String, reviewers: &[(GitHubUser, FcpReviewRequest)]) {
+    for (member, review) in reviewers {
+        msg.push_str(if review.reviewed {
+            "* [x] "
+        } else if review.waived {
+            "* [~] "
+        } else {
+            "* [ ] "
+        });
+        msg.push_str(&profile_link(&member.login));
+        if review.waived && !review.reviewed {
+            msg.push_str(" (waived)");
+        }
+        if let (true, Some(note)) = (review.reviewed, &review.note) {
+            msg.push_str(": ");
+            msg.push_str(&escape_review_note(note));
+        }
+        if let (true, Some(recorder)) = (review.reviewed, &review.recorded_by) {
+            msg.push_str(" (recorded by ");
+            msg.push_str(&profile_link(recorder));
+            msg.push(')');
+        }
+        msg.push('\n');
+    }
+}
+
+/// The most of a review note shown in a status comment, in characters.
+const MAX_REVIEW_NOTE_CHARS: usize = 100;
+
+/// A reviewer's note made safe to show in a status comment: it can't mention anyone, open an HTML
+/// comment (like the managed region's markers) or run past the end of its line.
+fn escape_review_note(note: &str) -> String {
+    let mut chars = note.chars().map(|c| if c.is_control() { ' ' } else { c });
+    let mut escaped = String::new();
+    for c in chars.by_ref().take(MAX_REVIEW_NOTE_CHARS) {
+        match c {
+            // a zero width space after the `@` stops GitHub seeing a mention
+            '@' => escaped.push_str("@&#8203;"),
+            '<' => escaped.push_str("&lt;"),
+            c => escaped.push(c),
+        }
+    }
+    if chars.next().is_some() {
+        escaped.push('…');
+    }
+    escaped
+}
+
+/// e.g. `[octocat](https://github.com/octocat)`, which names someone without notifying them.
+fn profile_link(login: &str) -> String { format!("[{0}](https://github.com/{0})", login) }
+
+/// e.g. `[rust-lang/lang](https://github.com/orgs/rust-lang/teams/lang)` for the team pinged as
+/// `rust-lang/lang`, which names the team without notifying its members.
+fn team_link(ping: &str) -> String {
+    let mut parts = ping.splitn(2, '/');
+    match (parts.next(), parts.next()) {
+        (Some(org), Some(team)) => {
+            format!("[{}](https://github.com/orgs/{}/teams/{})", ping, org, team)
+        }
+        _ => ping.to_string(),
+    }
+}
+
+/// Lists the reviews and concerns a pending proposal is waiting on.
+fn format_pending_steps(
+    msg: &mut String,
+    reviewers: &[(GitHubUser, FcpReviewRequest)],
+    concerns: &[(GitHubUser, FcpConcern)],
+) {
+    let waiting_on = reviewers
+        .iter()
+        .filter(|&&(_, ref review)| !review.reviewed)
+        .map(|(member, _)| profile_link(&member.login))
+        .collect::<Vec<_>>();
+    let outstanding = concerns
+        .iter()
+        .filter(|&&(_, ref concern)| concern.fk_resolved_comment.is_none())
+        .map(|(_, concern)| format!("`{}`", concern.name))
+        .collect::<Vec<_>>();
+
+    if waiting_on.is_empty() && outstanding.is_empty() {
+        msg.push_str(
+            "every reviewer has checked their box and there are no outstanding concerns, so \
+             the final comment period should start soon.",
+        );
+        return;
+    }
+
+    msg.push_str("before its final comment period can start, this proposal is waiting on:\n\n");
+    if !waiting_on.is_empty() {
+        msg.push_str("* [ ] reviews from ");
+        msg.push_str(&waiting_on.join(", "));
+        msg.push('\n');
+    }
+    if !outstanding.is_empty() {
+        msg.push_str("* [ ] resolving the concerns ");
+        msg.push_str(&outstanding.join(", "));
+        msg.push('\n');
+    }
+}
+
+fn format_ticky_boxes<'a>(
+    msg: &mut String,
+    reviewers: impl Iterator<Item = (&'a GitHubUser, bool)>,
+) {
+    for (member, reviewed) in reviewers {
+        msg.push_str(if reviewed { "* [x] @" } else { "* [ ] @" });
+        msg.push_str(&member.login);
+        msg.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::github::models::{CommentFromJson, ReactionFromJson};
+    use diesel::pg::PgConnection;
+
+    fn user(id: i32) -> GitHubUser {
+        GitHubUser {
+            id,
+            login: format!("user{}", id),
+        }
+    }
+
+    fn review(id: i32, reviewed: bool) -> (GitHubUser, FcpReviewRequest) {
+        (
+            user(id),
+            FcpReviewRequest {
+                id,
+                fk_proposal: 1,
+                fk_reviewer: id,
+                reviewed,
+                waived: false,
+                recorded_by: None,
+                note: None,
+            },
+        )
+    }
+
+    fn issue() -> Issue {
+        let now = Utc::now().naive_utc();
+        Issue {
+            id: 1,
+            number: 42,
+            fk_milestone: None,
+            fk_user: 1,
+            fk_assignee: None,
+            open: true,
+            is_pull_request: false,
+            title: "An RFC".to_string(),
+            body: String::new(),
+            locked: false,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+            labels: vec!["T-avengers".to_string()],
+            repository: "rust-lang/rfcs".to_string(),
+            muted: false,
+        }
+    }
+
+    fn proposal(state: ProposalState) -> FcpProposal {
+        FcpProposal {
+            id: 1,
+            fk_issue: 1,
+            fk_initiator: 1,
+            fk_initiating_comment: 1,
+            disposition: FcpDisposition::Merge.repr().to_string(),
+            fk_bot_tracking_comment: 1,
+            fcp_start: None,
+            snooze_until: None,
+            state: state.repr().to_string(),
+            almost_ready_notified: false,
+            fcp_end_override: None,
+            tracking_label: None,
+        }
+    }
+
+    fn status_comment(body: String) -> IssueComment {
+        let now = Utc::now().naive_utc();
+        IssueComment {
+            id: 1,
+            fk_issue: 1,
+            fk_user: 1,
+            body,
+            created_at: now,
+            updated_at: now,
+            repository: "rust-lang/rfcs".to_string(),
+        }
+    }
+
+    #[test]
+    fn queued_proposal_asks_for_confirmation() {
+        let issue = issue();
+        let author = user(7);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposalQueued {
+                author: &author,
+                disposition: FcpDisposition::Merge,
+            },
+        );
+
+        assert!(comment
+            .body
+            .starts_with("@user7 has proposed to merge this"));
+        assert!(comment.body.contains("`@rfcbot confirm`"));
+    }
+
+    #[test]
+    fn waived_reviews_count_as_complete() {
+        let mut reviews = vec![review(1, true), review(2, false), review(3, false)];
+        assert!(!fcp_can_start(&user(1), &reviews, &[], 0, true));
+
+        reviews[2].1.waived = true;
+        assert!(fcp_can_start(&user(1), &reviews, &[], 0, true));
+    }
+
+    #[test]
+    fn initiator_review_counted_when_configured() {
+        let members = vec![user(1), user(2)];
+
+        let requests = initial_review_requests(7, &user(1), &members, true);
+        assert_eq!(
+            requests.iter().map(|r| r.reviewed).collect::<Vec<_>>(),
+            vec![true, false]
+        );
+        assert!(requests.iter().all(|r| r.fk_proposal == 7));
+
+        let requests = initial_review_requests(7, &user(1), &members, false);
+        assert!(requests.iter().all(|r| !r.reviewed));
+    }
+
+    #[test]
+    fn status_comment_shows_the_state() {
+        let issue = issue();
+        let initiator = user(1);
+        let reviewers = vec![review(1, true), review(2, false)];
+        let states = vec![
+            (ProposalState::Pending, "\u{23f3}"),
+            (ProposalState::InFcp, "\u{1f514}"),
+            (ProposalState::Closed, "\u{2705}"),
+            (ProposalState::Cancelled, "\u{274c}"),
+            (ProposalState::OnHold, "\u{23f8}\u{fe0f}"),
+        ];
+
+        for (state, emoji) in states {
+            let comment = RfcBotComment::new(
+                &issue,
+                CommentType::FcpProposed {
+                    initiator: &initiator,
+                    disposition: FcpDisposition::Merge,
+                    reviewers: &reviewers,
+                    concerns: &[],
+                    teams: &[],
+                    min_distinct_reviewers: 1,
+                    fcp_end: None,
+                    state: Some(state),
+                },
+            );
+            let expected = format!(
+                "{}\n{} Team member [user1](https://github.com/user1) has proposed to merge this.",
+                MANAGED_START, emoji
+            );
+            assert!(comment.body.starts_with(&expected), "{}", comment.body);
+
+            // the emoji doesn't get in the way of reading the reviews back
+            let stored = status_comment(comment.body);
+            assert_eq!(
+                parse_ticky_boxes("proposal", 1, &stored).collect::<Vec<_>>(),
+                vec!["user1"]
+            );
+        }
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviewers,
+                concerns: &[],
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        );
+        assert!(comment
+            .body
+            .starts_with(&format!("{}\nTeam member [user1](", MANAGED_START)));
+    }
+
+    #[test]
+    fn custom_proposal_template() {
+        let issue = issue();
+        let initiator = user(1);
+        let reviewers = vec![review(1, true), review(2, false)];
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Close,
+                reviewers: &reviewers,
+                concerns: &[],
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        )
+        .preserving_edits_to(
+            "Notes from the meeting.\n\n<!-- rfcbot-managed-start -->\n<!-- rfcbot-managed-end -->",
+        );
+
+        let templates = btreemap! {
+            "fcp_proposed".to_string() =>
+                "@{initiator} would like to {disposition} this {unknown}. Please review:\n\n{reviewers}"
+                    .to_string(),
+        };
+        let body = comment.templated(&templates);
+        assert_eq!(
+            body,
+            "Notes from the meeting.\n\n<!-- rfcbot-managed-start -->\n\
+             @user1 would like to close this {unknown}. Please review:\n\n\
+             * [x] [user1](https://github.com/user1)\n* [ ] [user2](https://github.com/user2)\n\n\
+             <!-- rfcbot-managed-end -->"
+        );
+
+        // the checklist can still be read back
+        let stored = status_comment(body);
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &stored).collect::<Vec<_>>(),
+            vec!["user1"]
+        );
+
+        assert_eq!(comment.templated(&BTreeMap::new()), comment.body);
+    }
+
+    #[test]
+    fn muted_issues_get_no_comments() {
+        let mut issue = issue();
+        assert!(may_comment_on(&issue));
+
+        issue.muted = true;
+        assert!(!may_comment_on(&issue));
+
+        issue.muted = false;
+        issue.open = false;
+        assert!(!may_comment_on(&issue));
+    }
+
+    #[test]
+    fn auto_merge_is_gated() {
+        let mut issue = issue();
+        assert!(!should_auto_merge(&issue, true));
+
+        issue.is_pull_request = true;
+        assert!(should_auto_merge(&issue, true));
+        assert!(!should_auto_merge(&issue, false));
+
+        issue.open = false;
+        assert!(!should_auto_merge(&issue, true));
+    }
+
+    #[test]
+    fn fcp_never_starts_without_reviewers() {
+        assert!(!fcp_can_start(&user(1), &[], &[], 0, true));
+
+        let body = RfcBotComment::new(&issue(), CommentType::NoReviewers(&user(1))).body;
+        assert!(body.starts_with("@user1, no reviewers could be found"));
+    }
+
+    #[test]
+    fn waived_reviews_rendered_distinctly() {
+        let mut reviews = vec![review(1, true), review(2, false), review(3, false)];
+        reviews[2].1.waived = true;
+
+        let mut msg = String::new();
+        format_review_boxes(&mut msg, &reviews);
+        assert_eq!(
+            msg,
+            "* [x] [user1](https://github.com/user1)\n* [ ] [user2](https://github.com/user2)\n\
+             * [~] [user3](https://github.com/user3) (waived)\n"
+        );
+
+        // the waived box doesn't count as a checked one
+        let comment = status_comment(msg);
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &comment).collect::<Vec<_>>(),
+            vec!["user1"]
+        );
+    }
+
+    #[test]
+    fn reviewers_are_pinged_once() {
+        let reviews = vec![review(1, true), review(2, false)];
+        let issue = issue();
+        let initiator = user(1);
+        let status = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviews,
+                concerns: &[],
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        )
+        .body;
+        let pinged = vec![user(2)];
+        let request = RfcBotComment::new(
+            &issue,
+            CommentType::ReviewRequested {
+                status_comment_id: 7,
+                disposition: FcpDisposition::Merge,
+                reviewers: &pinged,
+            },
+        )
+        .body;
+
+        // the status comment is edited all the time, so it only links to the reviewers
+        assert!(status.contains("* [ ] [user2](https://github.com/user2)\n"));
+        assert!(!status.contains("@user2"));
+        assert!(request.starts_with("@user2: your review is requested"));
+        assert!(request.contains("#issuecomment-7"));
+        assert!(!request.contains("@user1"));
+
+        // boxes checked in either format are still counted
+        let checked = status_comment(status.replace("* [ ] [user2]", "* [x] [user2]"));
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &checked).collect::<Vec<_>>(),
+            vec!["user1", "user2"]
+        );
+    }
+
+    #[test]
+    fn recorded_reviews_name_the_recorder() {
+        let mut recorded = review(2, true);
+        recorded.1.recorded_by = Some("user1".to_string());
+        let reviews = vec![review(1, true), recorded];
+        let mut msg = String::new();
+        format_review_boxes(&mut msg, &reviews);
+
+        assert_eq!(
+            msg,
+            "* [x] [user1](https://github.com/user1)\n\
+             * [x] [user2](https://github.com/user2) (recorded by \
+             [user1](https://github.com/user1))\n"
+        );
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &status_comment(msg)).collect::<Vec<_>>(),
+            vec!["user1", "user2"]
+        );
+    }
+
+    #[test]
+    fn review_notes_follow_the_reviewer() {
+        let mut noted = review(1, true);
+        noted.1.note = Some("concern-free".to_string());
+        let mut unreviewed = review(2, false);
+        unreviewed.1.note = Some("stale".to_string());
+        let mut msg = String::new();
+        format_review_boxes(&mut msg, &[noted, unreviewed]);
+
+        assert_eq!(
+            msg,
+            "* [x] [user1](https://github.com/user1): concern-free\n\
+             * [ ] [user2](https://github.com/user2)\n"
+        );
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &status_comment(msg)).collect::<Vec<_>>(),
+            vec!["user1"]
+        );
+
+        assert_eq!(
+            escape_review_note("cc @octocat <!-- rfcbot-managed-end -->"),
+            "cc @&#8203;octocat &lt;!-- rfcbot-managed-end -->"
+        );
+        assert_eq!(escape_review_note("fine\nby me"), "fine by me");
+        let long = "a".repeat(MAX_REVIEW_NOTE_CHARS + 1);
+        assert_eq!(
+            escape_review_note(&long),
+            format!("{}…", &long[..MAX_REVIEW_NOTE_CHARS])
+        );
+        assert_eq!(escape_review_note(&long[1..]), &long[1..]);
+    }
+
+    #[test]
+    fn reconciliation_picks_up_external_checks() {
+        let reviews = vec![review(1, false), review(2, false)];
+        let mut msg = String::new();
+        format_review_boxes(&mut msg, &reviews);
+        let stored = status_comment(msg.clone());
+        assert!(parse_ticky_boxes("proposal", 1, &stored).next().is_none());
+
+        // someone checked user2's box while rfcbot wasn't listening
+        let edited = msg.replace("* [ ] [user2]", "* [x] [user2]");
+        let later = stored.updated_at + Duration::hours(1);
+        let reconciled = reconciled_comment(stored, edited, later);
+
+        assert_eq!(reconciled.id, 1);
+        assert_eq!(reconciled.updated_at, later);
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &reconciled).collect::<Vec<_>>(),
+            vec!["user2"]
+        );
+    }
+
+    #[test]
+    fn status_comment_names_the_teams() {
+        let reviews = vec![review(1, false), review(2, false)];
+        let issue = issue();
+        let initiator = user(1);
+        let teams = vec!["rust-lang/lang".to_string(), "rust-lang/libs".to_string()];
+        let proposed = CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition: FcpDisposition::Merge,
+            reviewers: &reviews,
+            concerns: &[],
+            teams: &teams,
+            min_distinct_reviewers: 1,
+            fcp_end: None,
+            state: None,
+        };
+
+        let body = RfcBotComment::new(&issue, proposed).body;
+        assert!(body.contains(
+            "has proposed to merge this. The next step is review by the rest of the team \
+             members of [rust-lang/lang](https://github.com/orgs/rust-lang/teams/lang), \
+             [rust-lang/libs](https://github.com/orgs/rust-lang/teams/libs):\n\n"
+        ));
+        assert!(!body.contains("tagged team members"));
+    }
+
+    #[test]
+    fn status_comment_mentions_no_one() {
+        let reviews = vec![review(1, false), review(2, true)];
+        let issue = issue();
+        let initiator = user(1);
+        let teams = vec!["rust-lang/lang".to_string()];
+        let concern = FcpConcern {
+            id: 1,
+            fk_proposal: 1,
+            fk_initiator: 1,
+            fk_resolved_comment: None,
+            name: "naming".to_string(),
+            fk_initiating_comment: 10,
+            fk_directed_at: Some(2),
+            description: None,
+            fk_overridden_by: None,
+        };
+        let concerns = vec![(user(1), concern)];
+        let proposed = CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition: FcpDisposition::Merge,
+            reviewers: &reviews,
+            concerns: &concerns,
+            teams: &teams,
+            min_distinct_reviewers: 1,
+            fcp_end: None,
+            state: None,
+        };
+
+        let body = RfcBotComment::new(&issue, proposed).body;
+        assert!(body.contains("(for [user2](https://github.com/user2))"));
+        assert!(!body.contains('@'), "{}", body);
+    }
+
+    #[test]
+    fn team_pings_follow_the_issue() {
+        use crate::teams::test::TEST_SETUP;
+
+        let mut issue = issue();
+        assert_eq!(team_pings(&TEST_SETUP, &issue), vec!["marvel/avengers"]);
+        issue.labels.clear();
+        assert!(team_pings(&TEST_SETUP, &issue).is_empty());
+    }
+
+    #[test]
+    fn status_comment_names_the_tagged_teams() {
+        use crate::teams::test::TEST_SETUP;
+
+        let mut issue = issue();
+        let initiator = user(1);
+        let reviews = vec![review(2, false)];
+        let render = |issue: &Issue| {
+            let teams = team_pings(&TEST_SETUP, issue);
+            let proposed = CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviews,
+                concerns: &[],
+                teams: &teams,
+                min_distinct_reviewers: 0,
+                fcp_end: None,
+                state: None,
+            };
+            RfcBotComment::new(issue, proposed).body
+        };
+
+        assert!(render(&issue).contains(
+            "The next step is review by the rest of the team members of \
+             [marvel/avengers](https://github.com/orgs/marvel/teams/avengers):\n\n"
+        ));
+        issue.labels.clear();
+        assert!(render(&issue)
+            .contains("The next step is review by the rest of the tagged team members:\n\n"));
+    }
+
+    #[test]
+    fn edits_outside_managed_region_survive() {
+        let reviews = vec![review(1, false)];
+        let issue = issue();
+        let initiator = user(1);
+        let proposed = CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition: FcpDisposition::Merge,
+            reviewers: &reviews,
+            concerns: &[],
+            teams: &[],
+            min_distinct_reviewers: 1,
+            fcp_end: None,
+            state: None,
+        };
+
+        let original = RfcBotComment::new(&issue, proposed.clone()).body;
+        let edited = format!("**Pinned:** see the summary.\n\n{}\n\nThanks!", original);
+
+        let reviews = vec![review(1, true)];
+        let proposed = CommentType::FcpProposed {
+            initiator: &initiator,
+            disposition: FcpDisposition::Merge,
+            reviewers: &reviews,
+            concerns: &[],
+            teams: &[],
+            min_distinct_reviewers: 1,
+            fcp_end: None,
+            state: None,
+        };
+        let regenerated = RfcBotComment::new(&issue, proposed).preserving_edits_to(&edited);
+
+        assert!(regenerated
+            .body
+            .starts_with("**Pinned:** see the summary.\n\n"));
+        assert!(regenerated.body.ends_with("\n\nThanks!"));
+        assert!(regenerated.body.contains("* [x] [user1]"));
+        assert!(!regenerated.body.contains("* [ ] [user1]"));
+    }
+
+    #[test]
+    fn ticky_boxes_only_parsed_in_managed_region() {
+        let body = format!(
+            "* [x] @user2\n{}\n* [x] @user1\n* [ ] @user3\n{}\n",
+            MANAGED_START, MANAGED_END
+        );
+        let comment = status_comment(body);
+
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &comment).collect::<Vec<_>>(),
+            vec!["user1"]
+        );
+    }
+
+    #[test]
+    fn concern_names_normalized() {
+        assert_eq!(
+            normalize_concern_name("  Concern \t  A ").unwrap(),
+            "Concern A"
+        );
+        assert!(normalize_concern_name(" \t ").is_err());
+    }
+
+    /// Stands in for GitHub, keeping the labels and comments of a single issue.
+    struct FakeGithub {
+        user: GitHubUser,
+        labels: Mutex<BTreeSet<String>>,
+        /// The issue number and body of each comment by its id.
+        comments: Mutex<BTreeMap<i32, (i32, String)>>,
+        /// Who reacted to any comment, with any reaction.
+        reactions: Mutex<Vec<String>>,
+        /// The numbers of the pull requests merged.
+        merged: Mutex<Vec<i32>>,
+    }
+
+    impl FakeGithub {
+        fn comment(&self, repo: &str, issue_num: i32, id: i32, body: &str) -> CommentFromJson {
+            CommentFromJson {
+                id,
+                html_url: format!(
+                    "https://github.com/{}/issues/{}#issuecomment-{}",
+                    repo, issue_num, id
+                ),
+                body: body.to_string(),
+                user: self.user.clone(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }
+        }
+    }
+
+    impl GithubClient for FakeGithub {
+        fn add_label(&self, _: &str, _: i32, label: &str) -> DashResult<()> {
+            self.labels.lock().unwrap().insert(label.to_string());
+            Ok(())
+        }
+
+        fn remove_label(&self, _: &str, _: i32, label: &str) -> DashResult<()> {
+            self.labels.lock().unwrap().remove(label);
+            Ok(())
+        }
+
+        fn issue_labels(&self, _: &str, _: i32) -> DashResult<Vec<String>> {
+            Ok(self.labels.lock().unwrap().iter().cloned().collect())
+        }
+
+        fn comments_of_issue(
+            &self,
+            repo: &str,
+            issue_num: i32,
+        ) -> DashResult<Vec<CommentFromJson>> {
+            let comments = self.comments.lock().unwrap();
+            Ok(comments
+                .iter()
+                .map(|(&id, (_, body))| self.comment(repo, issue_num, id, body))
+                .collect())
+        }
+
+        fn new_comment(
+            &self,
+            repo: &str,
+            issue_num: i32,
+            text: &str,
+        ) -> DashResult<CommentFromJson> {
+            let mut comments = self.comments.lock().unwrap();
+            // well clear of the ids used by the test fixtures
+            let id = -100_000 - comments.len() as i32;
+            comments.insert(id, (issue_num, text.to_string()));
+            Ok(self.comment(repo, issue_num, id, text))
+        }
+
+        fn edit_comment(
+            &self,
+            repo: &str,
+            comment_num: i32,
+            text: &str,
+        ) -> DashResult<CommentFromJson> {
+            let mut comments = self.comments.lock().unwrap();
+            let issue_num = comments.get(&comment_num).ok_or(DashError::Misc(None))?.0;
+            comments.insert(comment_num, (issue_num, text.to_string()));
+            Ok(self.comment(repo, issue_num, comment_num, text))
+        }
+
+        fn close_issue(&self, _: &str, _: i32) -> DashResult<()> { Ok(()) }
+
+        fn comment_reaction_users(&self, _: &str, _: i32, _: &str) -> DashResult<Vec<String>> {
+            Ok(self.reactions.lock().unwrap().clone())
+        }
+
+        fn comment_reactions(&self, _: &str, _: i32) -> DashResult<Vec<ReactionFromJson>> {
+            Ok(vec![])
+        }
+
+        fn delete_comment_reaction(&self, _: &str, _: i32, _: i32) -> DashResult<()> { Ok(()) }
+
+        fn set_milestone(&self, _: &str, _: i32, _: i32) -> DashResult<()> { Ok(()) }
+
+        fn merge_pr(&self, _: &str, pr_num: i32, _: &str) -> DashResult<bool> {
+            self.merged.lock().unwrap().push(pr_num);
+            Ok(true)
+        }
+
+        fn issue_events(&self, _: &str, _: i32) -> DashResult<Vec<LabelEvent>> { Ok(vec![]) }
+
+        fn get_comment(&self, repo: &str, comment_num: i32) -> DashResult<CommentFromJson> {
+            let comments = self.comments.lock().unwrap();
+            let (issue_num, body) = comments.get(&comment_num).ok_or(DashError::Misc(None))?;
+            Ok(self.comment(repo, *issue_num, comment_num, body))
+        }
+
+        fn current_user(&self) -> DashResult<GitHubUser> { Ok(self.user.clone()) }
+
+        fn login(&self) -> DashResult<String> { Ok(self.user.login.clone()) }
+
+        fn get_user(&self, _: &str) -> DashResult<GitHubUser> { Ok(self.user.clone()) }
+    }
+
+    /// Uninstalls the fake when it's dropped, so a failing test doesn't leave it in place.
+    struct FakeGithubGuard(&'static FakeGithub);
+
+    impl std::ops::Deref for FakeGithubGuard {
+        type Target = FakeGithub;
+
+        fn deref(&self) -> &FakeGithub { self.0 }
+    }
+
+    impl Drop for FakeGithubGuard {
+        fn drop(&mut self) { FAKE_GITHUB.with(|current| current.set(None)); }
+    }
+
+    /// Sends this thread's requests to GitHub to a fake, whose comments are posted by `user`,
+    /// until the returned guard is dropped.
+    fn use_fake_github(user: &GitHubUser) -> FakeGithubGuard {
+        let fake: &'static FakeGithub = Box::leak(Box::new(FakeGithub {
+            user: user.clone(),
+            labels: Mutex::new(BTreeSet::new()),
+            comments: Mutex::new(BTreeMap::new()),
+            reactions: Mutex::new(vec![]),
+            merged: Mutex::new(vec![]),
+        }));
+        FAKE_GITHUB.with(|current| current.set(Some(fake)));
+        FakeGithubGuard(fake)
+    }
+
+    /// Deletes the issue's proposal and the comments posted to the fake.
+    fn clear_proposal(conn: &PgConnection, issue: &Issue, fake: &FakeGithub) {
+        if let Some(proposal) = existing_proposal(issue).unwrap() {
+            diesel::delete(
+                fcp_review_request::table.filter(fcp_review_request::fk_proposal.eq(proposal.id)),
+            )
+            .execute(conn)
+            .unwrap();
+            diesel::delete(fcp_proposal::table.find(proposal.id))
+                .execute(conn)
+                .unwrap();
+        }
+        let posted = fake
+            .comments
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        diesel::delete(issuecomment::table.filter(issuecomment::id.eq_any(posted)))
+            .execute(conn)
+            .unwrap();
+    }
+
+    /// Run `f` against a pending proposal in the test database, cleaning up afterwards.
+    fn with_test_proposal<F>(seed: i32, f: F)
+    where
+        F: FnOnce(&PgConnection, &GitHubUser, &Issue, &IssueComment, &FcpProposal),
+    {
+        use crate::domain::github::IssuePartial;
+        use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+        crate::utils::setup_test_env();
+        let conn = &*DB_POOL.get().expect("Unable to get a test connection");
+
+        let test_id = -1000 - seed;
+        let author = GitHubUser {
+            id: test_id,
+            login: format!("rfcbot-test-{}", seed),
+        };
+        crate::github::handle_user(conn, &author).expect("Unable to insert test user");
+
+        let now = Utc::now().naive_utc();
+        let partial = IssuePartial {
+            number: test_id,
+            fk_milestone: None,
+            fk_user: author.id,
+            fk_assignee: None,
+            open: true,
+            is_pull_request: false,
+            title: "A test RFC".to_string(),
+            body: String::new(),
+            locked: false,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+            labels: vec![],
+            repository: "rfcbot-rs/test-fixtures".to_string(),
+        };
+        let test_issue = diesel::insert_into(issue::table)
+            .values(&partial)
+            .get_result::<Issue>(conn)
+            .expect("Unable to insert test issue");
+
+        let mut comment = status_comment(String::new());
+        comment.id = test_id;
+        comment.fk_issue = test_issue.id;
+        comment.fk_user = author.id;
+        diesel::insert_into(issuecomment::table)
+            .values(&comment)
+            .execute(conn)
+            .expect("Unable to insert test comment");
+
+        let proposal = diesel::insert_into(fcp_proposal::table)
+            .values(&NewFcpProposal {
+                fk_issue: test_issue.id,
+                fk_initiator: author.id,
+                fk_initiating_comment: comment.id,
+                disposition: FcpDisposition::Merge.repr(),
+                fk_bot_tracking_comment: comment.id,
+                fcp_start: None,
+                snooze_until: None,
+                state: ProposalState::Pending.repr(),
+                almost_ready_notified: false,
+                fcp_end_override: None,
+                tracking_label: None,
+            })
+            .get_result::<FcpProposal>(conn)
+            .expect("Unable to insert test proposal");
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            f(conn, &author, &test_issue, &comment, &proposal)
+        }));
+
+        // Clean up after ourselves
+        diesel::delete(fcp_proposal::table.find(proposal.id))
+            .execute(conn)
+            .expect("Failed to clear test proposal");
+        diesel::delete(issuecomment::table.find(comment.id))
+            .execute(conn)
+            .expect("Failed to clear test comment");
+        diesel::delete(issue::table.find(test_issue.id))
+            .execute(conn)
+            .expect("Failed to clear test issue");
+        diesel::delete(githubuser::table.find(author.id))
+            .execute(conn)
+            .expect("Failed to clear test user");
+
+        if let Err(panic) = result {
+            resume_unwind(panic);
+        }
+    }
+
+    #[test]
+    fn close_proposals_have_their_own_reviewers() {
+        with_test_proposal(10, |conn, author, issue, comment, fixture| {
+            let other = GitHubUser {
+                id: author.id - 500,
+                login: format!("{}-other", author.login),
+            };
+            crate::github::handle_user(conn, &other).unwrap();
+            let setup = crate::teams::test::setup_from(&format!(
+                r#"
+[fcp_behaviors]
+
+[teams]
+
+[teams.T-avengers]
+name = "The Avengers"
+ping = "marvel/avengers"
+members = ["{}", "{}"]
+
+[disposition_reviewers.close]
+members = ["{}"]
+"#,
+                author.login, other.login, other.login
+            ));
+            let fake = use_fake_github(author);
+            diesel::delete(fcp_proposal::table.find(fixture.id))
+                .execute(conn)
+                .unwrap();
+
+            let team = [author.clone(), other.clone()];
+            let reviewers = |disp| -> DashResult<Vec<String>> {
+                let members = disposition_reviewers(&setup, disp, &team);
+                let proposed = propose_fcp(author, issue, comment, &members, disp, false, None);
+                let proposal = existing_proposal(issue)?.ok_or(DashError::Misc(None));
+                let reviews = proposal.and_then(|p| list_review_requests(p.id));
+                clear_proposal(conn, issue, &fake);
+                proposed?;
+                Ok(reviews?.into_iter().map(|(user, _)| user.login).collect())
+            };
+            let merge = reviewers(FcpDisposition::Merge);
+            let close = reviewers(FcpDisposition::Close);
+
+            diesel::delete(githubuser::table.find(other.id))
+                .execute(conn)
+                .unwrap();
+
+            let mut merge = merge.unwrap();
+            merge.sort();
+            let mut everyone = vec![author.login.clone(), other.login.clone()];
+            everyone.sort();
+            assert_eq!(merge, everyone);
+            assert_eq!(close.unwrap(), vec![other.login.clone()]);
+        });
+    }
+
+    #[test]
+    fn confirmed_proposals_keep_their_initiator() {
+        with_test_proposal(22, |conn, author, issue, comment, fixture| {
+            let member = GitHubUser {
+                id: author.id - 500,
+                login: format!("{}-member", author.login),
+            };
+            crate::github::handle_user(conn, &member).unwrap();
+            let fake = use_fake_github(author);
+            diesel::delete(fcp_proposal::table.find(fixture.id))
+                .execute(conn)
+                .unwrap();
+            diesel::insert_into(proposed_command::table)
+                .values(&NewProposedCommand {
+                    fk_issue: issue.id,
+                    fk_initiator: author.id,
+                    fk_initiating_comment: comment.id,
+                    disposition: FcpDisposition::Merge.repr(),
+                })
+                .execute(conn)
+                .unwrap();
+
+            let confirmed = process_confirm(&member, issue, &[member.clone()]);
+            let proposal = existing_proposal(issue);
+            let still_queued = queued_proposal(issue);
+            clear_proposal(conn, issue, &fake);
+            diesel::delete(proposed_command::table.filter(proposed_command::fk_issue.eq(issue.id)))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(githubuser::table.find(member.id))
+                .execute(conn)
+                .unwrap();
+
+            confirmed.unwrap();
+            let proposal = proposal.unwrap().unwrap();
+            assert_eq!(proposal.fk_initiator, author.id);
+            assert_eq!(proposal.fk_initiating_comment, comment.id);
+            assert!(still_queued.unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn mirror_comment_format() {
+        let source = issue();
+        let mut target = issue();
+        target.id = 2;
+        target.number = 43;
+        let proposal = FcpProposal {
+            fk_issue: source.id,
+            fk_bot_tracking_comment: 7,
+            ..proposal(ProposalState::Pending)
+        };
+        let concern = |id, name: &str, resolved| FcpConcern {
+            id,
+            fk_proposal: 1,
+            fk_initiator: 2,
+            fk_resolved_comment: resolved,
+            name: name.to_string(),
+            fk_initiating_comment: 10 + id,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: None,
+        };
+        let reviewers = [review(1, true), review(2, false), review(3, true)];
+        let concerns = [
+            (user(2), concern(1, "naming", None)),
+            (user(2), concern(2, "docs", Some(20))),
+        ];
+
+        let mirror = RfcBotComment::new(
+            &target,
+            CommentType::FcpMirror {
+                source: &source,
+                proposal: &proposal,
+                reviewers: &reviewers,
+                concerns: &concerns,
+            },
+        );
+        assert_eq!(
+            mirror.body,
+            "This mirrors the proposal to merge rust-lang/rfcs#42. Its status is kept up to date \
+             here, but reviews, concerns and other commands belong on [its status comment](\
+             https://github.com/rust-lang/rfcs/issues/42#issuecomment-7).\n\n\
+             * Status: waiting on reviews or concerns\n\
+             * Reviews: 2 of 3 done\n\
+             * Unresolved concerns: `naming`\n"
+        );
+    }
+
+    #[test]
+    fn mirrors_follow_their_proposal() {
+        with_test_proposal(11, |conn, author, issue, _, proposal| {
+            use crate::domain::github::IssuePartial;
+
+            let fake = use_fake_github(author);
+            let target = diesel::insert_into(issue::table)
+                .values(&IssuePartial {
+                    number: issue.number - 500,
+                    fk_milestone: None,
+                    fk_user: author.id,
+                    fk_assignee: None,
+                    open: true,
+                    is_pull_request: false,
+                    title: "A tracking issue".to_string(),
+                    body: String::new(),
+                    locked: false,
+                    closed_at: None,
+                    created_at: issue.created_at,
+                    updated_at: issue.updated_at,
+                    labels: vec![],
+                    repository: issue.repository.clone(),
+                })
+                .get_result::<Issue>(conn)
+                .unwrap();
+
+            let mirrored = process_mirror(issue, target.number);
+            let before = fake.comments.lock().unwrap().clone();
+            let updated = diesel::update(fcp_proposal::table.find(proposal.id))
+                .set(fcp_proposal::state.eq(ProposalState::InFcp.repr()))
+                .execute(conn)
+                .map_err(DashError::from)
+                .and_then(|_| update_mirrors());
+            let after = fake.comments.lock().unwrap().clone();
+
+            diesel::delete(fcp_mirrors::table.filter(fcp_mirrors::fk_proposal.eq(proposal.id)))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(issuecomment::table.filter(issuecomment::fk_issue.eq(target.id)))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(issue::table.find(target.id))
+                .execute(conn)
+                .unwrap();
+
+            mirrored.unwrap();
+            updated.unwrap();
+            assert_eq!(before.len(), 1);
+            let (&id, (number, body)) = before.iter().next().unwrap();
+            assert_eq!(*number, target.number);
+            assert!(body.contains("* Status: waiting on reviews or concerns"));
+            assert_eq!(after.len(), 1);
+            assert!(after[&id]
+                .1
+                .contains("* Status: in its final comment period"));
+        });
+    }
+
+    #[test]
+    fn proposals_need_the_required_label() {
+        with_test_proposal(12, |conn, author, issue, comment, fixture| {
+            let fake = use_fake_github(author);
+            diesel::delete(fcp_proposal::table.find(fixture.id))
+                .execute(conn)
+                .unwrap();
+            let team = [author.clone()];
+            let propose = |issue: &Issue| {
+                propose_fcp(
+                    author,
+                    issue,
+                    comment,
+                    &team,
+                    FcpDisposition::Merge,
+                    true,
+                    Some("T-lang"),
+                )
+                .and_then(|_| existing_proposal(issue))
+            };
+
+            let refused = propose(issue);
+            let refusals = fake.comments.lock().unwrap().clone();
+            let mut labelled = issue.clone();
+            labelled.labels.push("T-lang".to_string());
+            let accepted = propose(&labelled);
+
+            clear_proposal(conn, issue, &fake);
+
+            assert_eq!(refused.unwrap(), None);
+            assert_eq!(refusals.len(), 1);
+            assert!(refusals
+                .values()
+                .all(|(_, body)| body.contains("issues labelled `T-lang`")));
+            let accepted = accepted
+                .unwrap()
+                .expect("the labelled issue should have a proposal");
+            assert_eq!(accepted.fk_issue, issue.id);
+        });
+    }
+
+    #[test]
+    fn reactions_only_count_for_team_members() {
+        let user = |id: i32, login: &str| GitHubUser {
+            id,
+            login: login.to_string(),
+        };
+        let request = |id: i32, reviewed: bool| FcpReviewRequest {
+            id,
+            fk_proposal: 1,
+            fk_reviewer: id,
+            reviewed,
+            waived: false,
+            recorded_by: None,
+            note: None,
+        };
+        let reviews = vec![
+            (user(1, "alice"), request(1, false)),
+            (user(2, "bob"), request(2, false)),
+            (user(3, "carol"), request(3, true)),
+            (user(4, "dave"), request(4, false)),
+        ];
+        let reacted = vec!["Alice".to_string(), "bob".to_string(), "carol".to_string()];
+        // bob has left the team since the proposal was made
+        let members = vec![user(1, "alice"), user(3, "carol"), user(4, "dave")];
+
+        assert_eq!(reaction_reviews(&reviews, &reacted, &members), vec![1]);
+    }
+
+    #[test]
+    fn reaction_marks_review() {
+        with_test_proposal(13, |conn, author, issue, _, proposal| {
+            let fake = use_fake_github(author);
+            diesel::insert_into(fcp_review_request::table)
+                .values(&NewFcpReviewRequest {
+                    fk_proposal: proposal.id,
+                    fk_reviewer: author.id,
+                    reviewed: false,
+                    waived: false,
+                })
+                .execute(conn)
+                .unwrap();
+            fake.reactions.lock().unwrap().push(author.login.clone());
+
+            let result = review_from_reactions(issue, proposal, &[author.clone()]);
+            let reviews = list_review_requests(proposal.id);
+
+            clear_proposal(conn, issue, &fake);
+
+            result.unwrap();
+            let reviews = reviews.unwrap();
+            assert_eq!(reviews.len(), 1);
+            assert!(reviews[0].1.reviewed);
+        });
+    }
+
+    #[test]
+    fn reviewed_proposal_enters_fcp() {
+        with_test_proposal(8, |conn, author, issue, comment, fixture| {
+            let fake = use_fake_github(author);
+            diesel::delete(fcp_proposal::table.find(fixture.id))
+                .execute(conn)
+                .unwrap();
+
+            let flow = || -> DashResult<FcpProposal> {
+                propose_fcp(
+                    author,
+                    issue,
+                    comment,
+                    &[author.clone()],
+                    FcpDisposition::Merge,
+                    false,
+                    None,
+                )?;
+                process_reviewed(author, issue)?;
+
+                let mut proposal = existing_proposal(issue)?.ok_or(DashError::Misc(None))?;
+                let reviews = list_review_requests(proposal.id)?;
+                let concerns = list_concerns_with_authors(proposal.id)?;
+                assert!(fcp_can_start(author, &reviews, &concerns, 0, true));
+                start_fcp(conn, &mut proposal, issue, author);
+                Ok(fcp_proposal::table.find(proposal.id).first(conn)?)
+            };
+            let started = flow();
+
+            clear_proposal(conn, issue, &fake);
+
+            let started = started.unwrap();
+            assert_eq!(started.state().unwrap(), ProposalState::InFcp);
+            assert!(started.fcp_start.is_some());
+
+            let labels = fake.labels.lock().unwrap();
+            assert!(labels.contains(Label::FCP.as_str()));
+            assert!(!labels.contains(Label::PFCP.as_str()));
+            let comments = fake.comments.lock().unwrap();
+            assert!(comments.values().any(|(_, body)| announces_fcp_start(
+                issue,
+                started.fk_bot_tracking_comment,
+                body
+            )));
+        });
+    }
+
+    #[test]
+    fn fcp_can_be_ended_early() {
+        with_test_proposal(15, |conn, author, issue, _, fixture| {
+            let fake = use_fake_github(author);
+            let pending = process_end_fcp_now(issue);
+
+            let started = FcpProposal {
+                state: ProposalState::InFcp.repr(),
+                fcp_start: Some(Utc::now().naive_utc()),
+                ..fixture.clone()
+            };
+            diesel::update(fcp_proposal::table.find(fixture.id))
+                .set(&started)
+                .execute(conn)
+                .unwrap();
+            let ended = process_end_fcp_now(issue)
+                .and_then(|_| Ok(fcp_proposal::table.find(fixture.id).first(conn)?));
+
+            clear_proposal(conn, issue, &fake);
+
+            assert!(pending.is_err());
+            let ended: FcpProposal = ended.unwrap();
+            assert_eq!(ended.state().unwrap(), ProposalState::Closed);
+            assert!(fake.labels.lock().unwrap().contains(Label::FFCP.as_str()));
+            let comments = fake.comments.lock().unwrap();
+            assert!(comments
+                .values()
+                .any(|(_, body)| body.contains("is now **complete**")));
+        });
+    }
+
+    #[test]
+    fn reviews_can_be_recorded_for_someone() {
+        with_test_proposal(16, |conn, author, issue, _, proposal| {
+            let other = GitHubUser {
+                id: author.id - 500,
+                login: format!("{}-other", author.login),
+            };
+            crate::github::handle_user(conn, &other).unwrap();
+            diesel::insert_into(fcp_review_request::table)
+                .values(&NewFcpReviewRequest {
+                    fk_proposal: proposal.id,
+                    fk_reviewer: other.id,
+                    reviewed: false,
+                    waived: false,
+                })
+                .execute(conn)
+                .unwrap();
+
+            let recorded = process_reviewed_for(author, issue, &other.login);
+            let stranger = process_reviewed_for(author, issue, "nobody-in-particular");
+            let review: FcpReviewRequest = fcp_review_request::table
+                .filter(fcp_review_request::fk_reviewer.eq(other.id))
+                .first(conn)
+                .unwrap();
+
+            diesel::delete(
+                fcp_review_request::table.filter(fcp_review_request::fk_proposal.eq(proposal.id)),
+            )
+            .execute(conn)
+            .unwrap();
+            diesel::delete(githubuser::table.find(other.id))
+                .execute(conn)
+                .unwrap();
+
+            recorded.unwrap();
+            stranger.unwrap();
+            assert!(review.reviewed);
+            assert_eq!(review.recorded_by, Some(author.login.clone()));
+        });
+    }
+
+    #[test]
+    fn waived_reviews_shown_in_status_comment() {
+        with_test_proposal(21, |conn, author, issue, comment, proposal| {
+            let fake = use_fake_github(author);
+            fake.comments
+                .lock()
+                .unwrap()
+                .insert(comment.id, (issue.number, String::new()));
+            diesel::insert_into(fcp_review_request::table)
+                .values(&NewFcpReviewRequest {
+                    fk_proposal: proposal.id,
+                    fk_reviewer: author.id,
+                    reviewed: false,
+                    waived: false,
+                })
+                .execute(conn)
+                .unwrap();
+
+            let waived = process_waive(author, issue, &author.login);
+            let posted = fake.comments.lock().unwrap()[&comment.id].1.clone();
+
+            clear_proposal(conn, issue, &fake);
+
+            waived.unwrap();
+            assert!(posted.contains(&format!(
+                "* [~] [{0}](https://github.com/{0}) (waived)",
+                author.login
+            )));
+        });
+    }
+
+    #[test]
+    fn proposals_can_be_put_on_hold() {
+        with_test_proposal(18, |conn, author, issue, comment, proposal| {
+            let fake = use_fake_github(author);
+            fake.comments
+                .lock()
+                .unwrap()
+                .insert(comment.id, (issue.number, String::new()));
+            let state_now = || {
+                fcp_proposal::table
+                    .find(proposal.id)
+                    .first::<FcpProposal>(conn)
+                    .unwrap()
+            };
+
+            let held = process_hold(issue, true);
+            let on_hold = state_now();
+            let held_twice = process_hold(issue, true);
+            let unheld = process_hold(issue, false);
+            let pending = state_now();
+
+            held.unwrap();
+            assert_eq!(on_hold.state().unwrap(), ProposalState::OnHold);
+            assert!(held_twice.is_err());
+            unheld.unwrap();
+            assert_eq!(pending.state().unwrap(), ProposalState::Pending);
+
+            let mut running = pending.clone();
+            running.transition(ProposalState::InFcp).unwrap();
+            running.fcp_start = Some(Utc::now().naive_utc());
+            diesel::update(fcp_proposal::table.find(proposal.id))
+                .set(&running)
+                .execute(conn)
+                .unwrap();
+            let unheld_in_fcp = process_hold(issue, false);
+            let still_running = state_now();
+
+            assert!(unheld_in_fcp.is_err());
+            assert_eq!(still_running.state().unwrap(), ProposalState::InFcp);
+            assert!(still_running.fcp_start.is_some());
+        });
+    }
+
+    #[test]
+    fn finished_fcps_are_merged() {
+        with_test_proposal(19, |conn, author, issue, comment, fixture| {
+            let fake = use_fake_github(author);
+            fake.comments
+                .lock()
+                .unwrap()
+                .insert(comment.id, (issue.number, String::new()));
+            let mut proposal = fixture.clone();
+            proposal.transition(ProposalState::InFcp).unwrap();
+            diesel::update(fcp_proposal::table.find(proposal.id))
+                .set(&proposal)
+                .execute(conn)
+                .unwrap();
+            let pr = Issue {
+                is_pull_request: true,
+                ..issue.clone()
+            };
+            let config = Config {
+                auto_merge_on_fcp: true,
+                status_emoji: false,
+                ..crate::config::test::config()
+            };
+
+            finish_fcp(
+                &config,
+                conn,
+                &mut proposal,
+                &pr,
+                author,
+                FcpDisposition::Merge,
+            );
+            let finished = fcp_proposal::table
+                .find(proposal.id)
+                .first::<FcpProposal>(conn)
+                .unwrap();
+            let merged = fake.merged.lock().unwrap().clone();
+
+            clear_proposal(conn, issue, &fake);
+
+            assert_eq!(finished.state().unwrap(), ProposalState::Closed);
+            assert_eq!(merged, vec![issue.number]);
+        });
+    }
+
+    #[test]
+    fn snoozed_proposals_still_enter_and_finish_fcp() {
+        with_test_proposal(20, |conn, author, issue, comment, fixture| {
+            let fake = use_fake_github(author);
+            fake.comments
+                .lock()
+                .unwrap()
+                .insert(comment.id, (issue.number, String::new()));
+            let state_now = || {
+                fcp_proposal::table
+                    .find(fixture.id)
+                    .first::<FcpProposal>(conn)
+                    .unwrap()
+            };
+            let config = Config {
+                auto_merge_on_fcp: false,
+                status_emoji: false,
+                ..crate::config::test::config()
+            };
+
+            let snoozed = process_snooze(issue, 7);
+            let mut proposal = state_now();
+            start_fcp(conn, &mut proposal, issue, author);
+            let started = state_now();
+            let later = Utc::now().naive_utc() + Duration::days(30);
+            let due =
+                repo_proposal_finished(&config, issue, &started, FcpDisposition::Merge, later);
+            finish_fcp(
+                &config,
+                conn,
+                &mut proposal,
+                issue,
+                author,
+                FcpDisposition::Merge,
+            );
+            let finished = state_now();
+
+            clear_proposal(conn, issue, &fake);
+
+            snoozed.unwrap();
+            assert!(started.snooze_until.is_some());
+            assert_eq!(started.state().unwrap(), ProposalState::InFcp);
+            assert!(due);
+            assert_eq!(finished.state().unwrap(), ProposalState::Closed);
+        });
+    }
+
+    #[test]
+    fn resync_picks_up_checked_boxes() {
+        with_test_proposal(17, |conn, author, _, comment, proposal| {
+            diesel::insert_into(fcp_review_request::table)
+                .values(&NewFcpReviewRequest {
+                    fk_proposal: proposal.id,
+                    fk_reviewer: author.id,
+                    reviewed: false,
+                    waived: false,
+                })
+                .execute(conn)
+                .unwrap();
+            // the box was checked while rfcbot parsed it differently
+            let body = format!("* [x] [{0}](https://github.com/{0})\n", author.login);
+            diesel::update(issuecomment::table.find(comment.id))
+                .set(issuecomment::body.eq(body))
+                .execute(conn)
+                .unwrap();
+
+            let marked = resync_reviews();
+            let reviews = list_review_requests(proposal.id).unwrap();
+
+            diesel::delete(
+                fcp_review_request::table.filter(fcp_review_request::fk_proposal.eq(proposal.id)),
+            )
+            .execute(conn)
+            .unwrap();
+
+            // other tests' proposals might have been resynced too
+            assert!(marked.unwrap() >= 1);
+            assert!(reviews[0].1.reviewed);
+        });
+    }
+
+    #[test]
+    fn restore_after_reopen() {
+        with_test_proposal(6, |conn, author, issue, comment, fixture| {
+            let proposal = &FcpProposal {
+                tracking_label: Some("project-board".to_string()),
+                ..fixture.clone()
+            };
+            // the issue was closed, so the proposal was cancelled
+            archive_proposal(conn, proposal).unwrap();
+            diesel::delete(fcp_proposal::table.find(proposal.id))
+                .execute(conn)
+                .unwrap();
+
+            let restored = restore_archived(conn, issue, &[author.clone()], true)
+                .unwrap()
+                .expect("the archived proposal should be restored");
+            let reviews = list_review_requests(restored.id).unwrap();
+            let archived = archived_proposals::table
+                .filter(archived_proposals::fk_issue.eq(issue.id))
+                .count()
+                .get_result::<i64>(conn)
+                .unwrap();
+            let again = restore_archived(conn, issue, &[author.clone()], true).unwrap();
+            diesel::delete(fcp_proposal::table.find(restored.id))
+                .execute(conn)
+                .unwrap();
+
+            assert_eq!(restored.state().unwrap(), ProposalState::Pending);
+            assert_eq!(restored.disposition, proposal.disposition);
+            assert_eq!(restored.fk_bot_tracking_comment, comment.id);
+            assert_eq!(restored.tracking_label.as_deref(), Some("project-board"));
+            assert_eq!(reviews.len(), 1);
+            assert!(reviews[0].1.reviewed);
+            assert_eq!(archived, 0);
+            // there's nothing left to restore
+            assert!(again.is_none());
+        });
+    }
+
+    #[test]
+    fn force_closed_proposals_are_left_alone() {
+        with_test_proposal(5, |conn, _, _, _, proposal| {
+            let closed = force_close(conn, proposal.id).unwrap();
+            assert_eq!(closed.state().unwrap(), ProposalState::Closed);
+
+            let stored = fcp_proposal::table
+                .find(proposal.id)
+                .first::<FcpProposal>(conn)
+                .unwrap();
+            assert_eq!(stored.state().unwrap(), ProposalState::Closed);
+
+            // neither the pending nor the running proposals include it any more
+            let evaluated = fcp_proposal::table
+                .filter(fcp_proposal::id.eq(proposal.id))
+                .filter(
+                    fcp_proposal::state
+                        .eq(ProposalState::Pending.repr())
+                        .or(fcp_proposal::state.eq(ProposalState::InFcp.repr())),
+                )
+                .count()
+                .get_result::<i64>(conn)
+                .unwrap();
+            assert_eq!(evaluated, 0);
+
+            // closing it again is harmless
+            assert!(force_close(conn, proposal.id).is_ok());
+        });
+    }
+
+    #[test]
+    fn admins_resolve_other_peoples_concerns() {
+        with_test_proposal(7, |conn, author, issue, comment, proposal| {
+            let other = GitHubUser {
+                id: -2007,
+                login: "rfcbot-test-other".to_string(),
+            };
+            crate::github::handle_user(conn, &other).unwrap();
+            process_new_concern(author, issue, comment, "naming", None, None).unwrap();
+            let resolved = || {
+                fcp_concern::table
+                    .filter(fcp_concern::fk_proposal.eq(proposal.id))
+                    .first::<FcpConcern>(conn)
+                    .unwrap()
+            };
+
+            // someone else can't resolve it
+            process_resolve_concern(&other, issue, comment, "naming", false).unwrap();
+            let unresolved = resolved();
+
+            // unless they're an admin
+            process_resolve_concern(&other, issue, comment, "naming", true).unwrap();
+            let overridden = resolved();
+
+            diesel::delete(fcp_concern::table.filter(fcp_concern::fk_proposal.eq(proposal.id)))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(githubuser::table.find(other.id))
+                .execute(conn)
+                .unwrap();
+
+            assert_eq!(unresolved.fk_resolved_comment, None);
+            assert_eq!(overridden.fk_resolved_comment, Some(comment.id));
+            assert_eq!(overridden.fk_overridden_by, Some(other.id));
+        });
+    }
+
+    #[test]
+    fn concerns_deduplicated_case_insensitively() {
+        with_test_proposal(1, |conn, author, issue, comment, proposal| {
+            process_new_concern(author, issue, comment, "Concern A", None, None).unwrap();
+            process_new_concern(author, issue, comment, "concern   a", None, None).unwrap();
+
+            let concerns = fcp_concern::table
+                .filter(fcp_concern::fk_proposal.eq(proposal.id))
+                .load::<FcpConcern>(conn)
+                .unwrap();
+            assert_eq!(concerns.len(), 1);
+            assert_eq!(concerns[0].name, "Concern A");
+
+            process_resolve_concern(author, issue, comment, "CONCERN A", false).unwrap();
+            let concern = fcp_concern::table
+                .find(concerns[0].id)
+                .first::<FcpConcern>(conn)
+                .unwrap();
+            assert_eq!(concern.fk_resolved_comment, Some(comment.id));
+        });
+    }
+
+    #[test]
+    fn bursts_of_evaluations_edit_once() {
+        let start = NaiveDate::from_ymd(2018, 1, 1).and_hms(12, 0, 0);
+        let at = |secs| start + Duration::seconds(secs);
+        let mut edits = EditCoalescer::new(Duration::seconds(10));
+
+        // five evaluations in as many seconds, each wanting a different body
+        let posted = (0..5)
+            .filter(|&i| {
+                let allowed = edits.offer(7, &format!("body {}", i), at(i));
+                if allowed {
+                    edits.edited(7, at(i));
+                }
+                allowed
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(posted, vec![0]);
+        assert!(edits.take_due(at(9)).is_empty());
+
+        // only the newest body goes out once things quiet down
+        assert_eq!(edits.take_due(at(10)), vec![(7, "body 4".to_string())]);
+        assert!(edits.take_due(at(30)).is_empty());
+
+        // other comments aren't held up, and a quiet comment can be edited straight away
+        assert!(edits.offer(8, "other", at(11)));
+        assert!(edits.offer(7, "body 5", at(30)));
+    }
+
+    #[test]
+    fn failed_edits_dont_hold_back_the_next() {
+        let start = NaiveDate::from_ymd(2018, 1, 1).and_hms(12, 0, 0);
+        let at = |secs| start + Duration::seconds(secs);
+        let mut edits = EditCoalescer::new(Duration::seconds(10));
+
+        // the first edit didn't go through, so the retry a second later isn't held back
+        assert!(edits.offer(7, "body 0", at(0)));
+        assert!(edits.offer(7, "body 1", at(1)));
+        edits.edited(7, at(1));
+        assert!(!edits.offer(7, "body 2", at(2)));
+    }
+
+    #[test]
+    fn failed_posts_are_retried_until_they_succeed() {
+        with_test_proposal(4, |conn, _, _, comment, proposal| {
+            let now = Utc::now().naive_utc();
+            diesel::insert_into(pending_comment_posts::table)
+                .values(&NewPendingCommentPost {
+                    fk_proposal: proposal.id,
+                    fk_target_comment: comment.id,
+                    body: "the latest status",
+                    attempts: 1,
+                    retry_after: now - Duration::minutes(1),
+                })
+                .execute(conn)
+                .unwrap();
+            let waiting = || {
+                pending_comment_posts::table
+                    .filter(pending_comment_posts::fk_proposal.eq(proposal.id))
+                    .load::<PendingCommentPost>(conn)
+                    .unwrap()
+            };
+
+            // GitHub is still down, so back off
+            retry_due_posts(conn, now, |_| Err(DashError::Misc(None))).unwrap();
+            let still_waiting = waiting();
+
+            // not due yet, so nothing is tried
+            let mut tried = vec![];
+            retry_due_posts(conn, now, |p| {
+                tried.push(p.body.clone());
+                Ok(())
+            })
+            .unwrap();
+            let not_due = tried.clone();
+
+            retry_due_posts(conn, now + Duration::hours(1), |p| {
+                tried.push(p.body.clone());
+                Ok(())
+            })
+            .unwrap();
+            let after_success = waiting();
+
+            assert_eq!(still_waiting.len(), 1);
+            assert_eq!(still_waiting[0].attempts, 2);
+            assert!(still_waiting[0].retry_after > now);
+            assert!(not_due.is_empty());
+            assert_eq!(tried, vec!["the latest status".to_string()]);
+            assert!(after_success.is_empty());
+        });
+    }
+
+    #[test]
+    fn resolved_concerns_can_be_raised_again() {
+        with_test_proposal(9, |conn, author, issue, comment, proposal| {
+            process_new_concern(author, issue, comment, "naming", None, None).unwrap();
+            process_resolve_concern(author, issue, comment, "naming", false).unwrap();
+            process_new_concern(author, issue, comment, "Naming", None, None).unwrap();
+
+            let concerns = fcp_concern::table
+                .filter(fcp_concern::fk_proposal.eq(proposal.id))
+                .order(fcp_concern::id)
+                .load::<FcpConcern>(conn)
+                .unwrap();
+            diesel::delete(fcp_concern::table.filter(fcp_concern::fk_proposal.eq(proposal.id)))
+                .execute(conn)
+                .unwrap();
+
+            assert_eq!(concerns.len(), 2);
+            assert_eq!(concerns[0].fk_resolved_comment, Some(comment.id));
+            assert_eq!(concerns[1].fk_resolved_comment, None);
+            assert_eq!(concerns[1].name, "Naming");
+        });
+    }
+
+    #[test]
+    fn concerns_stop_at_the_limit() {
+        with_test_proposal(14, |conn, author, issue, comment, proposal| {
+            let fake = use_fake_github(author);
+            let raise = |name: &str| {
+                raise_concern(author, issue, comment, name, None, None, Some(2)).unwrap()
+            };
+            raise("naming");
+            raise("performance");
+            raise("docs");
+            let refusals = fake.comments.lock().unwrap().clone();
+            // resolved concerns don't count towards the limit
+            process_resolve_concern(author, issue, comment, "naming", false).unwrap();
+            raise("docs");
+
+            let concerns = fcp_concern::table
+                .filter(fcp_concern::fk_proposal.eq(proposal.id))
+                .order(fcp_concern::id)
+                .load::<FcpConcern>(conn)
+                .unwrap();
+            diesel::delete(fcp_concern::table.filter(fcp_concern::fk_proposal.eq(proposal.id)))
+                .execute(conn)
+                .unwrap();
+            clear_proposal(conn, issue, &fake);
+
+            let names = concerns.iter().map(|c| &*c.name).collect::<Vec<_>>();
+            assert_eq!(names, vec!["naming", "performance", "docs"]);
+            assert_eq!(refusals.len(), 1);
+            assert!(refusals
+                .values()
+                .all(|(_, body)| body.contains("already has 2 unresolved concerns")));
+        });
+    }
+
+    #[test]
+    fn resolve_all_only_resolves_the_authors_concerns() {
+        with_test_proposal(3, |conn, author, issue, comment, proposal| {
+            let other = GitHubUser {
+                id: author.id - 500,
+                login: format!("{}-other", author.login),
+            };
+            crate::github::handle_user(conn, &other).unwrap();
+
+            process_new_concern(author, issue, comment, "naming", None, None).unwrap();
+            process_new_concern(author, issue, comment, "performance", None, None).unwrap();
+            process_new_concern(&other, issue, comment, "docs", None, None).unwrap();
+
+            let processed = process_resolve_all(author, issue, comment);
+            let concerns = fcp_concern::table
+                .filter(fcp_concern::fk_proposal.eq(proposal.id))
+                .load::<FcpConcern>(conn)
+                .unwrap();
+
+            diesel::delete(fcp_concern::table.filter(fcp_concern::fk_proposal.eq(proposal.id)))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(githubuser::table.find(other.id))
+                .execute(conn)
+                .unwrap();
+
+            processed.unwrap();
+            assert_eq!(concerns.len(), 3);
+            for concern in concerns {
+                if concern.fk_initiator == author.id {
+                    assert_eq!(concern.fk_resolved_comment, Some(comment.id));
+                } else {
+                    assert_eq!(concern.fk_resolved_comment, None);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn digest_table_formatting() {
+        assert_eq!(
+            format_fcp_digest(&[], &crate::config::test::config(), Utc::now().naive_utc()),
+            "<!-- rfcbot-fcp-digest -->\nNo final comment periods are currently running.\n"
+        );
+
+        let now = Utc::now().naive_utc();
+        let in_fcp = |id, disposition: &str, started_days_ago| FcpProposal {
+            id,
+            fk_issue: id,
+            disposition: disposition.to_string(),
+            fcp_start: Some(now - Duration::days(started_days_ago)),
+            ..proposal(ProposalState::InFcp)
+        };
+
+        let mut pr = issue();
+        pr.number = 7;
+        pr.is_pull_request = true;
+        pr.repository = "rust-lang/rust".to_string();
+
+        let running = vec![
+            (issue(), in_fcp(1, "merge", 3)),
+            (pr, in_fcp(2, "close", 12)),
+        ];
+
+        assert_eq!(
+            format_fcp_digest(&running, &crate::config::test::config(), now),
+            "<!-- rfcbot-fcp-digest -->
+These are currently in their final comment period:
+
+| Repository | Issue | Disposition | Days remaining |
+|---|---|---|---|
+| rust-lang/rfcs | [#42](https://github.com/rust-lang/rfcs/issues/42) | merge | 7 |
+| rust-lang/rust | [#7](https://github.com/rust-lang/rust/pull/7) | close | 0 |
+"
+        );
+    }
+
+    #[test]
+    fn fcp_end_override_drives_closure() {
+        let now = Utc::now().naive_utc();
+        let mut proposal = FcpProposal {
+            fcp_start: Some(now - Duration::days(3)),
+            ..proposal(ProposalState::InFcp)
+        };
+        assert!(!proposal_finished(&proposal, 10, now));
+
+        // ending early
+        proposal.fcp_end_override = Some(now - Duration::hours(1));
+        assert!(proposal_finished(&proposal, 10, now));
+
+        // and running long
+        proposal.fcp_end_override = Some(now + Duration::days(1));
+        assert!(!proposal_finished(&proposal, 1, now));
+
+        let start = proposal.fcp_start;
+        assert!(check_fcp_end(now + Duration::days(1), start, now).is_ok());
+        assert!(check_fcp_end(now - Duration::days(1), start, now).is_err());
+        assert!(check_fcp_end(now - Duration::days(4), None, now).is_err());
+        assert!(
+            check_fcp_end(now + Duration::days(1), Some(now + Duration::days(2)), now).is_err()
+        );
+    }
+
+    #[test]
+    fn fcps_close_on_their_repository_schedule() {
+        let now = Utc::now().naive_utc();
+        let config = Config {
+            repo_fcp_duration_days: btreemap! {
+                "rust-lang/cargo".to_string() => FcpDurations {
+                    merge: 3,
+                    close: 3,
+                    postpone: 3,
+                },
+            },
+            ..crate::config::test::config()
+        };
+        let proposal = FcpProposal {
+            fcp_start: Some(now - Duration::days(5)),
+            ..proposal(ProposalState::InFcp)
+        };
+        let in_repo = |repo: &str| Issue {
+            repository: repo.to_string(),
+            ..issue()
+        };
+        let finished = |repo, days_later| {
+            let now = now + Duration::days(days_later);
+            repo_proposal_finished(
+                &config,
+                &in_repo(repo),
+                &proposal,
+                FcpDisposition::Merge,
+                now,
+            )
+        };
+
+        assert!(finished("rust-lang/cargo", 0));
+        assert!(!finished("rust-lang/rfcs", 0));
+        assert!(!finished("rust-lang/rfcs", 4));
+        assert!(finished("rust-lang/rfcs", 5));
+        assert_eq!(shortest_fcp_length_days(&config), 3);
+    }
+
+    #[test]
+    fn durations_depend_on_disposition() {
+        let durations = FcpDurations {
+            merge: 10,
+            close: 7,
+            postpone: 5,
+        };
+        let now = Utc::now().naive_utc();
+        let finished = |disp, started_days_ago| {
+            let length = fcp_length_days(&durations, disp);
+            fcp_finished(now - Duration::days(started_days_ago), length, now)
+        };
+
+        assert!(!finished(FcpDisposition::Merge, 8));
+        assert!(finished(FcpDisposition::Merge, 10));
+        assert!(finished(FcpDisposition::Close, 8));
+        assert!(!finished(FcpDisposition::Close, 6));
+        assert!(finished(FcpDisposition::Postpone, 6));
+        assert!(!finished(FcpDisposition::Postpone, 4));
+
+        let issue = issue();
+        let author = user(1);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpAllReviewedNoConcerns {
+                author: &author,
+                status_comment_id: 1,
+                added_label: true,
+                length_days: fcp_length_days(&durations, FcpDisposition::Close),
+            },
+        );
+        assert!(comment
+            .body
+            .contains("The final comment period will last for **7 days**."));
+    }
+
+    #[test]
+    fn existing_labels_are_not_reapplied() {
+        let labels = vec![Label::FCP.as_str().to_string()];
+
+        let added_label = apply_label(Some(&labels[..]), Label::FCP.as_str(), true, || {
+            panic!("the label is already present")
+        })
+        .is_ok();
+        assert!(added_label);
+
+        apply_label(Some(&labels[..]), Label::PFCP.as_str(), false, || {
+            panic!("the label is already absent")
+        })
+        .unwrap();
+
+        let mut changed = false;
+        apply_label(Some(&labels[..]), Label::FCP.as_str(), false, || {
+            changed = true;
+            Ok(())
+        })
+        .unwrap();
+        assert!(changed);
+
+        let mut changed = false;
+        apply_label(None, Label::FCP.as_str(), true, || {
+            changed = true;
+            Ok(())
+        })
+        .unwrap();
+        assert!(changed);
+    }
+
+    #[test]
+    fn tracking_label_applied_on_creation() {
+        let mut proposal = FcpProposal {
+            tracking_label: Some("project-board".to_string()),
+            ..proposal(ProposalState::Pending)
+        };
+
+        let mut added = Vec::new();
+        apply_tracking_label(Some(&[][..]), &proposal, |label| {
+            added.push(label.to_string());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(added, vec!["project-board"]);
+
+        // already there
+        let labels = vec!["project-board".to_string()];
+        apply_tracking_label(Some(&labels[..]), &proposal, |_| {
+            panic!("the label is already present")
+        })
+        .unwrap();
+
+        // nothing to add without a tracking label
+        proposal.tracking_label = None;
+        apply_tracking_label(None, &proposal, |_| panic!("there's no tracking label")).unwrap();
+    }
+
+    #[test]
+    fn status_reports_version() {
+        let issue = issue();
+        let durations = FcpDurations::default();
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::Status {
+                version: VERSION,
+                commit: "abc123",
+                post_comments: false,
+                durations: &durations,
+            },
+        );
+
+        assert!(comment
+            .body
+            .starts_with(&format!("rfcbot version {} (commit abc123)", VERSION)));
+        assert!(comment.body.contains("* posting comments: off"));
+        assert!(comment.body.contains("merge 10 days, close 10 days"));
+    }
+
+    #[test]
+    fn echo_reports_parse() {
+        let issue = issue();
+        let author = user(2);
+        let command = RfcBotCommand::Snooze(7);
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ParseEcho {
+                author: &author,
+                text: "snooze 7",
+                parsed: Some(&command),
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user2, I parsed `snooze 7` as:\n\n```\nSnooze(7)\n```"
+        );
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ParseEcho {
+                author: &author,
+                text: "frobnicate",
+                parsed: None,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user2, I could not parse `frobnicate` as a command."
+        );
+    }
+
+    #[test]
+    fn outstanding_concerns_are_listed_first() {
+        let issue = issue();
+        let initiator = user(1);
+        let reviewers = vec![review(1, true), review(2, false)];
+        let concern = |id, name: &str, resolved| FcpConcern {
+            id,
+            fk_proposal: 1,
+            fk_initiator: 2,
+            fk_resolved_comment: resolved,
+            name: name.to_string(),
+            fk_initiating_comment: 10 + id,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: None,
+        };
+        let concerns = vec![
+            (user(2), concern(1, "aardvark", Some(20))),
+            (user(2), concern(3, "zebra", None)),
+            (user(2), concern(2, "yak", None)),
+            (user(2), concern(4, "bison", Some(21))),
+            (user(2), concern(3, "zebra", None)),
+        ];
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviewers,
+                concerns: &concerns,
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        );
+        let listed = comment
+            .body
+            .lines()
+            .filter(|line| line.starts_with("* ") && !line.starts_with("* ["))
+            .map(|line| line.split(' ').nth(1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(listed, vec!["yak", "zebra", "~~aardvark~~", "~~bison~~"]);
+    }
+
+    #[test]
+    fn concern_descriptions_are_nested() {
+        let issue = issue();
+        let initiator = user(1);
+        let reviewers = vec![review(1, true), review(2, false)];
+        let concern = |id, name: &str, description: Option<&str>| FcpConcern {
+            id,
+            fk_proposal: 1,
+            fk_initiator: 2,
+            fk_resolved_comment: None,
+            name: name.to_string(),
+            fk_initiating_comment: 10 + id,
+            fk_directed_at: None,
+            description: description.map(String::from),
+            fk_overridden_by: None,
+        };
+        let concerns = vec![
+            (
+                user(2),
+                concern(1, "naming", Some("    too long\n\n    * [x] @user2")),
+            ),
+            (user(2), concern(2, "docs", None)),
+        ];
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviewers,
+                concerns: &concerns,
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        );
+        assert!(comment.body.contains(
+            "* naming (https://github.com/rust-lang/rfcs/issues/42#issuecomment-11)\n\
+             \x20 too long\n\n\
+             \x20 * [x] @user2\n\
+             * docs (https://github.com/rust-lang/rfcs/issues/42#issuecomment-12)\n"
+        ));
+
+        // a description can't check anyone's box
+        let stored = status_comment(comment.body);
+        assert_eq!(
+            parse_ticky_boxes("proposal", 1, &stored).collect::<Vec<_>>(),
+            vec!["user1"]
+        );
+    }
+
+    #[test]
+    fn concern_list_shows_resolution() {
+        let issue = issue();
+        let author = user(3);
+        let concern = |id, name: &str, resolved| FcpConcern {
+            id,
+            fk_proposal: 1,
+            fk_initiator: id,
+            fk_resolved_comment: resolved,
+            name: name.to_string(),
+            fk_initiating_comment: 10 + id,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: None,
+        };
+        let concerns = vec![
+            (user(1), concern(1, "naming", Some(20))),
+            (user(2), concern(2, "performance", None)),
+        ];
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ConcernList {
+                author: &author,
+                concerns: &concerns,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user3, these are the concerns on this proposal:\n\n\
+             * performance raised by @user2 \
+             (https://github.com/rust-lang/rfcs/issues/42#issuecomment-12)\n\
+             * ~~naming~~ raised by @user1, resolved by \
+             https://github.com/rust-lang/rfcs/issues/42#issuecomment-20\n"
+        );
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ConcernList {
+                author: &author,
+                concerns: &[],
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user3, no concerns have been raised on this proposal."
+        );
+    }
+
+    #[test]
+    fn feedback_list_shows_open_and_resolved_requests() {
+        let issue = issue();
+        let author = user(4);
+        let request = |id, feedback_comment| FeedbackRequest {
+            id,
+            fk_initiator: 1,
+            fk_requested: id,
+            fk_issue: issue.id,
+            fk_feedback_comment: feedback_comment,
+        };
+        let requests = vec![
+            (user(1), user(2), request(2, Some(30))),
+            (user(1), user(3), request(3, None)),
+        ];
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FeedbackList {
+                author: &author,
+                requests: &requests,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user4, this is the feedback requested on this issue:\n\n\
+             * ~~@user2~~ asked by @user1, resolved by \
+             https://github.com/rust-lang/rfcs/issues/42#issuecomment-30\n\
+             * @user3 asked by @user1, still waiting\n"
+        );
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FeedbackList {
+                author: &author,
+                requests: &[],
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user4, nobody has been asked for feedback on this issue."
+        );
+    }
+
+    #[test]
+    fn reviewed_roster_lists_checked_boxes() {
+        let issue = issue();
+        let author = user(4);
+        let reviews = vec![review(1, true), review(2, false), review(3, true)];
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ReviewedRoster {
+                author: &author,
+                reviewers: &reviews,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user4, this proposal has been reviewed by:\n\n* @user1\n* @user3\n"
+        );
+
+        let unreviewed = vec![review(2, false)];
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ReviewedRoster {
+                author: &author,
+                reviewers: &unreviewed,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user4, nobody has reviewed this proposal yet."
+        );
+    }
+
+    #[test]
+    fn history_is_a_dated_timeline() {
+        let day = |d| NaiveDate::from_ymd(2026, 3, d).and_hms(12, 0, 0);
+        let mut proposal = FcpProposal {
+            fk_initiating_comment: 101,
+            fk_bot_tracking_comment: 100,
+            fcp_start: Some(day(8)),
+            ..proposal(ProposalState::Closed)
+        };
+        let concern = |id, name: &str, raised, resolved, overridden| FcpConcern {
+            id,
+            fk_proposal: 1,
+            fk_initiator: 2,
+            fk_resolved_comment: resolved,
+            name: name.to_string(),
+            fk_initiating_comment: raised,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: overridden,
+        };
+        let concerns = vec![
+            (user(2), concern(1, "naming", 102, Some(104), None)),
+            (user(3), concern(2, "docs", 103, Some(105), Some(4))),
+            (user(3), concern(3, "lost", 999, None, None)),
+        ];
+        let dates = btreemap! {
+            101 => day(1),
+            102 => day(1),
+            103 => day(3),
+            104 => day(6),
+            105 => day(5),
+        };
+        let date_of = |id| dates.get(&id).cloned();
+
+        let entries = proposal_history(&proposal, &user(1), &concerns, date_of, 10);
+        let issue = issue();
+        let author = user(5);
+        let mut reviews = vec![review(1, true), review(2, false), review(3, true)];
+        reviews[2].1.recorded_by = Some("user4".to_string());
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ProposalHistory {
+                author: &author,
+                entries: &entries,
+                reviewers: &reviews,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user5, here's how this proposal has gone so far:\n\n\
+             * 2026-03-01: @user1 proposed to merge this\n\
+             * 2026-03-01: @user2 raised the concern `naming`\n\
+             * 2026-03-03: @user3 raised the concern `docs`\n\
+             * 2026-03-05: the concern `docs` was resolved by an administrator\n\
+             * 2026-03-06: the concern `naming` was resolved\n\
+             * 2026-03-08: the final comment period started\n\
+             * 2026-03-18: the final comment period ended\n\
+             \nReviewed by (review times aren't tracked):\n\n\
+             * @user1\n\
+             * @user3 (recorded by @user4)\n"
+        );
+
+        // a running FCP hasn't ended yet
+        proposal.state = ProposalState::InFcp.repr().to_string();
+        let entries = proposal_history(&proposal, &user(1), &[], date_of, 10);
+        assert_eq!(
+            entries.last().map(|e| e.event.as_str()),
+            Some("the final comment period started")
+        );
+    }
+
+    #[test]
+    fn pushes_reset_other_reviews() {
+        let mut reviews = vec![review(1, true), review(2, true), review(3, false)];
+        reviews.push(review(4, false));
+        reviews[3].1.waived = true;
+
+        let reset = reviews_to_reset(1, &reviews)
+            .into_iter()
+            .map(|&(ref member, _)| member.login.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(reset, vec!["user2"]);
+
+        let issue = issue();
+        let reviewers = vec![user(2), user(3)];
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ReviewsReset {
+                reviewers: &reviewers,
+            },
+        );
+        assert!(comment
+            .body
+            .starts_with("@user2, @user3: new commits have been pushed"));
+    }
+
+    #[test]
+    fn fcp_start_is_announced_once() {
+        let issue = issue();
+        let author = user(1);
+        let start = |status_comment_id| {
+            RfcBotComment::new(
+                &issue,
+                CommentType::FcpAllReviewedNoConcerns {
+                    author: &author,
+                    status_comment_id,
+                    added_label: true,
+                    length_days: 10,
+                },
+            )
+            .body
+        };
+
+        // the first pass finds nothing and posts
+        let mut bodies = vec!["Looks good to me!".to_string()];
+        assert!(!bodies.iter().any(|b| announces_fcp_start(&issue, 5, b)));
+
+        // the second one sees that comment, even if a footer was added
+        bodies.push(format!("{}\n\n---\nA footer", start(5)));
+        assert!(bodies.iter().any(|b| announces_fcp_start(&issue, 5, b)));
+
+        // but another proposal's start doesn't count
+        assert!(!announces_fcp_start(&issue, 6, &start(5)));
+    }
+
+    #[test]
+    fn fcp_start_is_sent_to_the_notify_webhook() {
+        let issue = issue();
+        let proposal = FcpProposal {
+            id: 3,
+            fcp_start: Some(Utc::now().naive_utc()),
+            ..proposal(ProposalState::InFcp)
+        };
+
+        let mut sent = None;
+        send_notification(
+            Some("https://chat.example/hook"),
+            &issue,
+            &proposal,
+            |url, payload| {
+                sent = Some((url.to_string(), serde_json::to_value(payload)?));
+                Ok(())
+            },
+        );
+        assert_eq!(
+            sent,
+            Some((
+                "https://chat.example/hook".to_string(),
+                json!({
+                    "proposal_id": 3,
+                    "repository": "rust-lang/rfcs",
+                    "number": 42,
+                    "disposition": "merge",
+                    "state": "in-fcp",
+                })
+            ))
+        );
+
+        // nothing is sent unless a webhook is configured, and failures aren't fatal
+        send_notification(None, &issue, &proposal, |_, _| {
+            panic!("no webhook to send to")
+        });
+        send_notification(
+            Some("https://chat.example/hook"),
+            &issue,
+            &proposal,
+            |_, _| Err(DashError::Misc(None)),
+        );
+    }
+
+    #[test]
+    fn errors_are_reported_when_configured() {
+        let why = DashError::Misc(Some("the database fell over".to_string()));
+
+        let mut sent = None;
+        send_error_report(
+            Some("https://errors.example/report"),
+            "Unable to evaluate running fcps",
+            &why,
+            |url, report| {
+                sent = Some((url.to_string(), serde_json::to_value(report)?));
+                Ok(())
+            },
+        );
+        assert_eq!(
+            sent,
+            Some((
+                "https://errors.example/report".to_string(),
+                json!({
+                    "context": "Unable to evaluate running fcps",
+                    "error": "Misc(Some(\"the database fell over\"))",
+                    "version": VERSION,
+                })
+            ))
+        );
+
+        // nothing is sent unless a URL is configured, and failing to report isn't fatal
+        send_error_report(None, "Unable to evaluate running fcps", &why, |_, _| {
+            panic!("nowhere to report to")
+        });
+        send_error_report(
+            Some("https://errors.example/report"),
+            "Unable to evaluate running fcps",
+            &why,
+            |_, _| Err(DashError::Misc(None)),
+        );
+    }
+
+    #[test]
+    fn proposal_id_links_tracking_comment() {
+        let issue = issue();
+        let author = user(1);
+        let proposal = FcpProposal {
+            id: 12,
+            fk_bot_tracking_comment: 345,
+            ..proposal(ProposalState::Pending)
+        };
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ProposalId {
+                author: &author,
+                proposal: Some(&proposal),
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user1, this issue's proposal has id 12. Its tracking comment is \
+             https://github.com/rust-lang/rfcs/issues/42#issuecomment-345."
+        );
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ProposalId {
+                author: &author,
+                proposal: None,
+            },
+        );
+        assert_eq!(comment.body, "@user1, there's no proposal on this issue.");
+    }
+
+    #[test]
+    fn next_steps_depend_on_the_state() {
+        let issue = issue();
+        let author = user(1);
+        let now = Utc::now().naive_utc();
+        let in_state = |state: ProposalState| FcpProposal {
+            id: 12,
+            fk_bot_tracking_comment: 345,
+            ..proposal(state)
+        };
+        let concern = |id, name: &str, resolved| FcpConcern {
+            id,
+            fk_proposal: 12,
+            fk_initiator: 2,
+            fk_resolved_comment: resolved,
+            name: name.to_string(),
+            fk_initiating_comment: 10 + id,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: None,
+        };
+        let reviewers = vec![review(1, true), review(2, false), review(3, false)];
+        let concerns = vec![
+            (user(2), concern(1, "naming", None)),
+            (user(2), concern(2, "docs", Some(20))),
+        ];
+        let next_steps = |proposal: &FcpProposal,
+                          reviewers: &[(GitHubUser, FcpReviewRequest)],
+                          concerns: &[(GitHubUser, FcpConcern)],
+                          fcp_end| {
+            RfcBotComment::new(
+                &issue,
+                CommentType::NextSteps {
+                    author: &author,
+                    proposal: Some(proposal),
+                    reviewers,
+                    concerns,
+                    fcp_end,
+                },
+            )
+            .body
+        };
+
+        let pending = in_state(ProposalState::Pending);
+        assert_eq!(
+            next_steps(&pending, &reviewers, &concerns, None),
+            "@user1, before its final comment period can start, this proposal is waiting \
+             on:\n\n\
+             * [ ] reviews from [user2](https://github.com/user2), \
+             [user3](https://github.com/user3)\n\
+             * [ ] resolving the concerns `naming`\n"
+        );
+        assert!(
+            next_steps(&pending, &[review(1, true)], &concerns[1..], None)
+                .contains("the final comment period should start soon.")
+        );
+        assert!(
+            next_steps(&in_state(ProposalState::OnHold), &reviewers, &[], None)
+                .starts_with("@user1, this proposal is on hold")
+        );
+
+        let end = now + Duration::days(3);
+        assert_eq!(
+            next_steps(&in_state(ProposalState::InFcp), &reviewers, &[], Some(end)),
+            format!(
+                "@user1, this proposal is in its final comment period, which ends on {}. \
+                 If you have concerns, please speak up before it ends!",
+                end.format("%Y-%m-%d")
+            )
+        );
+
+        assert!(
+            next_steps(&in_state(ProposalState::Closed), &reviewers, &[], None)
+                .ends_with("final comment period is complete, so there's nothing left to do here.")
+        );
+        assert!(
+            next_steps(&in_state(ProposalState::Cancelled), &reviewers, &[], None)
+                .contains("was cancelled")
+        );
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::NextSteps {
+                author: &author,
+                proposal: None,
+                reviewers: &[],
+                concerns: &[],
+                fcp_end: None,
+            },
+        );
+        assert_eq!(comment.body, "@user1, there's no proposal on this issue.");
+    }
+
+    #[test]
+    fn closed_issue_proposals_are_split_off() {
+        let pending = |id| FcpProposal {
+            id,
+            fk_issue: id,
+            ..proposal(ProposalState::Pending)
+        };
+        let mut closed = issue();
+        closed.open = false;
+
+        let (open, closed) = split_closed(vec![
+            (pending(1), issue()),
+            (pending(2), closed),
+            (pending(3), issue()),
+        ]);
+        let ids = |proposals: &[(FcpProposal, Issue)]| {
+            proposals.iter().map(|(p, _)| p.id).collect::<Vec<_>>()
+        };
+
+        // only open issues go on to have their reviews processed
+        assert_eq!(ids(&open), vec![1, 3]);
+        assert_eq!(ids(&closed), vec![2]);
+    }
+
+    #[test]
+    fn ignored_authors_are_skipped() {
+        let ignored = btreeset! { "rfcbot".to_string(), "bors".to_string() };
+        assert!(is_ignored_author("bors", &ignored));
+        assert!(is_ignored_author("RfcBot", &ignored));
+        assert!(!is_ignored_author("thor", &ignored));
+        assert!(!is_ignored_author("thor", &BTreeSet::new()));
+    }
+
+    #[test]
+    fn own_comments_are_skipped() {
+        assert!(is_own_comment("rust-highfive", "rust-highfive"));
+        assert!(is_own_comment("Rust-Highfive", "rust-highfive"));
+        assert!(!is_own_comment("thor", "rust-highfive"));
+        // the login couldn't be looked up
+        assert!(!is_own_comment("thor", ""));
+        assert!(!is_own_comment("", ""));
+    }
+
+    #[test]
+    fn linked_issues_found_in_description() {
+        assert_eq!(
+            linked_issue_number("Does a thing.\n\nTracking issue: #123"),
+            Some(123)
+        );
+        assert_eq!(linked_issue_number("  tracking: #7, probably"), Some(7));
+        assert_eq!(linked_issue_number("Fixes #123"), None);
+        assert_eq!(linked_issue_number("Tracking issue: TBD"), None);
+    }
+
+    #[test]
+    fn conflicting_linked_proposal_warns() {
+        let linked = |disposition: FcpDisposition, state: ProposalState| FcpProposal {
+            disposition: disposition.repr().to_string(),
+            ..proposal(state)
+        };
+
+        let close = linked(FcpDisposition::Close, ProposalState::InFcp);
+        assert_eq!(
+            conflicting_disposition(&close, FcpDisposition::Merge).unwrap(),
+            Some(FcpDisposition::Close)
+        );
+        assert_eq!(
+            conflicting_disposition(&close, FcpDisposition::Close).unwrap(),
+            None
+        );
+        let finished = linked(FcpDisposition::Close, ProposalState::Closed);
+        assert_eq!(
+            conflicting_disposition(&finished, FcpDisposition::Merge).unwrap(),
+            None
+        );
 
-        RfcBotComment {
-            issue,
-            body,
-            comment_type,
-        }
+        let issue = issue();
+        let author = user(1);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::ConflictingLinkedProposal {
+                author: &author,
+                linked_issue: 123,
+                disposition: FcpDisposition::Merge,
+                linked_disposition: FcpDisposition::Close,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user1, heads up: the tracking issue #123 already has a proposal to close, \
+             which conflicts with this proposal to merge."
+        );
     }
 
-    fn couldnt_add_label<'b>(msg: &mut String, author: &'b GitHubUser, label: Label) {
-        msg.push_str("\n\n*psst @");
-        msg.push_str(&author.login);
-        msg.push_str(", I wasn't able to add the `");
-        msg.push_str(label.as_str());
-        msg.push_str("` label, please do so.*");
+    #[test]
+    fn almost_ready_heads_up_formatting() {
+        let issue = issue();
+        let reviewer = user(2);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpAlmostReady {
+                blocker: LastBlocker::Review(&reviewer),
+                status_comment_id: 7,
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "@user2, yours is the last review needed before this enters its final comment \
+             period, as per the [review above]\
+             (https://github.com/rust-lang/rfcs/issues/42#issuecomment-7)."
+        );
+
+        let author = user(3);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpAlmostReady {
+                blocker: LastBlocker::Concern {
+                    author: &author,
+                    name: "naming",
+                },
+                status_comment_id: 7,
+            },
+        );
+        assert!(comment.body.starts_with(
+            "@user3, your concern `naming` is the last one outstanding before this enters"
+        ));
     }
 
-    fn format(issue: &Issue, comment_type: &CommentType<'_>) -> String {
-        match *comment_type {
-            CommentType::QuestionAsked {
-                initiator,
-                respondents,
-                question,
-                ref teams,
-            } => {
-                let mut msg = String::from("Team member @");
-                msg.push_str(&initiator.login);
-                msg.push_str(" has asked teams: ");
-                msg.extend(teams.iter().cloned().intersperse(", "));
-                msg.push_str(", for consensus on: \n > ");
-                msg.push_str(question);
-                msg.push_str("\n\n");
-                format_ticky_boxes(
-                    &mut msg,
-                    respondents.iter().map(|(m, rr)| (m, rr.responded)),
-                );
-                msg
-            }
+    #[test]
+    fn almost_ready_heads_up_sent_once() {
+        let initiator = user(1);
+        let mut proposal = proposal(ProposalState::Pending);
 
-            CommentType::FcpProposed(initiator, disposition, reviewers, concerns) => {
-                let mut msg = String::from("Team member @");
-                msg.push_str(&initiator.login);
-                msg.push_str(" has proposed to ");
-                msg.push_str(disposition.repr());
-                msg.push_str(" this. The next step is review by the rest of the tagged ");
-                msg.push_str("team members:\n\n");
+        let reviews = vec![review(2, true), review(3, false)];
+        let reviewer = user(3);
+        assert_eq!(
+            last_blocker(&proposal, &initiator, &reviews, &[], 1, true),
+            Some(LastBlocker::Review(&reviewer))
+        );
 
-                format_ticky_boxes(&mut msg, reviewers.iter().map(|(m, rr)| (m, rr.reviewed)));
+        proposal.almost_ready_notified = true;
+        assert_eq!(
+            last_blocker(&proposal, &initiator, &reviews, &[], 1, true),
+            None
+        );
 
-                if concerns.is_empty() {
-                    msg.push_str("\nNo concerns currently listed.\n");
-                } else {
-                    msg.push_str("\nConcerns:\n\n");
-                }
+        // more than one outstanding review isn't "almost" ready
+        proposal.almost_ready_notified = false;
+        let reviews = vec![review(2, true), review(3, false), review(4, false)];
+        assert_eq!(
+            last_blocker(&proposal, &initiator, &reviews, &[], 1, true),
+            None
+        );
+    }
 
-                for &(_, ref concern) in concerns {
-                    if let Some(resolved_comment_id) = concern.fk_resolved_comment {
-                        msg.push_str("* ~~");
-                        msg.push_str(&concern.name);
-                        msg.push_str("~~ resolved by ");
-                        Self::add_comment_url(issue, &mut msg, resolved_comment_id);
-                        msg.push_str("\n");
-                    } else {
-                        msg.push_str("* ");
-                        msg.push_str(&concern.name);
-                        msg.push_str(" (");
-                        Self::add_comment_url(issue, &mut msg, concern.fk_initiating_comment);
-                        msg.push_str(")\n");
-                    }
-                }
+    #[test]
+    fn footers_are_chosen_by_repo() {
+        let rfcs_footer = "See the [RFC process](https://rust-lang.github.io/rfcs/).";
+        let footers = btreemap! {
+            "rust-lang/rfcs".to_string() => rfcs_footer.to_string(),
+            DEFAULT_COMMENT_FOOTER.to_string() => "Thanks!".to_string(),
+        };
 
-                msg.push_str("\nOnce a majority of reviewers approve (and at most 2 approvals are outstanding), ");
-                msg.push_str("this will enter its final comment period. ");
-                msg.push_str("If you spot a major issue that hasn't been raised ");
-                msg.push_str("at any point in this process, please speak up!\n");
+        let issue = issue();
+        let body = RfcBotComment::new(&issue, CommentType::FcpProposalCancelled(&user(1))).body;
+        let with_rfcs_footer = with_footer(&body, &footers, &issue.repository);
+        assert_eq!(
+            with_rfcs_footer,
+            format!(
+                "@user1 proposal cancelled.\n\n<!-- rfcbot-footer -->\n{}",
+                rfcs_footer
+            )
+        );
 
-                msg.push_str("\nSee [this document](");
-                msg.push_str("https://github.com/rust-lang/rfcbot-rs/blob/master/README.md");
-                msg.push_str(") for info about what commands tagged team members can give me.");
+        // footers replace each other rather than piling up when a comment is edited
+        assert_eq!(
+            with_footer(&with_rfcs_footer, &footers, "rust-lang/rust"),
+            "@user1 proposal cancelled.\n\n<!-- rfcbot-footer -->\nThanks!"
+        );
+        assert_eq!(
+            with_footer(&with_rfcs_footer, &footers, &issue.repository),
+            with_rfcs_footer
+        );
+        assert_eq!(with_footer(&body, &BTreeMap::new(), "rust-lang/rust"), body);
+    }
 
-                msg
-            }
+    #[test]
+    fn comment_urls_distinguish_pull_requests() {
+        let mut issue = issue();
+        let mut url = String::new();
+        RfcBotComment::add_comment_url(&issue, &mut url, 99);
+        assert_eq!(
+            url,
+            "https://github.com/rust-lang/rfcs/issues/42#issuecomment-99"
+        );
 
-            CommentType::FcpProposalCancelled(initiator) => {
-                format!("@{} proposal cancelled.", initiator.login)
-            }
+        issue.is_pull_request = true;
+        let mut url = String::new();
+        RfcBotComment::add_comment_url(&issue, &mut url, 99);
+        assert_eq!(
+            url,
+            "https://github.com/rust-lang/rfcs/pull/42#issuecomment-99"
+        );
+    }
 
-            CommentType::FcpAllReviewedNoConcerns {
-                author,
-                status_comment_id,
-                added_label,
-            } => {
-                let mut msg = String::new();
+    #[test]
+    fn body_commands_are_attributed_to_the_issue_author() {
+        use crate::teams::test::TEST_SETUP;
+
+        let mut issue = issue();
+        issue.body = "We should do this.\n\n@rfcbot fcp merge".to_string();
+        let comment = body_comment(&issue);
+        assert_eq!(comment.fk_user, issue.fk_user);
+        assert_eq!(comment.fk_issue, issue.id);
+
+        let body = normalize_command_text(&comment.body);
+        let commands = RfcBotCommand::from_str_all(&TEST_SETUP, &body).collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            vec![RfcBotCommand::FcpPropose(FcpDisposition::Merge)]
+        );
 
-                msg.push_str(":bell: **This is now entering its final comment period**, ");
-                msg.push_str("as per the [review above](");
-                Self::add_comment_url(issue, &mut msg, status_comment_id);
-                msg.push_str("). :bell:");
+        // an edit only brings in the command lines it adds
+        let edited = format!("{}\n\nFixed a typo.\n@rfcbot concern naming", issue.body);
+        let added = added_lines(&issue.body, &edited);
+        assert_eq!(added, "Fixed a typo.\n@rfcbot concern naming");
+        let commands = RfcBotCommand::from_str_all(&TEST_SETUP, &added).collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            vec![RfcBotCommand::NewConcern("naming", None, None)]
+        );
+        assert_eq!(
+            added_lines(&edited, &edited.replace("this", "that")),
+            "We should do that."
+        );
 
-                if !added_label {
-                    Self::couldnt_add_label(&mut msg, author, Label::FCP);
-                }
+        let mut url = String::new();
+        RfcBotComment::add_comment_url(&issue, &mut url, comment.id);
+        assert_eq!(url, "https://github.com/rust-lang/rfcs/issues/42");
+    }
 
-                msg
-            }
+    #[test]
+    fn privileged_commands_need_an_admin() {
+        let privileged = btreeset! { "cancel".to_string(), "waive".to_string() };
+        let admins = btreeset! { "thor".to_string() };
+
+        assert!(command_allowed("cancel", "thor", &privileged, &admins));
+        assert!(!command_allowed("cancel", "loki", &privileged, &admins));
+        assert!(command_allowed("reviewed", "loki", &privileged, &admins));
+        assert!(command_allowed(
+            "fcp-end-now",
+            "thor",
+            &btreeset! {},
+            &admins
+        ));
+        assert!(!command_allowed(
+            "fcp-end-now",
+            "loki",
+            &btreeset! {},
+            &admins
+        ));
+        assert!(command_allowed(
+            "reviewed-for",
+            "thor",
+            &btreeset! {},
+            &admins
+        ));
+        assert!(!command_allowed(
+            "reviewed-for",
+            "loki",
+            &btreeset! {},
+            &admins
+        ));
+        assert!(command_allowed("cancel", "loki", &BTreeSet::new(), &admins));
+
+        assert_eq!(RfcBotCommand::FcpCancel.name(), "cancel");
+        assert_eq!(RfcBotCommand::Waive("thor").name(), "waive");
+
+        let issue = issue();
+        let author = user(2);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::CommandRefused {
+                author: &author,
+                command: "cancel",
+            },
+        );
+        assert_eq!(
+            comment.body,
+            "Sorry @user2, only rfcbot's administrators can use the `cancel` command here."
+        );
+    }
 
-            CommentType::FcpWeekPassed {
-                author,
-                added_label,
-                status_comment_id,
-                disposition,
-            } => {
-                let mut msg = String::new();
-                msg.push_str("The final comment period, with a disposition to **");
-                msg.push_str(disposition.repr());
-                msg.push_str("**, as per the [review above](");
-                Self::add_comment_url(issue, &mut msg, status_comment_id);
-                msg.push_str("), is now **complete**.");
+    #[test]
+    fn bootstrapped_proposals_follow_the_labels() {
+        let now = Utc::now();
+        let event = |label: &str, added, actor: &str, hours_ago| LabelEvent {
+            label: label.to_string(),
+            added,
+            actor: Some(actor.to_string()),
+            created_at: now - Duration::hours(hours_ago),
+        };
 
-                msg.push_str(
-                    "\n\nAs the automated representative of the governance process, \
-                     I would like to thank the author for their work and everyone else \
-                     who contributed.",
-                );
+        let events = vec![
+            event("final-comment-period", true, "thor", 48),
+            event("final-comment-period", false, "loki", 36),
+            event("T-avengers", true, "loki", 30),
+            event("final-comment-period", true, "odin", 24),
+        ];
+        let labelled = last_labelled(&events, "final-comment-period").unwrap();
+        assert_eq!(labelled.actor.as_deref(), Some("odin"));
+        assert_eq!(labelled.created_at, now - Duration::hours(24));
+        assert!(last_labelled(&events, "postponed").is_none());
+
+        let labels = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            labelled_disposition(&labels(&["final-comment-period", "disposition-close"])),
+            FcpDisposition::Close
+        );
+        assert_eq!(
+            labelled_disposition(&labels(&["disposition-postpone"])),
+            FcpDisposition::Postpone
+        );
+        assert_eq!(
+            labelled_disposition(&labels(&["final-comment-period"])),
+            FcpDisposition::Merge
+        );
+    }
 
-                match disposition {
-                    FcpDisposition::Merge => {
-                        msg.push_str("\n\nThis will be merged soon.");
-                    }
-                    FcpDisposition::Close if can_ffcp_close(issue) => {
-                        msg.push_str("\n\nThis is now closed.");
-                    }
-                    FcpDisposition::Postpone if can_ffcp_postpone(issue) => {
-                        msg.push_str("\n\nThis is now postponed.");
-                    }
-                    _ => {}
-                }
+    #[test]
+    fn team_labels_added_after_proposal() {
+        let proposed = Utc::now();
+        let event = |label: &str, added, hours_after_proposal| LabelEvent {
+            label: label.to_string(),
+            added,
+            actor: Some("thor".to_string()),
+            created_at: proposed + Duration::hours(hours_after_proposal),
+        };
 
-                if !added_label {
-                    Self::couldnt_add_label(&mut msg, author, Label::FFCP);
-                }
+        let events = vec![
+            event("T-avengers", true, -5),
+            event("T-justice-league", true, 2),
+            event("T-defenders", true, 3),
+            event("T-defenders", false, 4),
+        ];
+        assert_eq!(
+            labels_added_since(&events, proposed),
+            btreemap! { "T-justice-league" => Some("thor") }
+        );
 
-                msg
-            }
-        }
+        let members = vec![user(1), user(2), user(3)];
+        let reviews = vec![review(1, true), review(3, false)];
+        assert_eq!(missing_reviewers(&members, &reviews), vec![&members[1]]);
     }
 
-    fn add_comment_url(issue: &Issue, msg: &mut String, comment_id: i32) {
-        let url = format!(
-            "https://github.com/{repo}/{typ}/{number}#issuecomment-{id}",
-            repo = issue.repository,
-            typ = if issue.is_pull_request {
-                "pull"
-            } else {
-                "issues"
-            },
-            number = issue.number,
-            id = comment_id,
+    #[test]
+    fn team_label_added_mid_proposal() {
+        use crate::teams::test::TEST_SETUP;
+
+        let everyone = team_member_logins(&TEST_SETUP, |_| true);
+        let users = |logins: Vec<String>| {
+            logins
+                .into_iter()
+                .map(|login| GitHubUser {
+                    id: everyone.iter().position(|l| *l == login).unwrap() as i32,
+                    login,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the proposal was made while only the avengers were tagged
+        let mut labels = vec!["T-avengers".to_string()];
+        let reviews = users(team_member_logins(&TEST_SETUP, |l| labels.contains(l)))
+            .into_iter()
+            .map(|u| {
+                let (_, request) = review(u.id, false);
+                (u, request)
+            })
+            .collect::<Vec<_>>();
+
+        let members = users(team_member_logins(&TEST_SETUP, |l| labels.contains(l)));
+        assert!(missing_reviewers(&members, &reviews).is_empty());
+
+        labels.push("justice-league".to_string());
+        let members = users(team_member_logins(&TEST_SETUP, |l| labels.contains(l)));
+        let mut missing = missing_reviewers(&members, &reviews)
+            .into_iter()
+            .map(|u| u.login.as_str())
+            .collect::<Vec<_>>();
+        missing.sort();
+        assert_eq!(
+            missing,
+            vec!["aquaman", "batman", "superman", "theflash", "wonderwoman"]
         );
-        msg.push_str(&url);
     }
 
-    fn maybe_add_pfcp_label(&self) {
-        if let CommentType::FcpProposed(_, disposition, ..) = self.comment_type {
-            let _ = self.issue.add_label(Label::PFCP);
-            let _ = self.issue.add_label(disposition.label());
-        }
+    #[test]
+    fn preview_fcp_merge() {
+        use crate::teams::test::TEST_SETUP;
+
+        let config = crate::config::test::config();
+        let author = GitHubUser {
+            id: 0,
+            login: "thor".to_string(),
+        };
+
+        let previews = preview_command(
+            &TEST_SETUP,
+            &config,
+            &issue(),
+            &author,
+            "Let's do this.\n\n@rfcbot fcp merge\n",
+        );
+
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].command, "FcpPropose(Merge)");
+        let comment = previews[0].comment.as_ref().unwrap();
+        assert!(comment.contains("Team member [thor](https://github.com/thor) has proposed"));
+        assert!(comment.contains("* [x] [thor]"));
+        assert!(comment.contains("* [ ] [hulk]"));
     }
 
-    fn post(&self, existing_comment: Option<i32>) -> DashResult<CommentFromJson> {
-        use crate::config::CONFIG;
+    #[test]
+    fn repo_team_members_become_reviewers() {
+        use crate::teams::test::LOCAL_TEAM_SETUP;
 
-        if CONFIG.post_comments {
-            if self.issue.open {
-                if let Some(comment_id) = existing_comment {
-                    self.maybe_add_pfcp_label();
-                    GH.edit_comment(&self.issue.repository, comment_id, &self.body)
-                } else {
-                    GH.new_comment(&self.issue.repository, self.issue.number, &self.body)
-                }
-            } else {
-                info!(
-                    "Skipping comment to {}#{}, the issue is no longer open",
-                    self.issue.repository, self.issue.number
-                );
+        let issue = Issue {
+            repository: "octocat/gadget".to_string(),
+            labels: vec![],
+            ..issue()
+        };
+        let author = GitHubUser {
+            id: 0,
+            login: "octocat".to_string(),
+        };
 
-                throw!(DashError::Misc(None))
+        assert!(team_responsible(&LOCAL_TEAM_SETUP, &issue, "maintainers"));
+        assert!(!team_responsible(&LOCAL_TEAM_SETUP, &issue, "T-avengers"));
+
+        let previews = preview_command(
+            &LOCAL_TEAM_SETUP,
+            &crate::config::test::config(),
+            &issue,
+            &author,
+            "@rfcbot fcp merge",
+        );
+        let comment = previews[0].comment.as_ref().unwrap();
+        assert!(comment.contains("* [x] [octocat]"));
+        assert!(comment.contains("* [ ] [hubot]"));
+        assert!(!comment.contains("@hulk"));
+    }
+
+    #[test]
+    fn feedback_done_resolves_pending_request() {
+        use crate::domain::schema::rfc_feedback_request;
+
+        with_test_proposal(2, |conn, author, issue, comment, _| {
+            let requested = GitHubUser {
+                id: author.id - 500,
+                login: format!("{}-requested", author.login),
+            };
+            crate::github::handle_user(conn, &requested).unwrap();
+            let request = diesel::insert_into(rfc_feedback_request::table)
+                .values(&NewFeedbackRequest {
+                    fk_initiator: author.id,
+                    fk_requested: requested.id,
+                    fk_issue: issue.id,
+                    fk_feedback_comment: None,
+                })
+                .get_result::<FeedbackRequest>(conn)
+                .unwrap();
+
+            let processed = process_feedback_done(issue, comment, &requested.login);
+            let resolved = rfc_feedback_request::table
+                .find(request.id)
+                .first::<FeedbackRequest>(conn)
+                .unwrap();
+
+            diesel::delete(rfc_feedback_request::table.find(request.id))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(githubuser::table.find(requested.id))
+                .execute(conn)
+                .unwrap();
+
+            processed.unwrap();
+            assert_eq!(resolved.fk_feedback_comment, Some(comment.id));
+        });
+    }
+
+    #[test]
+    fn comments_from_ignored_authors_and_the_bot_arent_processed() {
+        use crate::domain::schema::rfc_feedback_request;
+
+        with_test_proposal(23, |conn, author, issue, comment, _| {
+            // the author is the account we're running as
+            let _fake = use_fake_github(author);
+            let ignored = GitHubUser {
+                id: author.id - 500,
+                login: CONFIG.ignored_authors.iter().next().unwrap().clone(),
+            };
+            crate::github::handle_user(conn, &ignored).unwrap();
+
+            // any comment without commands from someone whose feedback was asked for counts as
+            // their feedback, were it processed
+            let requests = [author, &ignored]
+                .iter()
+                .map(|requested| {
+                    diesel::insert_into(rfc_feedback_request::table)
+                        .values(&NewFeedbackRequest {
+                            fk_initiator: author.id,
+                            fk_requested: requested.id,
+                            fk_issue: issue.id,
+                            fk_feedback_comment: None,
+                        })
+                        .get_result::<FeedbackRequest>(conn)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let own = update_nags(&IssueComment {
+                body: "Thanks!".to_string(),
+                ..comment.clone()
+            });
+            let from_ignored = update_nags(&IssueComment {
+                body: "Thanks!".to_string(),
+                fk_user: ignored.id,
+                ..comment.clone()
+            });
+            let resolved = requests
+                .iter()
+                .map(|request| {
+                    rfc_feedback_request::table
+                        .find(request.id)
+                        .first::<FeedbackRequest>(conn)
+                        .unwrap()
+                        .fk_feedback_comment
+                })
+                .collect::<Vec<_>>();
+
+            for request in &requests {
+                diesel::delete(rfc_feedback_request::table.find(request.id))
+                    .execute(conn)
+                    .unwrap();
             }
-        } else {
-            info!(
-                "Skipping comment to {}#{}, comment posts are disabled.",
-                self.issue.repository, self.issue.number
-            );
-            throw!(DashError::Misc(None))
-        }
+            diesel::delete(githubuser::table.find(ignored.id))
+                .execute(conn)
+                .unwrap();
+
+            own.unwrap();
+            from_ignored.unwrap();
+            assert_eq!(resolved, vec![None, None]);
+        });
     }
-}
 
-fn format_ticky_boxes<'a>(
-    msg: &mut String,
-    reviewers: impl Iterator<Item = (&'a GitHubUser, bool)>,
-) {
-    for (member, reviewed) in reviewers {
-        msg.push_str(if reviewed { "* [x] @" } else { "* [ ] @" });
-        msg.push_str(&member.login);
-        msg.push('\n');
+    #[test]
+    fn status_comment_counts_down_mid_fcp() {
+        let issue = issue();
+        let initiator = user(1);
+        let reviews = vec![review(1, true), review(2, true)];
+        let fcp_end = Utc::now().naive_utc() + Duration::days(3) - Duration::hours(1);
+
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviews,
+                concerns: &[],
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: Some(fcp_end),
+                state: None,
+            },
+        );
+
+        let days = business_days_until(fcp_end, Utc::now().naive_utc());
+        let expected = format!(
+            ":bell: The final comment period ends in {} business day{} (on {}).",
+            days,
+            if days == 1 { "" } else { "s" },
+            fcp_end.format("%Y-%m-%d")
+        );
+        assert!(managed_region(&comment.body).contains(&expected));
+
+        let now = Utc::now().naive_utc();
+        assert_eq!(days_until(now + Duration::hours(1), now), 1);
+        assert_eq!(days_until(now - Duration::hours(1), now), 0);
+
+        // 2026-03-06 is a Friday, so the weekend doesn't count
+        let friday = NaiveDate::from_ymd(2026, 3, 6).and_hms(12, 0, 0);
+        assert_eq!(business_days_until(friday + Duration::days(3), friday), 1);
+        assert_eq!(business_days_until(friday, friday - Duration::days(2)), 2);
+        assert_eq!(business_days_until(friday + Duration::hours(2), friday), 1);
+        assert_eq!(business_days_until(friday, friday + Duration::hours(1)), 0);
+    }
+
+    #[test]
+    fn proposer_cannot_be_sole_reviewer() {
+        let reviews = vec![review(1, true)];
+        assert!(!fcp_can_start(&user(1), &reviews, &[], 1, true));
+        assert!(fcp_can_start(&user(1), &reviews, &[], 0, true));
+
+        let issue = issue();
+        let initiator = user(1);
+        let comment = RfcBotComment::new(
+            &issue,
+            CommentType::FcpProposed {
+                initiator: &initiator,
+                disposition: FcpDisposition::Merge,
+                reviewers: &reviews,
+                concerns: &[],
+                teams: &[],
+                min_distinct_reviewers: 1,
+                fcp_end: None,
+                state: None,
+            },
+        );
+        assert!(comment
+            .body
+            .contains("Waiting on 1 more review from someone other than the proposer."));
+
+        let reviews = vec![review(1, true), review(2, true)];
+        assert!(fcp_can_start(&user(1), &reviews, &[], 1, true));
+        assert!(!fcp_can_start(&user(1), &reviews, &[], 2, true));
+
+        // a waived review counts, as it does towards the majority
+        let mut reviews = vec![review(1, true), review(2, false)];
+        reviews[1].1.waived = true;
+        assert!(fcp_can_start(&user(1), &reviews, &[], 1, true));
+        assert_eq!(missing_distinct_reviews(&user(1), &reviews, 2), 1);
+    }
+
+    #[test]
+    fn only_concerns_block_unless_reviews_required() {
+        let initiator = user(1);
+        let reviews = vec![
+            review(1, true),
+            review(2, false),
+            review(3, false),
+            review(4, false),
+        ];
+        let concern = |resolved| FcpConcern {
+            id: 1,
+            fk_proposal: 1,
+            fk_initiator: 2,
+            fk_resolved_comment: resolved,
+            name: "naming".to_string(),
+            fk_initiating_comment: 11,
+            fk_directed_at: None,
+            description: None,
+            fk_overridden_by: None,
+        };
+        let resolved = vec![(user(2), concern(Some(20)))];
+        let unresolved = vec![(user(2), concern(None))];
+
+        assert!(!fcp_can_start(&initiator, &reviews, &resolved, 0, true));
+        assert!(fcp_can_start(&initiator, &reviews, &resolved, 0, false));
+        assert!(!fcp_can_start(&initiator, &reviews, &unresolved, 0, false));
+        // the minimum of distinct reviewers still applies
+        assert!(!fcp_can_start(&initiator, &reviews, &resolved, 1, false));
+
+        let proposal = proposal(ProposalState::Pending);
+        let author = user(2);
+        assert_eq!(
+            last_blocker(&proposal, &initiator, &reviews, &unresolved, 0, false),
+            Some(LastBlocker::Concern {
+                author: &author,
+                name: "naming"
+            })
+        );
+        assert_eq!(
+            last_blocker(&proposal, &initiator, &reviews, &unresolved, 0, true),
+            None
+        );
+    }
+
+    #[test]
+    fn unreviewing_prevents_fcp_start() {
+        let mut reviews = vec![review(1, true), review(2, true), review(3, false)];
+        assert!(fcp_can_start(&user(1), &reviews, &[], 1, true));
+
+        // reviewer 2 takes back their review
+        reviews[1].1.reviewed = false;
+        assert!(!fcp_can_start(&user(1), &reviews, &[], 1, true));
     }
 }
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io::Read;
 
 use crypto::hmac::Hmac;
@@ -6,12 +7,12 @@ use crypto::mac::MacResult;
 use crypto::sha1::Sha1;
 use hex::FromHex;
 use rocket::data::{self, Data, FromDataSimple};
-use rocket::http::Status;
+use rocket::http::{HeaderMap, Status};
 use rocket::outcome::Outcome::*;
 use rocket::request::Request;
+use rocket::response::{self, status, Responder};
 
 use crate::config::CONFIG;
-use crate::error::{DashError, DashResult};
 use crate::github::models::{CommentFromJson, IssueFromJson, PullRequestFromJson};
 
 #[derive(Debug)]
@@ -22,84 +23,142 @@ pub struct Event {
 }
 
 impl FromDataSimple for Event {
-    type Error = &'static str;
+    type Error = WebhookError;
     fn from_data(request: &Request<'_>, data: Data) -> data::Outcome<Self, Self::Error> {
-        let headers = request.headers();
-
-        // see [this document](https://developer.github.com/webhooks/securing/) for more information
-        let signature = match headers.get_one("X-Hub-Signature") {
-            Some(s) => s,
-            None => return Failure((Status::BadRequest, "missing signature header")),
-        };
-
-        // see [this document](https://developer.github.com/webhooks/#events) for available types
-        let event_name = match headers.get_one("X-Github-Event") {
-            Some(e) => e,
-            None => return Failure((Status::BadRequest, "missing event header")),
-        };
-
-        // unique id for each delivery
-        let delivery_id = match headers.get_one("X-Github-Delivery") {
-            Some(d) => d,
-            None => return Failure((Status::BadRequest, "missing delivery header")),
-        };
-
         let mut body = String::new();
         if let Err(why) = data.open().read_to_string(&mut body) {
             error!("unable to read request body: {:?}", why);
-            return Failure((Status::InternalServerError, "unable to read request body"));
+            return Failure((Status::InternalServerError, WebhookError::Unreadable));
         }
 
-        for secret in &CONFIG.github_webhook_secrets {
-            if authenticate(secret, &body, signature) {
-                // once we know it's from github, we'll parse it
-
-                let payload = match parse_event(event_name, &body) {
-                    Ok(p) => p,
-                    Err(DashError::Serde(why)) => {
-                        info!("failed to parse webhook payload: {:?}", why);
-                        return Failure((
-                            Status::BadRequest,
-                            "failed to deserialize request payload",
-                        ));
-                    }
-                    Err(why) => {
-                        error!("non-json-parsing error with webhook payload: {:?}", why);
-                        return Failure((
-                            Status::InternalServerError,
-                            "unknown failure, check the logs",
-                        ));
-                    }
-                };
-
-                let full_event = Event {
-                    delivery_id: delivery_id.to_owned(),
-                    event_name: event_name.to_owned(),
-                    payload,
-                };
-
+        match receive(request.headers(), &body, &CONFIG.github_webhook_secrets) {
+            Ok(event) => {
                 info!(
                     "Received valid webhook ({} id {})",
-                    full_event.event_name, full_event.delivery_id
+                    event.event_name, event.delivery_id
                 );
-
-                return Success(full_event);
+                Success(event)
+            }
+            Err(WebhookError::Unauthenticated) => {
+                warn!("Received invalid webhook: {:?}", request);
+                warn!("Invalid webhook body: `{}`", body);
+                warn!(
+                    "Tried {} webhook secrets",
+                    CONFIG.github_webhook_secrets.len()
+                );
+                Failure((Status::Forbidden, WebhookError::Unauthenticated))
+            }
+            Err(why) => {
+                info!("rejected webhook delivery: {}", why);
+                Failure((why.status(), why))
             }
         }
+    }
+}
 
-        warn!("Received invalid webhook: {:?}", request);
-        warn!("Invalid webhook body: `{}`", body);
-        warn!(
-            "Tried {} webhook secrets",
-            CONFIG.github_webhook_secrets.len()
-        );
-        Failure((Status::Forbidden, "unable to authenticate webhook"))
+/// Checks a delivery's headers and signature against each of `secrets`, then parses its body.
+fn receive(headers: &HeaderMap<'_>, body: &str, secrets: &[String]) -> Result<Event, WebhookError> {
+    // see [this document](https://developer.github.com/webhooks/securing/) for more information
+    let signature = headers
+        .get_one("X-Hub-Signature")
+        .ok_or(WebhookError::MissingHeader("signature"))?;
+
+    // see [this document](https://developer.github.com/webhooks/#events) for available types
+    let event_name = headers
+        .get_one("X-Github-Event")
+        .ok_or(WebhookError::MissingHeader("event"))?;
+
+    // unique id for each delivery
+    let delivery_id = headers
+        .get_one("X-Github-Delivery")
+        .ok_or(WebhookError::MissingHeader("delivery"))?;
+
+    if !secrets
+        .iter()
+        .any(|secret| authenticate(secret, body, signature))
+    {
+        return Err(WebhookError::Unauthenticated);
+    }
+
+    // once we know it's from github, we'll parse it
+    let payload =
+        parse_event(event_name, body).map_err(|why| WebhookError::Malformed(why.to_string()))?;
+
+    Ok(Event {
+        delivery_id: delivery_id.to_owned(),
+        event_name: event_name.to_owned(),
+        payload,
+    })
+}
+
+/// Why a webhook delivery was rejected, reported back to GitHub in the response.
+#[derive(Debug)]
+pub enum WebhookError {
+    MissingHeader(&'static str),
+    Unreadable,
+    Unauthenticated,
+    /// The body wasn't JSON or didn't have the shape of its event.
+    Malformed(String),
+}
+
+impl WebhookError {
+    pub fn status(&self) -> Status {
+        match self {
+            WebhookError::MissingHeader(_) | WebhookError::Malformed(_) => Status::BadRequest,
+            WebhookError::Unreadable => Status::InternalServerError,
+            WebhookError::Unauthenticated => Status::Forbidden,
+        }
+    }
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::MissingHeader(header) => write!(f, "missing {} header", header),
+            WebhookError::Unreadable => write!(f, "unable to read request body"),
+            WebhookError::Unauthenticated => write!(f, "unable to authenticate webhook"),
+            WebhookError::Malformed(why) => write!(f, "malformed payload: {}", why),
+        }
+    }
+}
+
+/// What became of a webhook delivery, which GitHub shows in its list of recent deliveries.
+#[derive(Debug)]
+pub enum Delivery {
+    Processed,
+    /// An event rfcbot doesn't act on, acknowledged so that GitHub doesn't show it as failed.
+    Ignored,
+    Rejected(WebhookError),
+}
+
+impl Delivery {
+    pub fn status(&self) -> Status {
+        match self {
+            Delivery::Processed => Status::Ok,
+            Delivery::Ignored => Status::Accepted,
+            Delivery::Rejected(why) => why.status(),
+        }
+    }
+}
+
+impl<'r> Responder<'r> for Delivery {
+    fn respond_to(self, request: &Request<'_>) -> response::Result<'r> {
+        let status = self.status();
+        let body = match self {
+            Delivery::Processed => "processed".to_string(),
+            Delivery::Ignored => "ignored".to_string(),
+            Delivery::Rejected(why) => why.to_string(),
+        };
+        status::Custom(status, body).respond_to(request)
     }
 }
 
 fn authenticate(secret: &str, payload: &str, signature: &str) -> bool {
     // https://developer.github.com/webhooks/securing/#validating-payloads-from-github
-    let sans_prefix = signature[5..].as_bytes();
+    let sans_prefix = match signature.get(5..) {
+        Some(hex) => hex.as_bytes(),
+        None => return false,
+    };
     if let Ok(sigbytes) = Vec::from_hex(sans_prefix) {
         let mut mac = Hmac::new(Sha1::new(), secret.as_bytes());
         mac.input(payload.as_bytes());
@@ -110,7 +169,7 @@ fn authenticate(secret: &str, payload: &str, signature: &str) -> bool {
     }
 }
 
-fn parse_event(event_name: &str, body: &str) -> DashResult<Payload> {
+fn parse_event(event_name: &str, body: &str) -> serde_json::Result<Payload> {
     match event_name {
         "issue_comment" => Ok(Payload::IssueComment(serde_json::from_str(body)?)),
         "issues" => Ok(Payload::Issues(serde_json::from_str(body)?)),
@@ -185,6 +244,13 @@ pub struct PullRequestEvent {
     pub pull_request: PullRequestFromJson,
 }
 
+impl PullRequestEvent {
+    /// Whether this event is a push of new commits which should reset reviews.
+    pub fn resets_reviews(&self, reset_reviews_on_push: bool) -> bool {
+        reset_reviews_on_push && self.action == "synchronize"
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Repository {
     pub full_name: String,
@@ -206,3 +272,128 @@ pub struct Commit {
 pub struct Committer {
     pub login: String,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pull_request_event(action: &str) -> PullRequestEvent {
+        let body = json!({
+            "action": action,
+            "number": 7,
+            "repository": { "full_name": "rust-lang/rust" },
+            "pull_request": {
+                "number": 7,
+                "review_comments_url": "https://api.github.com/repos/rust-lang/rust/pulls/7/comments",
+                "state": "open",
+                "title": "Stabilize a feature",
+                "body": null,
+                "assignee": null,
+                "milestone": null,
+                "locked": false,
+                "created_at": "2018-01-01T00:00:00Z",
+                "updated_at": "2018-01-02T00:00:00Z",
+                "closed_at": null,
+                "merged_at": null,
+                "commits": 2,
+                "additions": 10,
+                "deletions": 1,
+                "changed_files": 1
+            }
+        });
+        match parse_event("pull_request", &body.to_string()).unwrap() {
+            Payload::PullRequest(event) => event,
+            other => panic!("expected a pull request event, got {:?}", other),
+        }
+    }
+
+    const SECRET: &str = "s3cret";
+
+    fn deliver(event_name: &str, body: &str) -> Result<Event, WebhookError> {
+        let mut mac = Hmac::new(Sha1::new(), SECRET.as_bytes());
+        mac.input(body.as_bytes());
+        let signature = format!("sha1={}", hex::encode(mac.result().code()));
+
+        let mut headers = HeaderMap::new();
+        headers.add_raw("X-Hub-Signature", signature);
+        headers.add_raw("X-Github-Event", event_name.to_string());
+        headers.add_raw("X-Github-Delivery", "72d3162e-cc78-11e3-81ab-4c9367dc0958");
+        receive(&headers, body, &[SECRET.to_string()])
+    }
+
+    #[test]
+    fn malformed_payloads_are_bad_requests() {
+        for body in &["{not json", r#"{"action": "created"}"#] {
+            let why = deliver("issue_comment", body).unwrap_err();
+            assert!(why.to_string().starts_with("malformed payload: "));
+            assert_eq!(Delivery::Rejected(why).status(), Status::BadRequest);
+        }
+
+        // a signature too short to hold a digest isn't a panic
+        let mut headers = HeaderMap::new();
+        headers.add_raw("X-Hub-Signature", "sha");
+        headers.add_raw("X-Github-Event", "issue_comment");
+        headers.add_raw("X-Github-Delivery", "1");
+        let why = receive(&headers, "{}", &[SECRET.to_string()]).unwrap_err();
+        assert_eq!(why.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn unhandled_events_are_accepted() {
+        let event = deliver("watch", r#"{"action": "started"}"#).unwrap();
+        assert!(matches!(event.payload, Payload::Unsupported));
+        assert_eq!(Delivery::Ignored.status(), Status::Accepted);
+    }
+
+    #[test]
+    fn comment_events_are_processed() {
+        let body = json!({
+            "action": "created",
+            "repository": { "full_name": "rust-lang/rfcs" },
+            "issue": {
+                "number": 42,
+                "user": { "id": 1, "login": "thor" },
+                "assignee": null,
+                "state": "open",
+                "title": "An RFC",
+                "body": null,
+                "labels": [],
+                "milestone": null,
+                "locked": false,
+                "comments": 1,
+                "closed_at": null,
+                "created_at": "2018-01-01T00:00:00Z",
+                "updated_at": "2018-01-02T00:00:00Z",
+                "comments_url": "https://api.github.com/repos/rust-lang/rfcs/issues/42/comments"
+            },
+            "comment": {
+                "id": 7,
+                "html_url": "https://github.com/rust-lang/rfcs/issues/42#issuecomment-7",
+                "body": "@rfcbot fcp merge",
+                "user": { "id": 1, "login": "thor" },
+                "created_at": "2018-01-02T00:00:00Z",
+                "updated_at": "2018-01-02T00:00:00Z"
+            }
+        });
+
+        let event = deliver("issue_comment", &body.to_string()).unwrap();
+        match event.payload {
+            Payload::IssueComment(comment_event) => {
+                assert_eq!(comment_event.comment.body, "@rfcbot fcp merge");
+                assert_eq!(comment_event.issue.number, 42);
+            }
+            other => panic!("expected a comment event, got {:?}", other),
+        }
+        assert_eq!(Delivery::Processed.status(), Status::Ok);
+    }
+
+    #[test]
+    fn synchronize_resets_reviews_when_enabled() {
+        let pushed = pull_request_event("synchronize");
+        assert_eq!(pushed.number, 7);
+        assert!(pushed.resets_reviews(true));
+        assert!(!pushed.resets_reviews(false));
+
+        assert!(!pull_request_event("opened").resets_reviews(true));
+    }
+}
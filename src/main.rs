@@ -39,6 +39,7 @@ use chrono::Local;
 use diesel::pg::PgConnection;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
+use diesel::r2d2::PoolError;
 
 use crate::config::CONFIG;
 
@@ -64,7 +65,10 @@ fn main() {
         .init();
 
     debug!("Logging initialized.");
-    let _ = CONFIG.check();
+    if let Err(why) = config::validate() {
+        error!("{}", why);
+        std::process::exit(1);
+    }
     let _ = DB_POOL.get().expect("Unable to test connection pool.");
 
     // we want to panic if we're unable to find any of the usernames
@@ -74,7 +78,20 @@ fn main() {
         info!("parsed teams: {:?}", parsed_teams);
     }
 
+    if std::env::args().any(|arg| arg == "--backfill") {
+        ok_or!(scraper::backfill_github(), why =>
+            error!("Unable to backfill github activity: {:?}", why));
+        return;
+    }
+
+    if std::env::args().any(|arg| arg == "--bootstrap") {
+        ok_or!(scraper::bootstrap_github(), why =>
+            error!("Unable to bootstrap existing FCPs: {:?}", why));
+        return;
+    }
+
     teams::start_updater_thread();
+    github::start_edit_flusher();
 
     // FIXME(anp) need to handle panics in both the listeners and crash the server
     let _ = scraper::start_scraping();
@@ -89,9 +106,8 @@ lazy_static! {
     pub static ref DB_POOL: Pool<ConnectionManager<PgConnection>> = {
         info!("Initializing database connection pool.");
 
-        let manager = ConnectionManager::<PgConnection>::new(CONFIG.db_url.clone());
-
-        match Pool::builder().max_size(CONFIG.db_pool_size).build(manager) {
+        let timeout = std::time::Duration::from_secs(CONFIG.db_pool_timeout_secs);
+        match new_pool(&CONFIG.db_url, CONFIG.db_pool_size, timeout) {
             Ok(p) => {
                 info!("DB connection pool established.");
                 p
@@ -103,3 +119,15 @@ lazy_static! {
         }
     };
 }
+
+/// A pool of up to `size` connections, whose `get` fails once it has waited `timeout` for one.
+pub fn new_pool(
+    url: &str,
+    size: u32,
+    timeout: std::time::Duration,
+) -> Result<Pool<ConnectionManager<PgConnection>>, PoolError> {
+    Pool::builder()
+        .max_size(size)
+        .connection_timeout(timeout)
+        .build(ConnectionManager::new(url))
+}
@@ -1,10 +1,18 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDateTime, Utc};
 use diesel::prelude::*;
 
-use crate::domain::github::{GitHubUser, Issue, IssueComment};
-use crate::domain::rfcbot::{FcpProposal, FcpReviewRequest};
-use crate::error::DashResult;
+use crate::config::{Config, CONFIG};
+use crate::domain::github::{GitHubUser, Issue, IssueComment, IssueReactions};
+use crate::domain::rfcbot::{FcpConcern, FcpProposal, FcpReviewRequest, ProposalState};
+use crate::error::{DashError, DashResult};
 use crate::DB_POOL;
 
+/// A pending proposal as served by `/api/all`.
 #[derive(Serialize)]
 pub struct FcpWithInfo {
     pub fcp: FcpProposal,
@@ -21,7 +29,7 @@ pub fn all_fcps() -> DashResult<Vec<FcpWithInfo>> {
     let conn = &*DB_POOL.get()?;
 
     let proposals = fcp_proposal::table
-        .filter(fcp_proposal::fcp_start.is_null())
+        .filter(fcp_proposal::state.eq(ProposalState::Pending.repr()))
         .load::<FcpProposal>(conn)?;
 
     let mut all_fcps = Vec::new();
@@ -37,7 +45,8 @@ pub fn all_fcps() -> DashResult<Vec<FcpWithInfo>> {
             let user = githubuser::table
                 .filter(githubuser::id.eq(review.fk_reviewer))
                 .first(conn)?;
-            reviews_with_users.push((user, review.reviewed));
+            // waived reviewers aren't waiting on anything either
+            reviews_with_users.push((user, review.reviewed || review.waived));
         }
 
         let status_comment = issuecomment::table
@@ -61,6 +70,7 @@ pub fn all_fcps() -> DashResult<Vec<FcpWithInfo>> {
     Ok(all_fcps)
 }
 
+/// A proposal waiting on a particular member's review.
 #[derive(Queryable, Serialize)]
 pub struct IndividualFcp {
     issue: Issue,
@@ -68,8 +78,58 @@ pub struct IndividualFcp {
     review_request: FcpReviewRequest,
 }
 
-pub fn individual_nags(username: &str) -> DashResult<(GitHubUser, Vec<IndividualFcp>)> {
-    use crate::domain::schema::{fcp_proposal, fcp_review_request, githubuser, issue};
+/// A concern raised by a particular member which is holding up a proposal.
+#[derive(Serialize)]
+pub struct IndividualConcern {
+    issue: Issue,
+    proposal: FcpProposal,
+    concern: FcpConcern,
+}
+
+/// A member, the proposals they still need to review and the concerns they've raised which are
+/// still outstanding, as served by `/api/<username>`. This serializes as a three element array,
+/// `[user, fcps, concerns]`.
+#[derive(Serialize)]
+pub struct MemberNags(
+    pub GitHubUser,
+    pub Vec<IndividualFcp>,
+    pub Vec<IndividualConcern>,
+);
+
+/// How a member's proposals are ordered, chosen with `?sort=`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NagOrder {
+    /// Oldest proposal first.
+    Age,
+    /// By repository, then issue number.
+    Repo,
+}
+
+impl Default for NagOrder {
+    fn default() -> Self { NagOrder::Age }
+}
+
+impl FromStr for NagOrder {
+    type Err = DashError;
+
+    fn from_str(s: &str) -> DashResult<Self> {
+        match s {
+            "age" => Ok(NagOrder::Age),
+            "repo" => Ok(NagOrder::Repo),
+            _ => Err(DashError::Misc(Some(format!("unknown sort order {}", s)))),
+        }
+    }
+}
+
+/// The proposals a member has been asked to review, and the concerns they've raised on them.
+/// Reviews they've already done (or which were waived) and resolved concerns are left out unless
+/// `include_resolved` is set.
+pub fn individual_nags(
+    username: &str,
+    order: NagOrder,
+    include_resolved: bool,
+) -> DashResult<MemberNags> {
+    use crate::domain::schema::{fcp_concern, fcp_proposal, fcp_review_request, githubuser, issue};
     let conn = &*DB_POOL.get()?;
 
     let user = githubuser::table
@@ -78,13 +138,17 @@ pub fn individual_nags(username: &str) -> DashResult<(GitHubUser, Vec<Individual
 
     let review_requests = fcp_review_request::table
         .inner_join(fcp_proposal::table)
-        .filter(fcp_proposal::fcp_start.is_null())
+        .filter(fcp_proposal::state.eq(ProposalState::Pending.repr()))
         .filter(fcp_review_request::fk_reviewer.eq(user.id))
-        .filter(fcp_review_request::reviewed.eq(false))
         .load::<(FcpReviewRequest, FcpProposal)>(conn)?;
 
+    let now = Utc::now().naive_utc();
     let mut fcps = Vec::new();
     for (rr, proposal) in review_requests {
+        if is_snoozed(&proposal, now) || !(include_resolved || is_outstanding(&rr)) {
+            continue;
+        }
+
         let issue = issue::table
             .filter(issue::id.eq(proposal.fk_issue))
             .first::<Issue>(conn)?;
@@ -96,5 +160,718 @@ pub fn individual_nags(username: &str) -> DashResult<(GitHubUser, Vec<Individual
         });
     }
 
-    Ok((user, fcps))
+    let raised = fcp_concern::table
+        .inner_join(fcp_proposal::table)
+        .filter(fcp_proposal::state.eq(ProposalState::Pending.repr()))
+        .filter(fcp_concern::fk_initiator.eq(user.id))
+        .load::<(FcpConcern, FcpProposal)>(conn)?;
+
+    let mut concerns = Vec::new();
+    for (concern, proposal) in raised {
+        if is_snoozed(&proposal, now)
+            || !(include_resolved || concern.fk_resolved_comment.is_none())
+        {
+            continue;
+        }
+
+        let issue = issue::table
+            .filter(issue::id.eq(proposal.fk_issue))
+            .first::<Issue>(conn)?;
+
+        concerns.push(IndividualConcern {
+            issue,
+            proposal,
+            concern,
+        });
+    }
+
+    sort_nags(&mut fcps, order, |fcp| (&fcp.issue, fcp.proposal.id));
+    sort_nags(&mut concerns, order, |c| (&c.issue, c.concern.id));
+    Ok(MemberNags(user, fcps, concerns))
+}
+
+fn is_outstanding(review: &FcpReviewRequest) -> bool { !review.reviewed && !review.waived }
+
+/// Orders a member's nags, `key` giving each one's issue and an id which was handed out when it
+/// was made (which is how their age is told).
+fn sort_nags<T, F>(nags: &mut [T], order: NagOrder, key: F)
+where
+    F: Fn(&T) -> (&Issue, i32),
+{
+    match order {
+        NagOrder::Age => nags.sort_by_key(|nag| key(nag).1),
+        NagOrder::Repo => nags.sort_by(|a, b| {
+            let (a, b) = (key(a).0, key(b).0);
+            (&a.repository, a.number).cmp(&(&b.repository, b.number))
+        }),
+    }
+}
+
+/// Whether a team has asked not to be reminded about this proposal for now.
+fn is_snoozed(proposal: &FcpProposal, now: NaiveDateTime) -> bool {
+    proposal.snooze_until.map_or(false, |until| until > now)
+}
+
+/// A proposal in its final comment period, as served by `/fcp/ending-soon`.
+#[derive(Debug, Serialize)]
+pub struct EndingFcp {
+    pub issue: Issue,
+    pub proposal: FcpProposal,
+    pub fcp_end: NaiveDateTime,
+}
+
+/// The running final comment periods which end within `days`, soonest first.
+pub fn ending_soon(days: i64) -> DashResult<Vec<EndingFcp>> {
+    use crate::domain::schema::{fcp_proposal, issue};
+    let conn = &*DB_POOL.get()?;
+
+    let running = issue::table
+        .inner_join(fcp_proposal::table)
+        .filter(fcp_proposal::state.eq(ProposalState::InFcp.repr()))
+        .load::<(Issue, FcpProposal)>(conn)?;
+
+    let now = Utc::now().naive_utc();
+    Ok(ending_within(running, &CONFIG, now, Duration::days(days)))
+}
+
+fn ending_within(
+    running: Vec<(Issue, FcpProposal)>,
+    config: &Config,
+    now: NaiveDateTime,
+    window: Duration,
+) -> Vec<EndingFcp> {
+    let mut ending = running
+        .into_iter()
+        .filter_map(|(issue, proposal)| {
+            let durations = config.fcp_durations_for(&issue.repository);
+            let fcp_end = crate::github::fcp_end(&proposal, durations)?;
+            Some(EndingFcp {
+                issue,
+                proposal,
+                fcp_end,
+            })
+        })
+        .filter(|fcp| fcp.fcp_end <= now + window)
+        .collect::<Vec<_>>();
+    ending.sort_by_key(|fcp| fcp.fcp_end);
+    ending
+}
+
+/// How much each reaction and comment counts towards an issue's place in `hot_issues`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HotWeights {
+    pub thumbs_up: f64,
+    pub thumbs_down: f64,
+    pub hooray: f64,
+    pub comments: f64,
+}
+
+impl Default for HotWeights {
+    fn default() -> Self {
+        HotWeights {
+            thumbs_up: 1.0,
+            thumbs_down: 1.0,
+            hooray: 1.0,
+            comments: 1.0,
+        }
+    }
+}
+
+impl HotWeights {
+    fn score(&self, reactions: &IssueReactions, comments: usize) -> f64 {
+        self.thumbs_up * f64::from(reactions.thumbs_up)
+            + self.thumbs_down * f64::from(reactions.thumbs_down)
+            + self.hooray * f64::from(reactions.hooray)
+            + self.comments * comments as f64
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HotIssue {
+    pub issue: Issue,
+    pub reactions: IssueReactions,
+    pub comments: usize,
+    pub score: f64,
+}
+
+/// The open issues whose reactions have been counted by the scraper, ranked by `weights`, at
+/// most `limit` of them. The ranking is done by the database so that only those issues are
+/// loaded.
+pub fn hot_issues(weights: &HotWeights, limit: usize) -> DashResult<Vec<HotIssue>> {
+    use crate::domain::schema::{issue, issue_reactions};
+    use diesel::sql_types::{BigInt, Double, Integer};
+
+    #[derive(QueryableByName)]
+    struct Ranked {
+        #[sql_type = "Integer"]
+        id: i32,
+        #[sql_type = "BigInt"]
+        comments: i64,
+    }
+
+    let conn = &*DB_POOL.get()?;
+
+    // the same score as `HotWeights::score`
+    let top = diesel::sql_query(
+        "SELECT issue.id AS id, COUNT(issuecomment.id) AS comments FROM issue \
+         INNER JOIN issue_reactions ON issue_reactions.fk_issue = issue.id \
+         LEFT JOIN issuecomment ON issuecomment.fk_issue = issue.id \
+         WHERE issue.open \
+         GROUP BY issue.id, issue_reactions.fk_issue \
+         ORDER BY $1 * issue_reactions.thumbs_up + $2 * issue_reactions.thumbs_down \
+         + $3 * issue_reactions.hooray + $4 * COUNT(issuecomment.id) DESC, issue.id \
+         LIMIT $5",
+    )
+    .bind::<Double, _>(weights.thumbs_up)
+    .bind::<Double, _>(weights.thumbs_down)
+    .bind::<Double, _>(weights.hooray)
+    .bind::<Double, _>(weights.comments)
+    .bind::<BigInt, _>(limit as i64)
+    .load::<Ranked>(conn)?;
+
+    let comments = top
+        .into_iter()
+        .map(|ranked| (ranked.id, ranked.comments as usize))
+        .collect::<BTreeMap<_, _>>();
+    let hot = issue::table
+        .inner_join(issue_reactions::table)
+        .filter(issue::id.eq_any(comments.keys().cloned().collect::<Vec<_>>()))
+        .load::<(Issue, IssueReactions)>(conn)?;
+
+    // loading them by id loses the database's order
+    Ok(rank_hot_issues(hot, &comments, weights))
+}
+
+/// Highest score first, ties going to the older issue.
+fn rank_hot_issues(
+    issues: Vec<(Issue, IssueReactions)>,
+    comments: &BTreeMap<i32, usize>,
+    weights: &HotWeights,
+) -> Vec<HotIssue> {
+    let mut ranked = issues
+        .into_iter()
+        .map(|(issue, reactions)| {
+            let comments = comments.get(&issue.id).cloned().unwrap_or(0);
+            let score = weights.score(&reactions, comments);
+            HotIssue {
+                issue,
+                reactions,
+                comments,
+                score,
+            }
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.issue.id.cmp(&b.issue.id))
+    });
+    ranked
+}
+
+/// How many proposals `ProposalExport` reads from the database at a time.
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+const EXPORT_HEADER: &str =
+    "repository,number,disposition,state,created_at,fcp_start,closed_at,reviewers,concerns\n";
+
+/// Every proposal as CSV, served by `/fcp/export.csv`. Proposals are read a page at a time as the
+/// response is written, so the whole export is never held in memory.
+pub struct ProposalExport {
+    last_id: i32,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl Default for ProposalExport {
+    fn default() -> Self {
+        ProposalExport {
+            last_id: 0,
+            buf: EXPORT_HEADER.as_bytes().to_vec(),
+            pos: 0,
+            done: false,
+        }
+    }
+}
+
+impl ProposalExport {
+    fn fill(&mut self) -> DashResult<()> {
+        use crate::domain::schema::{
+            fcp_concern, fcp_proposal, fcp_review_request, issue, issuecomment,
+        };
+        let conn = &*DB_POOL.get()?;
+
+        let page = fcp_proposal::table
+            .inner_join(issue::table)
+            .filter(fcp_proposal::id.gt(self.last_id))
+            .order(fcp_proposal::id)
+            .limit(EXPORT_PAGE_SIZE)
+            .load::<(FcpProposal, Issue)>(conn)?;
+
+        let ids = page.iter().map(|(p, _)| p.id).collect::<Vec<_>>();
+        let initiating = page
+            .iter()
+            .map(|(p, _)| p.fk_initiating_comment)
+            .collect::<Vec<_>>();
+
+        let created = issuecomment::table
+            .filter(issuecomment::id.eq_any(&initiating))
+            .select((issuecomment::id, issuecomment::created_at))
+            .load::<(i32, NaiveDateTime)>(conn)?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let reviewers = count(
+            fcp_review_request::table
+                .filter(fcp_review_request::fk_proposal.eq_any(&ids))
+                .select(fcp_review_request::fk_proposal)
+                .load::<i32>(conn)?,
+        );
+        let concerns = count(
+            fcp_concern::table
+                .filter(fcp_concern::fk_proposal.eq_any(&ids))
+                .select(fcp_concern::fk_proposal)
+                .load::<i32>(conn)?,
+        );
+
+        self.buf.clear();
+        self.pos = 0;
+        self.done = (page.len() as i64) < EXPORT_PAGE_SIZE;
+        for (proposal, issue) in &page {
+            self.buf.extend_from_slice(
+                export_row(
+                    proposal,
+                    issue,
+                    created.get(&proposal.fk_initiating_comment).cloned(),
+                    reviewers.get(&proposal.id).cloned().unwrap_or(0),
+                    concerns.get(&proposal.id).cloned().unwrap_or(0),
+                )
+                .as_bytes(),
+            );
+            self.last_id = proposal.id;
+        }
+
+        Ok(())
+    }
+}
+
+impl Read for ProposalExport {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() && !self.done {
+            self.fill()
+                .map_err(|why| io::Error::new(io::ErrorKind::Other, format!("{:?}", why)))?;
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn count(fks: Vec<i32>) -> BTreeMap<i32, usize> {
+    let mut counts = BTreeMap::new();
+    for fk in fks {
+        *counts.entry(fk).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn export_row(
+    proposal: &FcpProposal,
+    issue: &Issue,
+    created_at: Option<NaiveDateTime>,
+    reviewers: usize,
+    concerns: usize,
+) -> String {
+    let timestamp = |t: Option<NaiveDateTime>| {
+        t.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    };
+
+    format!(
+        "{},{},{},{},{},{},{},{},{}\n",
+        csv_field(&issue.repository),
+        issue.number,
+        csv_field(&proposal.disposition),
+        csv_field(&proposal.state),
+        timestamp(created_at),
+        timestamp(proposal.fcp_start),
+        timestamp(issue.closed_at),
+        reviewers,
+        concerns
+    )
+}
+
+/// Quotes a field if it would otherwise break the row apart.
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn proposal() -> FcpProposal {
+        FcpProposal {
+            id: 1,
+            fk_issue: 1,
+            fk_initiator: 1,
+            fk_initiating_comment: 1,
+            disposition: "merge".to_string(),
+            fk_bot_tracking_comment: 1,
+            fcp_start: None,
+            snooze_until: None,
+            state: "pending".to_string(),
+            almost_ready_notified: false,
+            fcp_end_override: None,
+            tracking_label: None,
+        }
+    }
+
+    fn user() -> GitHubUser {
+        GitHubUser {
+            id: 1,
+            login: "thor".to_string(),
+        }
+    }
+
+    fn issue() -> Issue {
+        let now = Utc::now().naive_utc();
+        Issue {
+            id: 1,
+            number: 42,
+            fk_milestone: None,
+            fk_user: 1,
+            fk_assignee: None,
+            open: true,
+            is_pull_request: false,
+            title: "An RFC".to_string(),
+            body: String::new(),
+            locked: false,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+            labels: vec![],
+            repository: "rust-lang/rfcs".to_string(),
+            muted: false,
+        }
+    }
+
+    fn keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys = value
+            .as_object()
+            .expect("a JSON object")
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn only_fcps_ending_within_the_window() {
+        let now = Utc::now().naive_utc();
+        let started = |id, days_ago, end_override: Option<i64>| {
+            let proposal = FcpProposal {
+                id,
+                state: ProposalState::InFcp.repr().to_string(),
+                fcp_start: Some(now - Duration::days(days_ago)),
+                fcp_end_override: end_override.map(|days| now + Duration::days(days)),
+                ..proposal()
+            };
+            (issue(), proposal)
+        };
+        let running = vec![
+            started(1, 2, None),
+            started(2, 9, None),
+            started(3, 7, None),
+            started(4, 1, Some(2)),
+            (issue(), proposal()),
+        ];
+        let ending = ending_within(
+            running,
+            &crate::config::test::config(),
+            now,
+            Duration::days(3),
+        )
+        .into_iter()
+        .map(|fcp| fcp.proposal.id)
+        .collect::<Vec<_>>();
+
+        assert_eq!(ending, vec![2, 4, 3]);
+    }
+
+    #[test]
+    fn hot_issues_ranked_by_weighted_engagement() {
+        let now = Utc::now().naive_utc();
+        let seeded = |id, thumbs_up, thumbs_down, hooray| {
+            let issue = Issue { id, ..issue() };
+            let reactions = IssueReactions {
+                fk_issue: id,
+                thumbs_up,
+                thumbs_down,
+                hooray,
+                updated_at: now,
+            };
+            (issue, reactions)
+        };
+        let issues = || {
+            vec![
+                seeded(1, 2, 0, 0),
+                seeded(2, 0, 6, 0),
+                seeded(3, 1, 0, 3),
+                seeded(4, 0, 0, 0),
+            ]
+        };
+        let comments = btreemap! { 1 => 3, 4 => 4 };
+        let ranking = |weights: &HotWeights| {
+            rank_hot_issues(issues(), &comments, weights)
+                .into_iter()
+                .map(|hot| (hot.issue.id, hot.score))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ranking(&HotWeights::default()),
+            vec![(2, 6.0), (1, 5.0), (3, 4.0), (4, 4.0)]
+        );
+
+        let weights = HotWeights {
+            thumbs_up: 2.0,
+            thumbs_down: -1.0,
+            hooray: 0.5,
+            comments: 0.0,
+        };
+        assert_eq!(
+            ranking(&weights),
+            vec![(1, 4.0), (3, 3.5), (4, 0.0), (2, -6.0)]
+        );
+    }
+
+    #[test]
+    fn all_fcps_response_shape() {
+        let now = Utc::now().naive_utc();
+        let fcp = FcpWithInfo {
+            fcp: proposal(),
+            reviews: vec![(user(), true)],
+            issue: issue(),
+            status_comment: IssueComment {
+                id: 1,
+                fk_issue: 1,
+                fk_user: 1,
+                body: String::new(),
+                created_at: now,
+                updated_at: now,
+                repository: "rust-lang/rfcs".to_string(),
+            },
+        };
+
+        let value = serde_json::to_value(&fcp).unwrap();
+        assert_eq!(
+            keys(&value),
+            vec!["fcp", "issue", "reviews", "status_comment"]
+        );
+        assert_eq!(value["reviews"][0][0]["login"], "thor");
+        assert_eq!(value["reviews"][0][1], true);
+        assert_eq!(
+            keys(&value["fcp"]),
+            vec![
+                "almost_ready_notified",
+                "disposition",
+                "fcp_end_override",
+                "fcp_start",
+                "fk_bot_tracking_comment",
+                "fk_initiating_comment",
+                "fk_initiator",
+                "fk_issue",
+                "id",
+                "snooze_until",
+                "state",
+                "tracking_label",
+            ]
+        );
+    }
+
+    #[test]
+    fn member_nags_response_shape() {
+        let nags = MemberNags(
+            user(),
+            vec![IndividualFcp {
+                issue: issue(),
+                proposal: proposal(),
+                review_request: FcpReviewRequest {
+                    id: 1,
+                    fk_proposal: 1,
+                    fk_reviewer: 1,
+                    reviewed: false,
+                    waived: false,
+                    recorded_by: None,
+                    note: None,
+                },
+            }],
+            vec![IndividualConcern {
+                issue: issue(),
+                proposal: proposal(),
+                concern: FcpConcern {
+                    id: 1,
+                    fk_proposal: 1,
+                    fk_initiator: 1,
+                    fk_resolved_comment: None,
+                    name: "naming".to_string(),
+                    fk_initiating_comment: 2,
+                    fk_directed_at: None,
+                    description: None,
+                    fk_overridden_by: None,
+                },
+            }],
+        );
+
+        let value = serde_json::to_value(&nags).unwrap();
+        assert_eq!(value[0]["login"], "thor");
+        assert_eq!(keys(&value[2][0]), vec!["concern", "issue", "proposal"]);
+        assert_eq!(value[2][0]["concern"]["name"], "naming");
+        assert_eq!(
+            keys(&value[1][0]),
+            vec!["issue", "proposal", "review_request"]
+        );
+        assert_eq!(
+            keys(&value[1][0]["review_request"]),
+            vec![
+                "fk_proposal",
+                "fk_reviewer",
+                "id",
+                "note",
+                "recorded_by",
+                "reviewed",
+                "waived"
+            ]
+        );
+    }
+
+    #[test]
+    fn export_has_a_header_and_a_row_per_proposal() {
+        let mut proposal = proposal();
+        let start = NaiveDateTime::from_timestamp(1_500_000_000, 0);
+        proposal.fcp_start = Some(start);
+
+        let csv = format!(
+            "{}{}",
+            EXPORT_HEADER,
+            export_row(&proposal, &issue(), Some(start), 3, 1)
+        );
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "repository,number,disposition,state,created_at,fcp_start,closed_at,reviewers,\
+                 concerns",
+                "rust-lang/rfcs,42,merge,pending,2017-07-14 02:40:00,2017-07-14 02:40:00,,3,1",
+            ]
+        );
+
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    fn nag(proposal_id: i32, repo: &str, number: i32) -> IndividualFcp {
+        let mut fcp = proposal();
+        fcp.id = proposal_id;
+        let mut issue = issue();
+        issue.repository = repo.to_string();
+        issue.number = number;
+        IndividualFcp {
+            issue,
+            proposal: fcp,
+            review_request: FcpReviewRequest {
+                id: proposal_id,
+                fk_proposal: proposal_id,
+                fk_reviewer: 1,
+                reviewed: false,
+                waived: false,
+                recorded_by: None,
+                note: None,
+            },
+        }
+    }
+
+    #[test]
+    fn nags_sorted_by_age_or_repo() {
+        let mut fcps = vec![
+            nag(3, "rust-lang/rust", 7),
+            nag(1, "rust-lang/rust", 2),
+            nag(2, "rust-lang/rfcs", 9),
+        ];
+        let ids = |fcps: &[IndividualFcp]| fcps.iter().map(|f| f.proposal.id).collect::<Vec<_>>();
+        fn key(fcp: &IndividualFcp) -> (&Issue, i32) { (&fcp.issue, fcp.proposal.id) }
+
+        sort_nags(&mut fcps, NagOrder::default(), key);
+        assert_eq!(ids(&fcps), vec![1, 2, 3]);
+
+        sort_nags(&mut fcps, "repo".parse().unwrap(), key);
+        assert_eq!(ids(&fcps), vec![2, 1, 3]);
+
+        assert!("newest".parse::<NagOrder>().is_err());
+
+        // concerns are as old as they are, whatever the proposal they were raised on
+        let concern = |id, proposal_id| {
+            let IndividualFcp {
+                issue, proposal, ..
+            } = nag(proposal_id, "rust-lang/rfcs", proposal_id);
+            IndividualConcern {
+                issue,
+                proposal,
+                concern: FcpConcern {
+                    id,
+                    fk_proposal: proposal_id,
+                    fk_initiator: 1,
+                    fk_resolved_comment: None,
+                    name: "naming".to_string(),
+                    fk_initiating_comment: id,
+                    fk_directed_at: None,
+                    description: None,
+                    fk_overridden_by: None,
+                },
+            }
+        };
+        let mut concerns = vec![concern(5, 1), concern(4, 2)];
+        sort_nags(&mut concerns, NagOrder::Age, |c| (&c.issue, c.concern.id));
+        assert_eq!(
+            concerns.iter().map(|c| c.concern.id).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+    }
+
+    #[test]
+    fn resolved_reviews_are_filtered() {
+        let mut review = nag(1, "rust-lang/rfcs", 1).review_request;
+        assert!(is_outstanding(&review));
+
+        review.reviewed = true;
+        assert!(!is_outstanding(&review));
+
+        review.reviewed = false;
+        review.waived = true;
+        assert!(!is_outstanding(&review));
+    }
+
+    #[test]
+    fn snoozed_proposals_are_skipped() {
+        let now = Utc::now().naive_utc();
+        let mut proposal = proposal();
+        assert!(!is_snoozed(&proposal, now));
+
+        proposal.snooze_until = Some(now + Duration::days(3));
+        assert!(is_snoozed(&proposal, now));
+
+        proposal.snooze_until = Some(now - Duration::days(1));
+        assert!(!is_snoozed(&proposal, now));
+    }
 }
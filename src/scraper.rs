@@ -1,18 +1,26 @@
-use std::thread::JoinHandle;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 
 use crate::config::{CONFIG, GH_ORGS};
+use crate::error::{DashError, DashResult};
 use crate::github;
 
+/// How long a scraper worker waits after the database connection pool was exhausted.
+const DB_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+
 pub fn start_scraping() -> Option<JoinHandle<()>> {
     if CONFIG.github_access_token.is_empty() {
         info!("no github token specified, skipping scraping.");
         None
     } else {
-        Some(crate::utils::spawn_thread(
+        Some(crate::utils::spawn_jittered_thread(
             "GitHub scraper",
             CONFIG.github_interval_mins?,
+            CONFIG.github_scrape_jitter_percent,
             || {
                 scrape_github(github::most_recent_update()?);
                 Ok(())
@@ -21,24 +29,323 @@ pub fn start_scraping() -> Option<JoinHandle<()>> {
     }
 }
 
-pub fn scrape_github(since: DateTime<Utc>) {
+fn all_repos() -> DashResult<Vec<String>> {
+    watched_repos(
+        &GH_ORGS,
+        &CONFIG.github_users,
+        |org| github::GH.org_repos(org),
+        |user| github::GH.user_repos(user),
+    )
+}
+
+/// The repos of each org and user.
+fn watched_repos<O, U>(
+    orgs: &[&str],
+    users: &[String],
+    org_repos: O,
+    user_repos: U,
+) -> DashResult<Vec<String>>
+where
+    O: Fn(&str) -> DashResult<Vec<String>>,
+    U: Fn(&str) -> DashResult<Vec<String>>,
+{
     let mut repos = Vec::new();
-    for org in &GH_ORGS {
-        repos.extend(ok_or!(github::GH.org_repos(org), why => {
+    for org in orgs {
+        repos.extend(ok_or!(org_repos(org), why => {
             error!("Unable to retrieve repos for {}: {:?}", org, why);
-            return;
+            throw!(why);
         }));
     }
+    for user in users {
+        repos.extend(ok_or!(user_repos(user), why => {
+            error!("Unable to retrieve repos for user {}: {:?}", user, why);
+            throw!(why);
+        }));
+    }
+    Ok(repos)
+}
+
+/// Scrape every repository from its own cursor. Repositories which haven't been scraped on
+/// their own yet start from `since`, the last time every repository was scraped successfully.
+pub fn scrape_github(since: DateTime<Utc>) {
+    let repos = ok_or!(all_repos(), return);
+
+    // before any issues are scraped under a renamed repo's new name
+    ok_or!(github::update_renamed_repos(&repos), why =>
+        error!("Unable to update renamed repositories: {:?}", why));
+
+    info!("Scraping github activity since at least {:?}", since);
+    let start_time = Utc::now().naive_utc();
+    let complete = ingest_from_cursors(
+        repos,
+        CONFIG.github_scrape_workers,
+        start_time,
+        move |repo| github::repo_cursor(repo, since),
+        github::ingest_since,
+        github::record_repo_cursor,
+    );
+
+    if complete {
+        ok_or!(github::record_successful_update(start_time), why =>
+            error!("Problem recording successful update: {:?}", why));
+    }
+}
+
+/// Ingests each repo from its cursor, only moving a repo's cursor up to `start` once that repo
+/// has been ingested. Returns whether every repo was ingested successfully.
+fn ingest_from_cursors<C, I, A>(
+    repos: Vec<String>,
+    workers: usize,
+    start: NaiveDateTime,
+    cursor: C,
+    ingest: I,
+    advance: A,
+) -> bool
+where
+    C: Fn(&str) -> DashResult<DateTime<Utc>> + Send + Sync + 'static,
+    I: Fn(&str, DateTime<Utc>) -> DashResult<()> + Send + Sync + 'static,
+    A: Fn(&str, NaiveDateTime) -> DashResult<()> + Send + Sync + 'static,
+{
+    ingest_repos(repos, workers, move |repo| {
+        ingest(repo, cursor(repo)?)?;
+        advance(repo, start)
+    })
+}
 
-    info!("Scraping github activity since {:?}", since);
+/// Scrape every repository from `BACKFILL_SINCE` onwards. The cursor used by the regular
+/// scrapes is only moved forward once every repository has been ingested.
+pub fn backfill_github() -> DashResult<()> {
+    let since = CONFIG.backfill_since.ok_or_else(|| {
+        DashError::Misc(Some("BACKFILL_SINCE must be set to backfill".to_string()))
+    })?;
+    let repos = all_repos()?;
+
+    info!("Backfilling github activity since {:?}", since);
     let start_time = Utc::now().naive_utc();
-    for repo in repos {
-        match github::ingest_since(&repo, since) {
-            Ok(_) => info!("Scraped {} github successfully", repo),
-            Err(why) => error!("Unable to scrape github {}: {:?}", repo, why),
+    if backfill(
+        repos,
+        since,
+        CONFIG.github_scrape_workers,
+        github::ingest_since,
+    ) {
+        github::record_successful_update(start_time)?;
+        info!("Backfill complete");
+    } else {
+        warn!("Backfill was incomplete, the scrape cursor hasn't been moved");
+    }
+
+    Ok(())
+}
+
+/// Start tracking the FCPs which were already running in every repository, see
+/// `github::bootstrap_fcps`.
+pub fn bootstrap_github() -> DashResult<()> {
+    for repo in all_repos()? {
+        ok_or_continue!(github::bootstrap_fcps(&repo), why =>
+            error!("Unable to bootstrap the FCPs in {}: {:?}", repo, why));
+    }
+    Ok(())
+}
+
+/// Ingests each repo from `since`, returning whether all of them succeeded.
+fn backfill<F>(repos: Vec<String>, since: DateTime<Utc>, workers: usize, ingest: F) -> bool
+where
+    F: Fn(&str, DateTime<Utc>) -> DashResult<()> + Send + Sync + 'static,
+{
+    ingest_repos(repos, workers, move |repo| ingest(repo, since))
+}
+
+/// Ingest all of the repos using up to `workers` threads, returning once every worker
+/// has finished. Returns whether every repo was ingested successfully.
+fn ingest_repos<F>(repos: Vec<String>, workers: usize, ingest: F) -> bool
+where
+    F: Fn(&str) -> DashResult<()> + Send + Sync + 'static,
+{
+    let queue = Arc::new(Mutex::new(repos.into_iter().collect::<VecDeque<_>>()));
+    let ingest = Arc::new(ingest);
+    let failed = Arc::new(AtomicBool::new(false));
+
+    let handles = (0..workers.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let ingest = Arc::clone(&ingest);
+            let failed = Arc::clone(&failed);
+            thread::spawn(move || loop {
+                let repo = match queue.lock().unwrap().pop_front() {
+                    Some(repo) => repo,
+                    None => break,
+                };
+
+                match ingest(&repo) {
+                    Ok(_) => info!("Scraped {} github successfully", repo),
+                    Err(DashError::DbUnavailable(why)) => {
+                        // the rest of the queue would only wait on the pool too
+                        warn!(
+                            "No database connection to scrape {}, backing off for {:?}: {:?}",
+                            repo, DB_BACKOFF, why
+                        );
+                        failed.store(true, Ordering::SeqCst);
+                        thread::sleep(DB_BACKOFF);
+                    }
+                    Err(why) => {
+                        error!("Unable to scrape github {}: {:?}", repo, why);
+                        failed.store(true, Ordering::SeqCst);
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        ok_or!(handle.join(), why => {
+            error!("A github scraper worker panicked: {:?}", why);
+            failed.store(true, Ordering::SeqCst);
+        });
+    }
+
+    !failed.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Barrier;
+
+    #[test]
+    fn all_repos_ingested_before_returning() {
+        let repos = (0..20)
+            .map(|i| format!("org/repo{}", i))
+            .collect::<Vec<_>>();
+        let ingested = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        // the first four repos are only let through once they're all in flight
+        let started = Arc::new(AtomicUsize::new(0));
+        let first_four = Arc::new(Barrier::new(4));
+
+        {
+            let ingested = Arc::clone(&ingested);
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            let complete = ingest_repos(repos.clone(), 4, move |repo| {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                if started.fetch_add(1, Ordering::SeqCst) < 4 {
+                    first_four.wait();
+                }
+                thread::sleep(std::time::Duration::from_millis(5));
+                ingested.lock().unwrap().push(repo.to_string());
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            });
+            assert!(complete);
         }
+
+        // this is the point at which the successful update would be recorded
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+        // repos are ingested in parallel, but never more at once than asked for
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+
+        let mut ingested = ingested.lock().unwrap().clone();
+        ingested.sort();
+        let mut expected = repos;
+        expected.sort();
+        assert_eq!(ingested, expected);
+    }
+
+    #[test]
+    fn user_repos_are_included() {
+        let repos = watched_repos(
+            &["rust-lang"],
+            &["octocat".to_string()],
+            |org| Ok(vec![format!("{}/rfcs", org)]),
+            |user| Ok(vec![format!("{}/dotfiles", user)]),
+        );
+        assert_eq!(repos.unwrap(), vec!["rust-lang/rfcs", "octocat/dotfiles"]);
+    }
+
+    #[test]
+    fn backfill_starts_from_the_given_date() {
+        let since = Utc.ymd(2018, 1, 31).and_hms(0, 0, 0);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let complete = {
+            let seen = Arc::clone(&seen);
+            backfill(
+                vec!["org/repo".to_string()],
+                since,
+                1,
+                move |repo, start| {
+                    seen.lock().unwrap().push((repo.to_string(), start));
+                    Ok(())
+                },
+            )
+        };
+
+        assert!(complete);
+        assert_eq!(*seen.lock().unwrap(), vec![("org/repo".to_string(), since)]);
+        assert_eq!(github::client::since_param(since), "2018-01-31T00:00:00Z");
+    }
+
+    #[test]
+    fn cursors_advance_after_successful_ingests() {
+        let cursors = Arc::new(Mutex::new(BTreeMap::new()));
+        let old = Utc.ymd(2018, 1, 31).and_hms(0, 0, 0);
+        cursors.lock().unwrap().insert("org/ok".to_string(), old);
+        cursors
+            .lock()
+            .unwrap()
+            .insert("org/broken".to_string(), old);
+
+        let start = Utc.ymd(2018, 2, 1).and_hms(0, 0, 0);
+        let repos = vec![
+            "org/ok".to_string(),
+            "org/broken".to_string(),
+            "org/new".to_string(),
+        ];
+        let complete = {
+            let read = Arc::clone(&cursors);
+            let written = Arc::clone(&cursors);
+            ingest_from_cursors(
+                repos,
+                2,
+                start.naive_utc(),
+                move |repo| Ok(read.lock().unwrap().get(repo).cloned().unwrap_or(old)),
+                move |repo, since| {
+                    assert!(since < start);
+                    if repo.ends_with("broken") {
+                        throw!(DashError::Misc(None))
+                    }
+                    Ok(())
+                },
+                move |repo, updated| {
+                    let updated = DateTime::from_utc(updated, Utc);
+                    written.lock().unwrap().insert(repo.to_string(), updated);
+                    Ok(())
+                },
+            )
+        };
+
+        assert!(!complete);
+        let cursors = cursors.lock().unwrap();
+        assert_eq!(cursors["org/ok"], start);
+        assert_eq!(cursors["org/new"], start);
+        // the failure doesn't hold the others back, and isn't skipped over next time
+        assert_eq!(cursors["org/broken"], old);
     }
 
-    ok_or!(github::record_successful_update(start_time), why =>
-        error!("Problem recording successful update: {:?}", why));
+    #[test]
+    fn failed_backfill_is_incomplete() {
+        let repos = vec!["org/ok".to_string(), "org/broken".to_string()];
+        let complete = backfill(repos, Utc::now(), 2, |repo, _| {
+            if repo.ends_with("broken") {
+                throw!(DashError::Misc(None))
+            }
+            Ok(())
+        });
+        assert!(!complete);
+    }
 }
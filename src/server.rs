@@ -1,5 +1,14 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, RawStr, Status};
+use rocket::request::{self, FromFormValue, FromRequest};
+use rocket::{Outcome, Request, Response};
 use rocket_contrib::templates::handlebars::Handlebars;
 use std::panic::catch_unwind;
+use std::time::{Duration, Instant};
+
+use crate::config::CONFIG;
+use crate::error::DashResult;
+use crate::nag;
 
 pub fn serve() {
     // in debug builds this will force an init, good enough for testing
@@ -9,20 +18,218 @@ pub fn serve() {
         let port = std::env::var("ROCKET_PORT").unwrap_or_else(|_| String::from("OOPS"));
         info!("Attempting to launch Rocket at port {}...", &port);
         let result = catch_unwind(|| {
-            rocket::ignite()
+            let rocket = rocket::ignite()
                 .mount(
                     "/api",
                     routes![api::all_fcps, api::member_fcps, api::github_webhook],
                 )
-                .mount("/", routes![html::all_fcps, html::member_fcps])
+                .mount(
+                    "/",
+                    routes![
+                        html::all_fcps,
+                        html::member_fcps,
+                        api::preview_command,
+                        api::reconcile_proposal,
+                        api::force_close_proposal,
+                        api::resync_reviews,
+                        api::clear_reactions,
+                        api::export_fcps,
+                        api::ending_soon,
+                        api::hot_issues
+                    ],
+                )
                 .register(catchers![not_found])
-                .launch();
+                .attach(Cors::new(CONFIG.cors_allowed_origins.clone()));
+            let rocket = if CONFIG.log_requests {
+                rocket.attach(RequestLog::new(|line| info!("{}", line)))
+            } else {
+                rocket
+            };
+            rocket.launch();
         });
 
         ok_or!(result, why => error!("Rocket failed to ignite: {:?}", why));
     }
 }
 
+/// Adds CORS headers to the read-only endpoints and answers preflight requests for them.
+pub struct Cors {
+    allowed_origins: Vec<String>,
+}
+
+impl Cors {
+    pub fn new(allowed_origins: Vec<String>) -> Self { Cors { allowed_origins } }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+}
+
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS headers",
+            kind: Kind::Response,
+        }
+    }
+
+    fn on_response(&self, request: &Request<'_>, response: &mut Response<'_>) {
+        // webhooks come from GitHub, not browsers
+        let method = request.method();
+        if (method != Method::Get && method != Method::Options)
+            || request.uri().path() == "/api/github-webhook"
+        {
+            return;
+        }
+
+        let origin = match request.headers().get_one("Origin") {
+            Some(origin) if self.allows(origin) => origin,
+            _ => return,
+        };
+
+        response.set_header(Header::new(
+            "Access-Control-Allow-Origin",
+            origin.to_string(),
+        ));
+        response.set_header(Header::new("Vary", "Origin"));
+
+        if method == Method::Options {
+            // there are no OPTIONS routes, so preflight requests would otherwise 404
+            response.set_header(Header::new("Access-Control-Allow-Methods", "GET, OPTIONS"));
+            response.set_header(Header::new("Access-Control-Allow-Headers", "Content-Type"));
+            response.set_status(Status::NoContent);
+            let _ = response.take_body();
+        }
+    }
+}
+
+/// Logs the method, path, status and duration of every request. Bodies aren't logged, so
+/// webhook payloads and their signatures stay out of the logs.
+pub struct RequestLog {
+    log: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+/// When the request arrived, kept in its local cache.
+struct RequestStart(Instant);
+
+impl RequestLog {
+    pub fn new<F: Fn(&str) + Send + Sync + 'static>(log: F) -> Self {
+        RequestLog { log: Box::new(log) }
+    }
+}
+
+impl Fairing for RequestLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request logging",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    fn on_request(&self, request: &mut Request<'_>, _: &rocket::Data) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    fn on_response(&self, request: &Request<'_>, response: &mut Response<'_>) {
+        let start = request.local_cache(|| RequestStart(Instant::now()));
+        (self.log)(&request_log_line(
+            request.method(),
+            request.uri().path(),
+            response.status(),
+            start.0.elapsed(),
+        ));
+    }
+}
+
+/// e.g. `GET /api/all 200 OK (12ms)`. The query string is left out along with the body.
+fn request_log_line(method: Method, path: &str, status: Status, elapsed: Duration) -> String {
+    format!("{} {} {} ({}ms)", method, path, status, elapsed.as_millis())
+}
+
+/// A request which carries the configured `ADMIN_TOKEN`.
+pub struct Admin;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let header = request.headers().get_one("Authorization");
+        if is_admin(header, CONFIG.admin_token.as_ref().map(String::as_str)) {
+            Outcome::Success(Admin)
+        } else {
+            Outcome::Failure((Status::Forbidden, ()))
+        }
+    }
+}
+
+/// Admin endpoints are disabled entirely unless a token is configured.
+fn is_admin(authorization: Option<&str>, admin_token: Option<&str>) -> bool {
+    match (
+        authorization.and_then(|h| h.strip_prefix("token ")),
+        admin_token,
+    ) {
+        (Some(given), Some(token)) => constant_time_eq(given.as_bytes(), token.as_bytes()),
+        _ => false,
+    }
+}
+
+/// Compares every byte whatever the earlier ones were, so the time taken doesn't tell an
+/// attacker how much of a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The options for listing a member's proposals, e.g. `?sort=repo&include_resolved=true`.
+#[derive(FromForm)]
+pub struct NagQuery {
+    sort: Option<nag::NagOrder>,
+    include_resolved: Option<bool>,
+}
+
+impl NagQuery {
+    fn individual_nags(&self, username: &str) -> DashResult<nag::MemberNags> {
+        nag::individual_nags(
+            username,
+            self.sort.unwrap_or_default(),
+            self.include_resolved.unwrap_or(false),
+        )
+    }
+}
+
+/// The weighting of each reaction and of comments when ranking hot issues, e.g.
+/// `?thumbs_down=-1&comments=0.5`, and how many to list (every weight defaults to 1).
+#[derive(FromForm)]
+pub struct HotQuery {
+    thumbs_up: Option<f64>,
+    thumbs_down: Option<f64>,
+    hooray: Option<f64>,
+    comments: Option<f64>,
+    limit: Option<usize>,
+}
+
+impl HotQuery {
+    fn hot_issues(&self) -> DashResult<Vec<nag::HotIssue>> {
+        let defaults = nag::HotWeights::default();
+        let weights = nag::HotWeights {
+            thumbs_up: self.thumbs_up.unwrap_or(defaults.thumbs_up),
+            thumbs_down: self.thumbs_down.unwrap_or(defaults.thumbs_down),
+            hooray: self.hooray.unwrap_or(defaults.hooray),
+            comments: self.comments.unwrap_or(defaults.comments),
+        };
+        nag::hot_issues(&weights, self.limit.unwrap_or(50))
+    }
+}
+
+impl<'v> FromFormValue<'v> for nag::NagOrder {
+    type Error = &'v RawStr;
+
+    fn from_form_value(value: &'v RawStr) -> Result<Self, &'v RawStr> {
+        value.as_str().parse().map_err(|_| value)
+    }
+}
+
 #[catch(404)]
 fn not_found(req: &rocket::Request<'_>) -> String {
     info!("No matching routes for {} {}", req.method(), req.uri());
@@ -30,9 +237,10 @@ fn not_found(req: &rocket::Request<'_>) -> String {
 }
 
 mod html {
-    use super::TEMPLATES;
+    use super::{NagQuery, TEMPLATES};
     use crate::error::DashResult;
     use crate::nag;
+    use rocket::request::LenientForm;
     use rocket::response::content;
     use std::collections::BTreeMap;
 
@@ -86,14 +294,15 @@ mod html {
         Ok(content::Html(rendered))
     }
 
-    #[get("/fcp/<username>")]
-    pub fn member_fcps(username: String) -> DashResult<Html> {
-        let (user, fcps) = nag::individual_nags(&username)?;
+    #[get("/fcp/<username>?<query..>")]
+    pub fn member_fcps(username: String, query: LenientForm<NagQuery>) -> DashResult<Html> {
+        let nag::MemberNags(user, fcps, concerns) = query.individual_nags(&username)?;
 
         let context = json!({
             "model": {
                 "user": user,
                 "fcps": fcps,
+                "concerns": concerns,
             }
         });
 
@@ -103,26 +312,139 @@ mod html {
 }
 
 mod api {
-    use crate::domain::github::GitHubUser;
+    use super::{Admin, HotQuery, NagQuery};
+    use crate::config::CONFIG;
+    use crate::domain::github::{GitHubUser, Issue};
     use crate::error::DashResult;
-    use crate::github::webhooks::{Event, Payload};
-    use crate::github::{handle_comment, handle_issue, handle_pr};
+    use crate::github::webhooks::{Delivery, Event, Payload, WebhookError};
+    use crate::github::{handle_comment, handle_issue, handle_pr, CommandPreview};
     use crate::nag;
+    use crate::teams::SETUP;
     use crate::DB_POOL;
+    use diesel::prelude::*;
+    use rocket::http::ContentType;
+    use rocket::request::{Form, LenientForm};
+    use rocket::response::content::Content;
+    use rocket::response::{status, Stream};
     use rocket_contrib::json::Json;
 
+    #[derive(Deserialize)]
+    pub struct PreviewRequest {
+        repo: String,
+        issue_number: i32,
+        comment_body: String,
+    }
+
+    /// Renders what rfcbot would post in response to a comment, without posting anything.
+    #[post("/preview-command", data = "<request>")]
+    pub fn preview_command(
+        _admin: Admin,
+        request: Json<PreviewRequest>,
+    ) -> DashResult<Json<Vec<CommandPreview>>> {
+        use crate::domain::schema::issue;
+        let conn = &*DB_POOL.get()?;
+
+        let issue = issue::table
+            .filter(issue::repository.eq(request.repo.as_str()))
+            .filter(issue::number.eq(request.issue_number))
+            .first::<Issue>(conn)?;
+        let author = GitHubUser {
+            id: 0,
+            login: "author".to_string(),
+        };
+
+        let setup = SETUP.read().unwrap();
+        Ok(Json(crate::github::preview_command(
+            &setup,
+            &CONFIG,
+            &issue,
+            &author,
+            &request.comment_body,
+        )))
+    }
+
+    /// Every proposal, for crunching in a spreadsheet.
+    #[get("/fcp/export.csv")]
+    pub fn export_fcps() -> Content<Stream<nag::ProposalExport>> {
+        Content(
+            ContentType::CSV,
+            Stream::from(nag::ProposalExport::default()),
+        )
+    }
+
+    /// The furthest ahead `/fcp/ending-soon` looks, in days.
+    const MAX_ENDING_SOON_DAYS: i64 = 365;
+
+    /// The running FCPs which end within `days` (a week if omitted), for triage meetings.
+    #[get("/fcp/ending-soon?<days>")]
+    pub fn ending_soon(
+        days: Option<i64>,
+    ) -> Result<DashResult<Json<Vec<nag::EndingFcp>>>, status::BadRequest<String>> {
+        let days = days.unwrap_or(7);
+        if !(1..=MAX_ENDING_SOON_DAYS).contains(&days) {
+            return Err(status::BadRequest(Some(format!(
+                "days must be between 1 and {}",
+                MAX_ENDING_SOON_DAYS
+            ))));
+        }
+        Ok(nag::ending_soon(days).map(Json))
+    }
+
+    /// The open issues with the most reactions and comments, most engaged first.
+    #[get("/hot-issues?<query..>")]
+    pub fn hot_issues(query: Form<HotQuery>) -> DashResult<Json<Vec<nag::HotIssue>>> {
+        Ok(Json(query.hot_issues()?))
+    }
+
+    /// Re-reads a proposal's status comment from GitHub, for when a webhook was missed.
+    #[post("/fcp/reconcile/<id>")]
+    pub fn reconcile_proposal(_admin: Admin, id: i32) -> DashResult<()> {
+        crate::github::reconcile_proposal(id)
+    }
+
+    /// Closes a proposal which is stuck, e.g. because its status comment was deleted.
+    #[post("/fcp/force-close/<id>")]
+    pub fn force_close_proposal(_admin: Admin, id: i32) -> DashResult<()> {
+        crate::github::force_close_proposal(id)
+    }
+
+    /// Re-reads the checked boxes of every pending proposal, e.g. after their parsing changed.
+    #[post("/fcp/resync-reviews")]
+    pub fn resync_reviews(_admin: Admin) -> DashResult<Json<serde_json::Value>> {
+        let marked = crate::github::resync_reviews()?;
+        Ok(Json(json!({ "reviews_marked": marked })))
+    }
+
+    /// Deletes rfcbot's reactions from an issue's comments, e.g. before removing it from a repo.
+    #[post("/clear-reactions/<owner>/<name>/<number>")]
+    pub fn clear_reactions(
+        _admin: Admin,
+        owner: String,
+        name: String,
+        number: i32,
+    ) -> DashResult<Json<serde_json::Value>> {
+        let repo = format!("{}/{}", owner, name);
+        let deleted = crate::github::clear_bot_reactions(&repo, number)?;
+        Ok(Json(json!({ "reactions_deleted": deleted })))
+    }
+
     #[get("/all")]
     pub fn all_fcps() -> DashResult<Json<Vec<nag::FcpWithInfo>>> { Ok(Json(nag::all_fcps()?)) }
 
-    #[get("/<username>")]
+    #[get("/<username>?<query..>")]
     pub fn member_fcps(
         username: String,
-    ) -> DashResult<Json<(GitHubUser, Vec<nag::IndividualFcp>)>> {
-        Ok(Json(nag::individual_nags(&username)?))
+        query: LenientForm<NagQuery>,
+    ) -> DashResult<Json<nag::MemberNags>> {
+        Ok(Json(query.individual_nags(&username)?))
     }
 
     #[post("/github-webhook", data = "<event>")]
-    pub fn github_webhook(event: Event) -> DashResult<()> {
+    pub fn github_webhook(event: Result<Event, WebhookError>) -> DashResult<Delivery> {
+        let event = match event {
+            Ok(event) => event,
+            Err(why) => return Ok(Delivery::Rejected(why)),
+        };
         let conn = &*DB_POOL.get()?;
 
         match event.payload {
@@ -131,7 +453,13 @@ mod api {
             }
 
             Payload::PullRequest(pr_event) => {
-                handle_pr(conn, pr_event.pull_request, &pr_event.repository.full_name)?;
+                let resets_reviews = pr_event.resets_reviews(CONFIG.reset_reviews_on_push);
+                let repo = pr_event.repository.full_name;
+                handle_pr(conn, pr_event.pull_request, &repo)?;
+
+                if resets_reviews {
+                    crate::github::reset_reviews(&repo, pr_event.number)?;
+                }
             }
 
             Payload::IssueComment(comment_event) => {
@@ -153,10 +481,10 @@ mod api {
                 }
             }
 
-            Payload::Unsupported => (),
+            Payload::Unsupported => return Ok(Delivery::Ignored),
         }
 
-        Ok(())
+        Ok(Delivery::Processed)
     }
 }
 
@@ -181,3 +509,153 @@ lazy_static! {
         hbars
     };
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rocket::local::Client;
+
+    #[get("/all")]
+    fn all() -> &'static str { "[]" }
+
+    #[post("/github-webhook")]
+    fn github_webhook() -> &'static str { "" }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/api", routes![all, github_webhook])
+            .attach(Cors::new(vec!["https://dashboard.example".to_string()]));
+        Client::new(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn cors_header_for_allowed_origin() {
+        let client = client();
+        let response = client
+            .get("/api/all")
+            .header(Header::new("Origin", "https://dashboard.example"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://dashboard.example")
+        );
+    }
+
+    #[test]
+    fn no_cors_header_for_other_origins() {
+        let client = client();
+        let response = client
+            .get("/api/all")
+            .header(Header::new("Origin", "https://evil.example"))
+            .dispatch();
+
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+    }
+
+    #[test]
+    fn requests_are_logged() {
+        use std::sync::{Arc, Mutex};
+
+        let lines = Arc::new(Mutex::new(vec![]));
+        let captured = lines.clone();
+        let rocket = rocket::ignite()
+            .mount("/api", routes![all, github_webhook])
+            .attach(RequestLog::new(move |line| {
+                captured.lock().unwrap().push(line.to_string())
+            }));
+        let client = Client::new(rocket).expect("valid rocket instance");
+
+        client.get("/api/all?secret=abc").dispatch();
+        client
+            .post("/api/github-webhook")
+            .body("{\"token\": \"hunter2\"}")
+            .dispatch();
+        client.get("/nowhere").dispatch();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("GET /api/all 200 OK ("));
+        assert!(lines[1].starts_with("POST /api/github-webhook 200 OK ("));
+        assert!(lines[2].starts_with("GET /nowhere 404 Not Found ("));
+        assert!(lines
+            .iter()
+            .all(|l| !l.contains("secret") && !l.contains("hunter2")));
+    }
+
+    #[test]
+    fn ending_soon_rejects_windows_out_of_range() {
+        let rocket = rocket::ignite().mount("/api", routes![api::ending_soon]);
+        let client = Client::new(rocket).expect("valid rocket instance");
+
+        for days in &["0", "-3", "366", "9223372036854775807"] {
+            let response = client
+                .get(format!("/api/fcp/ending-soon?days={}", days))
+                .dispatch();
+            assert_eq!(response.status(), Status::BadRequest, "days={}", days);
+        }
+    }
+
+    #[test]
+    fn request_log_format() {
+        assert_eq!(
+            request_log_line(
+                Method::Get,
+                "/api/all",
+                Status::Ok,
+                Duration::from_millis(12)
+            ),
+            "GET /api/all 200 OK (12ms)"
+        );
+    }
+
+    #[test]
+    fn preflight_succeeds() {
+        let client = client();
+        let response = client
+            .options("/api/all")
+            .header(Header::new("Origin", "https://dashboard.example"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Methods"),
+            Some("GET, OPTIONS")
+        );
+    }
+
+    #[test]
+    fn webhook_unaffected() {
+        let client = client();
+        let response = client
+            .post("/api/github-webhook")
+            .header(Header::new("Origin", "https://dashboard.example"))
+            .dispatch();
+
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+    }
+
+    #[test]
+    fn admin_token_required() {
+        assert!(is_admin(Some("token s3cret"), Some("s3cret")));
+        assert!(!is_admin(Some("token wrong"), Some("s3cret")));
+        assert!(!is_admin(None, Some("s3cret")));
+        // no configured token means nobody is an admin
+        assert!(!is_admin(Some("token "), None));
+    }
+
+    #[test]
+    fn admin_token_needs_exactly_one_scheme() {
+        assert!(!is_admin(Some("s3cret"), Some("s3cret")));
+        assert!(!is_admin(Some("token token s3cret"), Some("s3cret")));
+        assert!(!is_admin(Some("token s3cret "), Some("s3cret")));
+        assert!(!is_admin(Some("token s3cre"), Some("s3cret")));
+    }
+}
@@ -6,6 +6,7 @@ use diesel::prelude::*;
 use super::DB_POOL;
 use crate::domain::github::GitHubUser;
 use crate::error::*;
+use crate::github::client::GithubClient;
 use crate::github::GH;
 
 const UPDATE_CONFIG_EVERY_MIN: u64 = 5;
@@ -27,6 +28,18 @@ pub struct RfcbotConfig {
     include_rust_team: bool,
     fcp_behaviors: BTreeMap<String, FcpBehavior>,
     teams: RfcbotTeams,
+    /// Teams defined here rather than by the team repo, e.g. the maintainers of a repository
+    /// which doesn't use the team labels.
+    #[serde(default)]
+    local_teams: TeamsMap,
+    /// The team, by label, responsible for every issue in a repository whether or not the issue
+    /// carries its label.
+    #[serde(default)]
+    repo_teams: BTreeMap<String, String>,
+    /// Who reviews proposals with a disposition, by its name, when that's narrower than
+    /// everyone on the issue's teams.
+    #[serde(default)]
+    disposition_reviewers: BTreeMap<String, DispositionReviewers>,
     #[serde(skip)]
     cached_teams: TeamsMap,
 }
@@ -37,10 +50,16 @@ impl RfcbotConfig {
 
     /// Retrive an iterator over all the (team label, team) pairs.
     pub fn teams(&self) -> impl Iterator<Item = (&TeamLabel, &Team)> {
-        match &self.teams {
+        let teams = match &self.teams {
             RfcbotTeams::Local(teams) => teams.iter(),
             RfcbotTeams::Remote { .. } => self.cached_teams.iter(),
-        }
+        };
+        teams.chain(self.local_teams.iter())
+    }
+
+    /// The label of the team responsible for all of a repository's issues, if it has one.
+    pub fn repo_team(&self, repo: &str) -> Option<&str> {
+        self.repo_teams.get(repo).map(String::as_str)
     }
 
     /// Are we allowed to auto-close issues after F-FCP in this repo?
@@ -59,6 +78,31 @@ impl RfcbotConfig {
             .unwrap_or_default()
     }
 
+    /// Does proposing an FCP count as the proposer's review? Not if any of the teams whose label
+    /// is `included` wants its members to review their own proposals explicitly.
+    pub fn initiator_auto_review<F>(&self, included: F) -> bool
+    where
+        F: Fn(&String) -> bool,
+    {
+        self.teams()
+            .filter(|&(label, _)| included(&label.0))
+            .all(|(_, team)| team.initiator_auto_review)
+    }
+
+    /// Does `login` review proposals with this disposition? Every member of the issue's teams
+    /// does, unless the deployment lists the teams and members who review it.
+    pub fn reviews_disposition(&self, disposition: &str, login: &str) -> bool {
+        let reviewers = match self.disposition_reviewers.get(disposition) {
+            Some(reviewers) => reviewers,
+            None => return true,
+        };
+        reviewers.members.iter().any(|m| m == login)
+            || self
+                .teams()
+                .filter(|(label, _)| reviewers.teams.contains(&label.0))
+                .any(|(_, team)| team.member_logins().any(|m| m == login))
+    }
+
     // Update the list of teams from external sources, if needed
     fn update(&mut self) -> Result<(), DashError> {
         #[derive(Deserialize)]
@@ -81,6 +125,15 @@ pub struct FcpBehavior {
     postpone: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DispositionReviewers {
+    /// Labels of the teams whose members review the disposition.
+    #[serde(default)]
+    teams: Vec<String>,
+    #[serde(default)]
+    members: Vec<String>,
+}
+
 // This enum definition mixes both struct-style and tuple-style variants: this is intentionally
 // done to get the wanted deserialization behavior from serde. Since this is an untagged enum from
 // serde's point of view it will deserialize a RfcbotTeams::Remote when it encounters a key named
@@ -97,8 +150,12 @@ pub struct Team {
     name: String,
     ping: String,
     members: Vec<String>,
+    #[serde(default = "default_initiator_auto_review")]
+    initiator_auto_review: bool,
 }
 
+fn default_initiator_auto_review() -> bool { true }
+
 impl Team {
     pub fn ping(&self) -> &str { &self.ping }
 
@@ -236,6 +293,33 @@ members = [
         );
     }
 
+    lazy_static! {
+        /// A team defined in `rfcbot.toml`, which looks after all of a repository's issues.
+        pub static ref LOCAL_TEAM_SETUP: RfcbotConfig = read_rfcbot_cfg_from(
+            r#"
+[fcp_behaviors]
+
+[teams]
+
+[teams.T-avengers]
+name = "The Avengers"
+ping = "marvel/avengers"
+members = ["hulk"]
+
+[local_teams.maintainers]
+name = "Gadget maintainers"
+ping = "octocat/gadget-maintainers"
+members = ["octocat", "hubot"]
+
+[repo_teams]
+"octocat/gadget" = "maintainers"
+"#
+        );
+    }
+
+    /// Parses a configuration without fetching teams or checking their members exist.
+    pub fn setup_from(input: &str) -> RfcbotConfig { read_rfcbot_cfg_from(input) }
+
     #[test]
     fn setup_parser_correct() {
         let cfg = &*TEST_SETUP;
@@ -288,6 +372,79 @@ members = [
         assert!(!cfg.should_ffcp_auto_postpone("random"));
     }
 
+    #[test]
+    fn initiator_auto_review_per_team() {
+        let cfg = read_rfcbot_cfg_from(
+            r#"
+[fcp_behaviors]
+
+[teams]
+
+[teams.T-avengers]
+name = "The Avengers"
+ping = "marvel/avengers"
+members = ["hulk"]
+
+[teams.justice-league]
+name = "Justice League of America"
+ping = "dc-comics/justice-league"
+members = ["batman"]
+initiator_auto_review = false
+"#,
+        );
+
+        assert!(cfg.initiator_auto_review(|l| l == "T-avengers"));
+        assert!(!cfg.initiator_auto_review(|l| l == "justice-league"));
+        assert!(!cfg.initiator_auto_review(|_| true));
+        assert!(TEST_SETUP.initiator_auto_review(|_| true));
+    }
+
+    #[test]
+    fn reviewers_per_disposition() {
+        let cfg = read_rfcbot_cfg_from(
+            r#"
+[fcp_behaviors]
+
+[teams]
+
+[teams.T-avengers]
+name = "The Avengers"
+ping = "marvel/avengers"
+members = ["hulk", "thor"]
+
+[teams.justice-league]
+name = "Justice League of America"
+ping = "dc-comics/justice-league"
+members = ["batman"]
+
+[disposition_reviewers.close]
+teams = ["justice-league"]
+members = ["thor"]
+"#,
+        );
+
+        assert!(cfg.reviews_disposition("merge", "hulk"));
+        assert!(cfg.reviews_disposition("close", "batman"));
+        assert!(cfg.reviews_disposition("close", "thor"));
+        assert!(!cfg.reviews_disposition("close", "hulk"));
+        assert!(TEST_SETUP.reviews_disposition("close", "hulk"));
+    }
+
+    #[test]
+    fn local_teams_and_repo_teams() {
+        let cfg = &*LOCAL_TEAM_SETUP;
+
+        assert_eq!(
+            cfg.team_labels()
+                .map(|tl| tl.0.as_str())
+                .collect::<Vec<_>>(),
+            vec!["T-avengers", "maintainers"]
+        );
+        assert_eq!(cfg.repo_team("octocat/gadget"), Some("maintainers"));
+        assert_eq!(cfg.repo_team("rust-lang/rfcs"), None);
+        assert_eq!(TEST_SETUP.repo_team("octocat/gadget"), None);
+    }
+
     #[test]
     fn cfg_file_wellformed() {
         // Just parse it and ensure that we get no panics for now!
@@ -6,11 +6,29 @@ pub(crate) fn spawn_thread<F>(name: &'static str, interval_minutes: u64, f: F) -
 where
     F: Fn() -> DashResult<()> + Send + 'static,
 {
-    let duration = Duration::from_secs(interval_minutes * 60);
+    spawn_jittered_thread(name, interval_minutes, 0, f)
+}
+
+/// The most a jittered sleep can stray from its interval, so that it never gets close to zero.
+pub(crate) const MAX_JITTER_PERCENT: u32 = 50;
+
+/// Like `spawn_thread`, but each sleep is randomly lengthened or shortened by up to
+/// `jitter_percent` (at most `MAX_JITTER_PERCENT`) of the interval.
+pub(crate) fn spawn_jittered_thread<F>(
+    name: &'static str,
+    interval_minutes: u64,
+    jitter_percent: u32,
+    f: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> DashResult<()> + Send + 'static,
+{
+    let interval = Duration::from_secs(interval_minutes * 60);
     thread::spawn(move || loop {
         if let Err(err) = f() {
             error!("the {} thread failed an iteration: {:?}", name, err);
         }
+        let duration = jittered(interval, jitter_percent, random_fraction());
         info!(
             "{} thread sleeping for {} seconds",
             name,
@@ -20,6 +38,29 @@ where
     })
 }
 
+/// Strays from `interval` by up to `jitter_percent` of it in either direction, `fraction` (in
+/// `[0, 1)`) picking where in that range.
+fn jittered(interval: Duration, jitter_percent: u32, fraction: f64) -> Duration {
+    let jitter = f64::from(jitter_percent.min(MAX_JITTER_PERCENT)) / 100.0;
+    interval.mul_f64(1.0 + jitter * (2.0 * fraction - 1.0))
+}
+
+/// A number in `[0, 1)` which differs between calls and between processes. Hashers are seeded
+/// randomly, so this doesn't need a dependency just to pick sleep lengths.
+fn random_fraction() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
 #[cfg(test)]
 pub(crate) fn setup_test_env() {
     use std::path::Path;
@@ -32,3 +73,28 @@ pub(crate) fn setup_test_env() {
         }
     });
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jitter_stays_in_bounds() {
+        let interval = Duration::from_secs(600);
+        // allowing for rounding at the very ends of the range
+        let bounds = Duration::from_millis(479_999)..Duration::from_millis(720_001);
+
+        assert_eq!(jittered(interval, 0, 0.9), interval);
+        assert_eq!(jittered(interval, 20, 0.5), interval);
+        assert!(jittered(interval, 20, 0.0) > bounds.start);
+        assert!(jittered(interval, 20, 0.0) < Duration::from_millis(480_001));
+        // no more than half the interval can be taken off
+        assert_eq!(jittered(interval, 150, 0.0), Duration::from_secs(300));
+
+        for _ in 0..100 {
+            let fraction = random_fraction();
+            assert!((0.0..1.0).contains(&fraction));
+            assert!(bounds.contains(&jittered(interval, 20, fraction)));
+        }
+    }
+}